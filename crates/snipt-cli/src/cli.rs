@@ -1,5 +1,8 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use snipt_core::SnippetKind;
 use std::env;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(
@@ -9,6 +12,13 @@ use std::env;
     long_about = "snipt allows you to define text snippets and expand them as you type."
 )]
 pub struct Snipt {
+    #[clap(
+        long,
+        global = true,
+        help = "Use a separate snippet set stored under profiles/<PROFILE>"
+    )]
+    pub profile: Option<String>,
+
     #[clap(subcommand)]
     pub commands: Option<Commands>,
 }
@@ -22,8 +32,46 @@ pub enum Commands {
 
         #[clap(long, short = 'c', help = "The snippet text")]
         snippet: String,
+
+        #[clap(
+            long,
+            short = 'g',
+            help = "Group to file the snippet under, e.g. code/rust"
+        )]
+        group: Option<String>,
+
+        #[clap(
+            long,
+            short = 'f',
+            help = "Replace an existing snippet with this shortcut"
+        )]
+        force: bool,
+
+        #[clap(
+            long,
+            short = 't',
+            help = "Trigger character for this snippet instead of the global one"
+        )]
+        trigger: Option<char>,
+
+        #[clap(
+            long,
+            short = 'k',
+            help = "What the snippet holds: text, url, command or link"
+        )]
+        kind: Option<SnippetKind>,
+
+        #[clap(long, help = "Link text of a link snippet (the shortcut by default)")]
+        label: Option<String>,
+
+        #[clap(
+            long,
+            short = 'd',
+            help = "What the snippet is for, shown in the manager"
+        )]
+        description: Option<String>,
     },
-    /// Delete a text snippet by shortcut
+    /// Move a text snippet to the trash by shortcut
     Delete {
         #[clap(long, short, help = "Shortcut of the snippet to delete")]
         shortcut: String,
@@ -36,23 +84,188 @@ pub enum Commands {
         #[clap(long, short = 'c', help = "New snippet text")]
         snippet: String,
     },
+    /// Edit a snippet's text in $VISUAL or $EDITOR
+    Edit {
+        #[clap(help = "Shortcut of the snippet to edit")]
+        shortcut: String,
+    },
     /// Add a new snippet interactively
     New,
+    /// Import snippets from a file or directory, or undo an import
+    #[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Import {
+        #[clap(subcommand)]
+        action: Option<ImportAction>,
+
+        #[clap(required = true, help = "File (or Espanso match directory) to import")]
+        path: Option<PathBuf>,
+
+        #[clap(
+            long,
+            value_enum,
+            default_value = "snipt",
+            help = "Format of the import source"
+        )]
+        from: ImportFormat,
+
+        #[clap(long, help = "Replace existing snippets that use the same shortcut")]
+        overwrite: bool,
+
+        #[clap(
+            long,
+            value_name = "COLUMN",
+            help = "CSV/TSV column holding the shortcut, by header name or 1-based position [default: 1]"
+        )]
+        shortcut_col: Option<String>,
+
+        #[clap(
+            long,
+            value_name = "COLUMN",
+            help = "CSV/TSV column holding the snippet text, by header name or 1-based position [default: 2]"
+        )]
+        body_col: Option<String>,
+
+        #[clap(
+            long,
+            value_name = "COLUMN",
+            help = "CSV/TSV column holding each snippet's tags, separated by , or ;"
+        )]
+        tag_col: Option<String>,
+    },
+    /// Share snippets as a .sniptpack file with a name, description and author
+    Pack {
+        #[clap(subcommand)]
+        action: PackAction,
+    },
+    /// Copy the snippet database to the backups directory
+    Backup,
+    /// Encrypt the snippet database with a passphrase
+    Encrypt,
+    /// Store the snippet database as plain text again
+    Decrypt,
     /// Start the daemon and API server for UI
     Start {
         #[clap(long, short, default_value = "3000", help = "Port for the API server")]
         port: u16,
+
+        #[clap(
+            long,
+            help = "Stay in the foreground, expand one snippet and exit (no API server)"
+        )]
+        once: bool,
     },
     /// Stop the snipt daemon
     Stop,
+    /// Pause snippet expansion without stopping the daemon
+    Pause,
+    /// Resume snippet expansion after a pause
+    Resume,
+    /// Make the running daemon reload its config and snippets now
+    Reload,
+    /// Restart the daemon and API server
+    Restart {
+        #[clap(long, short, default_value = "3000", help = "Port for the API server")]
+        port: u16,
+    },
     /// Check the status of the snipt daemon
     Status,
+    /// Check that the daemon's keyboard listener is still receiving events
+    DaemonHealth,
+    /// Start the daemon at login, or stop doing so
+    Autostart {
+        #[clap(subcommand)]
+        action: AutostartAction,
+    },
     /// List all the configs
-    List,
+    List {
+        #[clap(long, help = "Print all snippets as JSON instead of opening the UI")]
+        json: bool,
+    },
+    /// Move a snippet into a group, or out of its group when none is given
+    Group {
+        #[clap(help = "Shortcut of the snippet to move")]
+        shortcut: String,
+
+        #[clap(help = "Group such as email or code/rust")]
+        group: Option<String>,
+    },
+    /// Give a snippet its own trigger character, or go back to the global one when none is given
+    Trigger {
+        #[clap(help = "Shortcut of the snippet")]
+        shortcut: String,
+
+        #[clap(help = "Trigger character such as ';'")]
+        trigger: Option<char>,
+    },
+    /// Set what a snippet holds; a link expands to [label](url) in every app
+    Kind {
+        #[clap(help = "Shortcut of the snippet")]
+        shortcut: String,
+
+        #[clap(help = "text, url, command or link")]
+        kind: SnippetKind,
+
+        #[clap(long, help = "Link text of a link snippet (the shortcut by default)")]
+        label: Option<String>,
+    },
+    /// Add or remove a tag on every snippet whose shortcut or text matches
+    Tag {
+        #[clap(subcommand)]
+        action: TagAction,
+    },
+    /// List all tags with the number of snippets that have them
+    Tags,
+    /// Disable a snippet without deleting it, or enable it again
+    Toggle {
+        #[clap(help = "Shortcut of the snippet to toggle")]
+        shortcut: String,
+    },
+    /// Print the shortcuts of snippets matching a regular expression
+    Find {
+        #[clap(
+            long,
+            help = "Pattern matched against shortcuts, descriptions and snippet bodies"
+        )]
+        regex: String,
+
+        #[clap(long, help = "Only match snippet bodies")]
+        body_only: bool,
+
+        #[clap(long, help = "Print matching snippets as JSON")]
+        json: bool,
+    },
+    /// Choose a snippet with a fuzzy picker and print its shortcut
+    Pick {
+        #[clap(
+            long,
+            help = "Write the chosen shortcut to this file instead of stdout"
+        )]
+        output: Option<PathBuf>,
+
+        // Set by the quick switcher, which writes the passphrase to this pipe
+        #[clap(long, hide = true)]
+        passphrase_pipe: Option<PathBuf>,
+    },
+    /// Replace snippet references read from stdin and write the result to stdout
+    ExpandStdin,
+    /// Copy a snippet to the clipboard without opening the UI
+    Copy {
+        #[clap(help = "Shortcut of the snippet to copy")]
+        shortcut: String,
+    },
+    /// Paste a snippet into the focused application, e.g. from a global hotkey tool
+    Paste {
+        #[clap(help = "Shortcut of the snippet to paste")]
+        shortcut: String,
+    },
     /// Start just the API server (without daemon) for the Electron UI
     Serve {
         #[clap(long, short, default_value = "3000", help = "Port to listen on")]
         port: u16,
+
+        // Set by `snipt start`, which writes the passphrase to stdin
+        #[clap(long, hide = true)]
+        passphrase_stdin: bool,
     },
     /// Show the API server port
     Port,
@@ -60,7 +273,213 @@ pub enum Commands {
     ApiStatus,
     /// Diagnose API server issues
     ApiDiagnose,
+    /// Show recently expanded shortcuts
+    History {
+        #[clap(
+            long,
+            short = 'n',
+            default_value = "20",
+            help = "Number of entries to show"
+        )]
+        limit: usize,
+
+        #[clap(long, help = "Delete the recorded history")]
+        clear: bool,
+    },
+    /// Show how often each snippet was expanded
+    Stats {
+        #[clap(long, help = "Reset all usage counts (asks for confirmation)")]
+        reset: bool,
+    },
+    /// Show the end of the daemon log
+    Logs {
+        #[clap(
+            long,
+            short = 'n',
+            default_value = "50",
+            help = "Number of lines to show"
+        )]
+        lines: usize,
+
+        #[clap(long, short, help = "Keep printing new lines as they are written")]
+        follow: bool,
+    },
+    /// Show version, platform and capability details for bug reports
+    Info,
+    /// Check that the keyboard permissions snipt needs are granted
+    Permissions,
+    /// Print each key press and the character snipt reads from it, until Escape
+    DebugKeys,
+    /// Print each shortcut as it is typed and what it would expand to, without expanding it
+    Watch,
+    /// Serve expansions to the browser extension over native messaging
+    BrowserHost {
+        /// Arguments browsers pass to native hosts (extension origin, window)
+        #[clap(hide = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        caller: Vec<String>,
+    },
+    /// View or change snipt settings
+    Config {
+        #[clap(subcommand)]
+        action: ConfigAction,
+    },
+    /// List, restore or permanently remove deleted snippets
+    Trash {
+        #[clap(subcommand)]
+        action: TrashAction,
+    },
+    /// Open the config directory in the system file manager
+    OpenConfig,
+    /// Print a shell completion script, e.g. `snipt completions bash`
+    Completions {
+        #[clap(value_enum, help = "Shell to generate completions for")]
+        shell: Shell,
+    },
+    // Hidden command the completion scripts use to list shortcuts
+    #[clap(hide = true)]
+    CompleteShortcuts,
+    // Hidden command `snipt copy` leaves behind on Linux to keep the copied
+    // text, read from stdin, on the clipboard
+    #[clap(hide = true)]
+    HoldClipboard,
     // Hidden command used internally to run the daemon worker
     #[clap(hide = true)]
-    DaemonWorker,
+    DaemonWorker {
+        // Set by `snipt start`, which writes the passphrase to stdin
+        #[clap(long, hide = true)]
+        passphrase_stdin: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Show the current settings
+    Show,
+    /// Check the config file for mistakes without applying it
+    Validate,
+    /// Change a setting
+    Set {
+        #[clap(value_enum, help = "Setting to change")]
+        key: ConfigKey,
+
+        #[clap(help = "New value")]
+        value: char,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AutostartAction {
+    /// Register `snipt start` to run when you log in
+    Enable,
+    /// Remove the login registration
+    Disable,
+    /// Show whether snipt starts at login
+    Status,
+}
+
+#[derive(Subcommand)]
+pub enum ImportAction {
+    /// Reverse an import, or list the imports that can be undone
+    Undo {
+        #[clap(help = "ID printed by the import")]
+        id: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PackAction {
+    /// Write snippets to a pack file
+    Export {
+        #[clap(
+            help = "Pack file to write, e.g. team.sniptpack; .sniptpack is added when there is no extension"
+        )]
+        path: PathBuf,
+
+        #[clap(long, help = "Name of the pack")]
+        name: String,
+
+        #[clap(long, help = "What the pack is for")]
+        description: Option<String>,
+
+        #[clap(long, help = "Who made the pack")]
+        author: Option<String>,
+
+        #[clap(long, help = "Only include snippets in this group and its subgroups")]
+        group: Option<String>,
+
+        #[clap(help = "Shortcuts to include (all snippets if none are given)")]
+        shortcuts: Vec<String>,
+    },
+    /// Show a pack and merge its snippets into the database
+    Import {
+        #[clap(help = "Pack file to import")]
+        path: PathBuf,
+
+        #[clap(long, help = "Replace existing snippets that use the same shortcut")]
+        overwrite: bool,
+
+        #[clap(long, short, help = "Import without asking for confirmation")]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TrashAction {
+    /// List deleted snippets
+    List,
+    /// Put a deleted snippet back
+    Restore {
+        #[clap(help = "Shortcut of the deleted snippet")]
+        shortcut: String,
+    },
+    /// Permanently remove all deleted snippets
+    Empty,
+}
+
+#[derive(Subcommand)]
+pub enum TagAction {
+    /// Tag the snippets whose shortcut or text contains the match
+    Add {
+        #[clap(help = "Tag to add, a single word")]
+        tag: String,
+
+        #[clap(
+            long = "match",
+            help = "Text to look for in shortcuts and snippets, ignoring case"
+        )]
+        pattern: String,
+    },
+    /// Remove a tag from the snippets whose shortcut or text contains the match
+    Remove {
+        #[clap(help = "Tag to remove")]
+        tag: String,
+
+        #[clap(
+            long = "match",
+            help = "Text to look for in shortcuts and snippets, ignoring case"
+        )]
+        pattern: String,
+    },
+}
+
+/// Settings that can be changed with `snipt config set`
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ConfigKey {
+    /// Character that starts a text expansion
+    Trigger,
+    /// Character that starts an execution
+    Execute,
+}
+
+/// Sources that `snipt import` understands
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ImportFormat {
+    /// A snipt JSON database
+    Snipt,
+    /// Espanso YAML match files
+    Espanso,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Tab-separated values with a header row
+    Tsv,
 }
@@ -1,18 +1,108 @@
-use crate::cli::Commands;
-use crate::utils::display_main_ui;
+use crate::cli::{
+    AutostartAction, Commands, ConfigAction, ConfigKey, ImportAction, ImportFormat, PackAction,
+    Snipt, TagAction, TrashAction,
+};
+use crate::utils::{display_main_ui, require_terminal};
+use clap::CommandFactory;
+use clap_complete::{generate, Shell};
 use crossterm::execute;
 use crossterm::terminal::{disable_raw_mode, LeaveAlternateScreen};
-use snipt_core::{add_snippet, delete_snippet, update_snippet, Result};
-use snipt_daemon::{daemon_status, daemon_worker_entry, start_daemon, stop_daemon};
+use regex::Regex;
+use snipt_core::clipboard::clipboard_available;
+use snipt_core::config::{active_profile, ensure_config_dir, get_daemon_log_path, PASSPHRASE_ENV};
+use snipt_core::crypto::{
+    cached_passphrase, ensure_passphrase, prompt_passphrase, read_passphrase_from,
+    read_passphrase_from_stdin, set_passphrase, take_passphrase_from_env,
+};
+use snipt_core::expansion::{expand_template, text_body};
+use snipt_core::history::{clear_history, load_history, reset_usage_counts, usage_counts};
+use snipt_core::import::{
+    read_delimited, read_espanso_matches, read_snipt_json, Column, ColumnMapping,
+};
+use snipt_core::settings::{check_config_file, get_config_file_path, validate_trigger_chars};
+use snipt_core::storage::{
+    export_pack, import_pack, load_import_journal, pack_path, read_pack, rewrite_backups,
+    save_import_journal, save_snippets, save_trash, shortcut_not_found,
+};
+use snipt_core::{
+    add_entry, add_tag, apply_config, backup_database, delete_snippet, empty_trash, execute_char,
+    get_config_dir, hold_clipboard_text, import_snippets, is_daemon_running, load_config,
+    load_snippets, load_trash, remove_tag, restore_snippet, save_config, set_clipboard_text,
+    set_snippet_group, set_snippet_kind, set_snippet_trigger, tag_counts, toggle_snippet,
+    trigger_char, undo_import, update_snippet, ImportSummary, PackInfo, Result, SnippetEntry,
+    SniptError,
+};
+use snipt_daemon::{
+    autostart_status, daemon_health, daemon_status, daemon_worker_entry, debug_keys,
+    disable_autostart, enable_autostart, paste_snippet_now, pause_daemon, reload_daemon,
+    report_permissions, restart_daemon, resume_daemon, run_once, start_daemon, stop_daemon,
+    verify_permissions, watch_expansions,
+};
+use snipt_server::browser_host::run_browser_host;
 use snipt_server::server::http_server::{check_api_server_health, diagnose_api_server};
 use snipt_server::server::start_api_server;
 use snipt_server::server::utils::get_api_server_port;
-use snipt_ui::{display_snippet_manager, interactive_add, AddResult};
-use std::io::stdout;
+use snipt_ui::{display_snippet_manager, interactive_add, pick_snippet, AddResult};
+use std::fs;
+use std::io::{stdin, stdout, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
 use std::thread;
 use std::time::Duration;
 
 pub fn handle_command(command: Option<Commands>) -> Result<()> {
+    // Use the configured trigger characters everywhere, including the UI.
+    // Other commands still run on an invalid config so it can be fixed, but
+    // the daemon doesn't start on one.
+    if let Err(e) = load_config().and_then(|config| apply_config(&config)) {
+        if matches!(
+            command,
+            Some(Commands::Start { .. } | Commands::Restart { .. } | Commands::DaemonWorker { .. })
+        ) {
+            return Err(e);
+        }
+        eprintln!("Warning: {}", e);
+    }
+
+    // Completion runs on every Tab press, so it must never prompt
+    match command {
+        Some(Commands::Completions { shell }) => return handle_completions_command(shell),
+        Some(Commands::CompleteShortcuts) => return handle_complete_shortcuts_command(),
+        Some(Commands::HoldClipboard) => return handle_hold_clipboard_command(),
+        _ => {}
+    }
+
+    // The daemon and API server started by `snipt start` get the passphrase
+    // on stdin rather than in their environment, where any process of the
+    // same user could read it
+    take_passphrase_from_env();
+    if let Some(
+        Commands::DaemonWorker {
+            passphrase_stdin: true,
+            ..
+        }
+        | Commands::Serve {
+            passphrase_stdin: true,
+            ..
+        },
+    ) = &command
+    {
+        read_passphrase_from_stdin()?;
+    }
+    // The picker runs in a new terminal window, so the quick switcher hands it
+    // the passphrase through a named pipe instead
+    if let Some(Commands::Pick {
+        passphrase_pipe: Some(path),
+        ..
+    }) = &command
+    {
+        read_passphrase_from(BufReader::new(fs::File::open(path)?))?;
+    }
+
+    // Ask for the passphrase of an encrypted database up front, before any
+    // command or UI needs the snippets
+    ensure_passphrase()?;
+
     match command {
         Some(command) => handle_subcommand(command),
         None => display_main_ui(), // Default: show main UI when no command provided
@@ -21,29 +111,172 @@ pub fn handle_command(command: Option<Commands>) -> Result<()> {
 
 fn handle_subcommand(command: Commands) -> Result<()> {
     match command {
-        Commands::Add { shortcut, snippet } => {
-            add_snippet(shortcut, snippet).map(|_| println!("Snippet added successfully"))
-        }
-        Commands::Delete { shortcut } => {
-            delete_snippet(&shortcut).map(|_| println!("Snippet deleted successfully"))
+        Commands::Add {
+            shortcut,
+            snippet,
+            group,
+            force,
+            trigger,
+            kind,
+            label,
+            description,
+        } => {
+            let entry = SnippetEntry {
+                group,
+                trigger,
+                kind: kind.unwrap_or_default(),
+                label,
+                description: description.unwrap_or_default(),
+                ..SnippetEntry::new(shortcut.clone(), snippet)
+            };
+            let replaced = add_entry(entry, force)?;
+            if replaced {
+                println!("Snippet '{}' replaced", shortcut);
+            } else {
+                println!("Snippet added successfully");
+            }
+            Ok(())
         }
+        Commands::Delete { shortcut } => delete_snippet(&shortcut).map(|_| {
+            println!(
+                "Snippet moved to the trash. Restore it with 'snipt trash restore {}'",
+                shortcut
+            )
+        }),
         Commands::Update { shortcut, snippet } => {
             update_snippet(&shortcut, snippet).map(|_| println!("Snippet updated successfully"))
         }
-        Commands::Start { port } => start_daemon(port),
+        Commands::Backup => {
+            backup_database().map(|path| println!("Snippets backed up to {}", path.display()))
+        }
+        Commands::Encrypt => handle_encrypt_command(),
+        Commands::Decrypt => handle_decrypt_command(),
+        Commands::Start { once: true, .. } => run_once(),
+        Commands::Start { port, .. } => start_daemon(port),
         Commands::Stop => stop_daemon(),
+        Commands::Pause => pause_daemon(),
+        Commands::Resume => resume_daemon(),
+        Commands::Reload => reload_daemon(),
+        Commands::Restart { port } => restart_daemon(port),
         Commands::Status => daemon_status(),
+        Commands::DaemonHealth => daemon_health(),
+        Commands::Autostart { action } => match action {
+            AutostartAction::Enable => enable_autostart(),
+            AutostartAction::Disable => disable_autostart(),
+            AutostartAction::Status => autostart_status(),
+        },
         Commands::New => handle_interactive_add(),
-        Commands::List => display_snippet_manager(),
-        Commands::Serve { port } => handle_serve_command(port),
+        Commands::Import {
+            action: Some(ImportAction::Undo { id }),
+            ..
+        } => handle_import_undo_command(id),
+        Commands::Import {
+            action: None,
+            path,
+            from,
+            overwrite,
+            shortcut_col,
+            body_col,
+            tag_col,
+        } => {
+            let columns = column_mapping(shortcut_col, body_col, tag_col);
+            let path = path.ok_or_else(|| {
+                SniptError::Other(
+                    "A file or directory to import is required, e.g. 'snipt import snippets.json'"
+                        .to_string(),
+                )
+            })?;
+            handle_import_command(&path, from, overwrite, columns)
+        }
+        Commands::List { json: true } => {
+            println!("{}", serde_json::to_string_pretty(&load_snippets()?)?);
+            Ok(())
+        }
+        Commands::List { json: false } => {
+            require_terminal("Use 'snipt list --json' to print snippets instead.")?;
+            display_snippet_manager(None)
+        }
+        Commands::Serve { port, .. } => handle_serve_command(port),
         Commands::Port => handle_port_command(),
         Commands::ApiStatus => check_api_server_health(),
         Commands::ApiDiagnose => diagnose_api_server(),
-        Commands::DaemonWorker => daemon_worker_entry(),
+        Commands::History { limit, clear } => handle_history_command(limit, clear),
+        Commands::Stats { reset } => handle_stats_command(reset),
+        Commands::Config { action } => handle_config_command(action),
+        Commands::Pack { action } => handle_pack_command(action),
+        Commands::Trash { action } => handle_trash_command(action),
+        Commands::Tag { action } => handle_tag_command(action),
+        Commands::Tags => {
+            let counts = tag_counts()?;
+            if counts.is_empty() {
+                println!("No snippets are tagged yet. Add tags with 'snipt tag add <tag> --match <text>'");
+            }
+            for (tag, count) in counts {
+                println!("{:<20} {}", tag, count);
+            }
+            Ok(())
+        }
+        Commands::OpenConfig => handle_open_config_command(),
+        Commands::Completions { shell } => handle_completions_command(shell),
+        Commands::CompleteShortcuts => handle_complete_shortcuts_command(),
+        Commands::HoldClipboard => handle_hold_clipboard_command(),
+        Commands::Logs { lines, follow } => handle_logs_command(lines, follow),
+        Commands::Info => handle_info_command(),
+        Commands::Permissions => report_permissions(),
+        Commands::DebugKeys => debug_keys(),
+        Commands::Watch => watch_expansions(),
+        Commands::BrowserHost { .. } => run_browser_host(),
+        Commands::Copy { shortcut } => handle_copy_command(&shortcut),
+        Commands::Paste { shortcut } => paste_snippet_now(&shortcut),
+        Commands::Group { shortcut, group } => {
+            let message = match &group {
+                Some(group) => format!("Moved '{}' to group '{}'", shortcut, group),
+                None => format!("Removed '{}' from its group", shortcut),
+            };
+            set_snippet_group(&shortcut, group).map(|_| println!("{}", message))
+        }
+        Commands::Kind {
+            shortcut,
+            kind,
+            label,
+        } => {
+            set_snippet_kind(&shortcut, kind, label)?;
+            println!("'{}' is now a {} snippet", shortcut, kind);
+            Ok(())
+        }
+        Commands::Trigger { shortcut, trigger } => {
+            set_snippet_trigger(&shortcut, trigger)?;
+            match trigger {
+                Some(trigger) => {
+                    println!("'{}' now expands with {}{}", shortcut, trigger, shortcut)
+                }
+                None => println!(
+                    "'{}' uses the global trigger {} again",
+                    shortcut,
+                    trigger_char()
+                ),
+            }
+            Ok(())
+        }
+        Commands::Edit { shortcut } => handle_edit_command(&shortcut),
+        Commands::Toggle { shortcut } => toggle_snippet(&shortcut).map(|enabled| {
+            let state = if enabled { "enabled" } else { "disabled" };
+            println!("Snippet '{}' {}", shortcut, state)
+        }),
+        Commands::ExpandStdin => handle_expand_stdin_command(),
+        Commands::Pick { output, .. } => handle_pick_command(output.as_deref()),
+        Commands::Find {
+            regex,
+            body_only,
+            json,
+        } => handle_find_command(&regex, body_only, json),
+        Commands::DaemonWorker { .. } => daemon_worker_entry(),
     }
 }
 
 fn handle_interactive_add() -> Result<()> {
+    require_terminal("Use 'snipt add --shortcut <name> --snippet <text>' instead.")?;
+
     // First, fully reset terminal state
     let _ = disable_raw_mode();
     let _ = execute!(stdout(), LeaveAlternateScreen);
@@ -62,10 +295,10 @@ fn handle_interactive_add() -> Result<()> {
     println!("\x1B[2J\x1B[1;1H");
 
     match interactive_result {
-        AddResult::Added => {
+        AddResult::Added(shortcut) => {
             thread::sleep(Duration::from_millis(300));
-            // Launch the snippet manager UI
-            if let Err(e) = display_snippet_manager() {
+            // Launch the snippet manager UI with the new snippet selected
+            if let Err(e) = display_snippet_manager(Some(&shortcut)) {
                 eprintln!("Error displaying snippets: {}", e);
             }
         }
@@ -89,6 +322,584 @@ fn handle_interactive_add() -> Result<()> {
     display_main_ui()
 }
 
+/// Column mapping from `--shortcut-col`, `--body-col` and `--tag-col`, if
+/// any of them was given
+fn column_mapping(
+    shortcut_col: Option<String>,
+    body_col: Option<String>,
+    tag_col: Option<String>,
+) -> Option<ColumnMapping> {
+    if shortcut_col.is_none() && body_col.is_none() && tag_col.is_none() {
+        return None;
+    }
+
+    let defaults = ColumnMapping::default();
+    Some(ColumnMapping {
+        shortcut: shortcut_col.map_or(defaults.shortcut, |col| Column::parse(&col)),
+        body: body_col.map_or(defaults.body, |col| Column::parse(&col)),
+        tags: tag_col.map(|col| Column::parse(&col)),
+    })
+}
+
+fn handle_import_command(
+    path: &Path,
+    from: ImportFormat,
+    overwrite: bool,
+    columns: Option<ColumnMapping>,
+) -> Result<()> {
+    let parsed = match (from, columns) {
+        (ImportFormat::Csv, columns) => read_delimited(path, ',', &columns.unwrap_or_default())?,
+        (ImportFormat::Tsv, columns) => read_delimited(path, '\t', &columns.unwrap_or_default())?,
+        (_, Some(_)) => {
+            return Err(SniptError::Other(
+                "--shortcut-col, --body-col and --tag-col only apply to --from csv or tsv"
+                    .to_string(),
+            ))
+        }
+        (ImportFormat::Snipt, None) => read_snipt_json(path)?,
+        (ImportFormat::Espanso, None) => read_espanso_matches(path)?,
+    };
+
+    for warning in &parsed.warnings {
+        println!("Warning: {}", warning);
+    }
+
+    print_import_summary(&import_snippets(parsed.entries, overwrite)?);
+    Ok(())
+}
+
+fn print_import_summary(summary: &ImportSummary) {
+    println!(
+        "Imported {} new snippet(s), updated {}.",
+        summary.added, summary.updated
+    );
+    if !summary.skipped.is_empty() {
+        println!(
+            "Skipped {} existing shortcut(s): {} (use --overwrite to replace them)",
+            summary.skipped.len(),
+            summary.skipped.join(", ")
+        );
+    }
+    if let Some(id) = &summary.id {
+        println!("Undo with: snipt import undo {}", id);
+    }
+}
+
+fn handle_import_undo_command(id: Option<String>) -> Result<()> {
+    let Some(id) = id else {
+        let journal = load_import_journal()?;
+        if journal.is_empty() {
+            println!("There are no imports to undo.");
+        }
+        for record in journal.iter().rev() {
+            println!(
+                "{}  {} added, {} overwritten  ({})",
+                record.id,
+                record.added.len(),
+                record.replaced.len(),
+                record.formatted_imported_time()
+            );
+        }
+        return Ok(());
+    };
+
+    let summary = undo_import(&id)?;
+    println!(
+        "Undid import {}: removed {} snippet(s), restored {}.",
+        id, summary.removed, summary.restored
+    );
+    Ok(())
+}
+
+fn handle_pack_command(action: PackAction) -> Result<()> {
+    match action {
+        PackAction::Export {
+            path,
+            name,
+            description,
+            author,
+            group,
+            shortcuts,
+        } => {
+            let info = PackInfo {
+                name,
+                description: description.unwrap_or_default(),
+                author: author.unwrap_or_default(),
+            };
+            let path = pack_path(&path);
+            let count = export_pack(&path, info, &shortcuts, group.as_deref())?;
+            println!("Wrote {} snippet(s) to {}", count, path.display());
+            Ok(())
+        }
+        PackAction::Import {
+            path,
+            overwrite,
+            yes,
+        } => {
+            let pack = read_pack(&path)?;
+            println!("Pack:        {}", pack.info.name);
+            if !pack.info.description.is_empty() {
+                println!("Description: {}", pack.info.description);
+            }
+            if !pack.info.author.is_empty() {
+                println!("Author:      {}", pack.info.author);
+            }
+            let shortcuts: Vec<&str> = pack
+                .snippets
+                .iter()
+                .map(|entry| entry.shortcut.as_str())
+                .collect();
+            println!(
+                "Snippets:    {} ({})",
+                shortcuts.len(),
+                shortcuts.join(", ")
+            );
+
+            if !yes {
+                require_terminal("Pass --yes to import without confirming.")?;
+                print!("Import these snippets? [y/N] ");
+                stdout().flush()?;
+                let mut answer = String::new();
+                stdin().read_line(&mut answer)?;
+                if !answer.trim().eq_ignore_ascii_case("y") {
+                    println!("Nothing imported.");
+                    return Ok(());
+                }
+            }
+
+            print_import_summary(&import_pack(pack, overwrite)?);
+            Ok(())
+        }
+    }
+}
+
+fn handle_history_command(limit: usize, clear: bool) -> Result<()> {
+    if clear {
+        clear_history()?;
+        println!("Expansion history cleared.");
+        return Ok(());
+    }
+
+    let history = load_history()?;
+    if history.is_empty() {
+        println!("No expansions recorded yet.");
+        if !load_config()?.history_enabled {
+            println!(
+                "History is disabled. Set \"history_enabled\": true in config.json to enable it."
+            );
+        }
+        return Ok(());
+    }
+
+    let start = history.len().saturating_sub(limit);
+    for entry in &history[start..] {
+        println!(
+            "{}  {:<8} {:<10} {}",
+            entry.formatted_time(),
+            entry.kind,
+            entry.style,
+            entry.shortcut
+        );
+    }
+
+    Ok(())
+}
+
+fn handle_encrypt_command() -> Result<()> {
+    let mut config = load_config()?;
+    if config.encrypt_database {
+        println!("The snippet database is already encrypted.");
+        return Ok(());
+    }
+    let snippets = load_snippets()?;
+    let trash = load_trash()?;
+    let imports = load_import_journal()?;
+
+    let passphrase = match cached_passphrase() {
+        Some(passphrase) => passphrase,
+        None => {
+            require_terminal(&format!(
+                "Set {} to choose the passphrase non-interactively.",
+                PASSPHRASE_ENV
+            ))?;
+            let passphrase = prompt_passphrase("New passphrase: ")?;
+            if passphrase.is_empty() {
+                return Err(SniptError::Other(
+                    "The passphrase can't be empty".to_string(),
+                ));
+            }
+            if prompt_passphrase("Repeat passphrase: ")? != passphrase {
+                return Err(SniptError::Other("Passphrases don't match".to_string()));
+            }
+            passphrase
+        }
+    };
+    set_passphrase(passphrase);
+
+    config.encrypt_database = true;
+    apply_config(&config)?;
+    save_snippets(&snippets)?;
+    save_trash(&trash)?;
+    save_import_journal(&imports)?;
+    let backups = rewrite_backups()?;
+    save_config(&config)?;
+
+    println!("Snippet database encrypted.");
+    if backups > 0 {
+        println!("Encrypted {} backup(s) as well.", backups);
+    }
+    println!(
+        "snipt will ask for the passphrase, or read it from {}.",
+        PASSPHRASE_ENV
+    );
+    if is_daemon_running()?.is_some() {
+        println!("Run 'snipt restart' so the daemon can read the encrypted database.");
+    }
+    Ok(())
+}
+
+fn handle_decrypt_command() -> Result<()> {
+    let mut config = load_config()?;
+    let snippets = load_snippets()?;
+    let trash = load_trash()?;
+    let imports = load_import_journal()?;
+
+    config.encrypt_database = false;
+    apply_config(&config)?;
+    save_snippets(&snippets)?;
+    save_trash(&trash)?;
+    save_import_journal(&imports)?;
+    let backups = rewrite_backups()?;
+    save_config(&config)?;
+
+    println!("Snippet database is stored as plain text again.");
+    if backups > 0 {
+        println!("So are its {} backup(s).", backups);
+    }
+    Ok(())
+}
+
+fn handle_stats_command(reset: bool) -> Result<()> {
+    if reset {
+        print!("Reset all usage counts? [y/N] ");
+        stdout().flush()?;
+        let mut answer = String::new();
+        stdin().read_line(&mut answer)?;
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            println!("Usage counts kept.");
+            return Ok(());
+        }
+        reset_usage_counts()?;
+        println!("Usage counts reset.");
+        return Ok(());
+    }
+
+    let counts = usage_counts()?;
+    if counts.is_empty() {
+        println!("No expansions recorded yet.");
+        return Ok(());
+    }
+
+    for (shortcut, count) in counts {
+        println!("{:>6}  {}", count, shortcut);
+    }
+    Ok(())
+}
+
+fn handle_logs_command(lines: usize, follow: bool) -> Result<()> {
+    let path = get_daemon_log_path();
+    if !path.exists() {
+        println!("No daemon log found at {}", path.display());
+        println!("The log is created when the daemon is started with 'snipt start'.");
+        return Ok(());
+    }
+
+    let content = fs::read(&path)?;
+    let text = String::from_utf8_lossy(&content);
+    let all_lines: Vec<&str> = text.lines().collect();
+    for line in &all_lines[all_lines.len().saturating_sub(lines)..] {
+        println!("{}", line);
+    }
+
+    if !follow {
+        return Ok(());
+    }
+
+    // Poll for appended output; the log is truncated whenever the daemon restarts
+    let mut position = content.len() as u64;
+    loop {
+        thread::sleep(Duration::from_millis(500));
+
+        let len = match fs::metadata(&path) {
+            Ok(metadata) => metadata.len(),
+            Err(_) => continue,
+        };
+        if len < position {
+            position = 0;
+        }
+        if len == position {
+            continue;
+        }
+
+        let mut file = fs::File::open(&path)?;
+        file.seek(SeekFrom::Start(position))?;
+        let mut new_content = Vec::new();
+        file.read_to_end(&mut new_content)?;
+        position += new_content.len() as u64;
+
+        let mut out = stdout();
+        out.write_all(&new_content)?;
+        out.flush()?;
+    }
+}
+
+fn handle_tag_command(action: TagAction) -> Result<()> {
+    match action {
+        TagAction::Add { tag, pattern } => {
+            let changed = add_tag(&tag, &pattern)?;
+            println!("Tagged {} snippet(s) with '{}'", changed, tag.trim());
+        }
+        TagAction::Remove { tag, pattern } => {
+            let changed = remove_tag(&tag, &pattern)?;
+            println!("Removed '{}' from {} snippet(s)", tag.trim(), changed);
+        }
+    }
+    Ok(())
+}
+
+fn handle_trash_command(action: TrashAction) -> Result<()> {
+    match action {
+        TrashAction::List => {
+            let trash = load_trash()?;
+            if trash.is_empty() {
+                println!("The trash is empty.");
+                return Ok(());
+            }
+            for trashed in trash.iter().rev() {
+                let first_line = trashed.entry.snippet.lines().next().unwrap_or("");
+                println!(
+                    "{:<10} {:<20} {}",
+                    trashed.formatted_deleted_time(),
+                    trashed.entry.shortcut,
+                    first_line.chars().take(60).collect::<String>()
+                );
+            }
+            Ok(())
+        }
+        TrashAction::Restore { shortcut } => {
+            restore_snippet(&shortcut)?;
+            println!("Snippet '{}' restored", shortcut);
+            Ok(())
+        }
+        TrashAction::Empty => {
+            let removed = empty_trash()?;
+            println!("Permanently removed {} snippet(s) from the trash", removed);
+            Ok(())
+        }
+    }
+}
+
+fn handle_config_command(action: ConfigAction) -> Result<()> {
+    match action {
+        ConfigAction::Show => {
+            let config = load_config()?;
+            println!("Config file:      {}", get_config_file_path().display());
+            println!("Trigger char:     {}", config.trigger_char);
+            println!("Execute char:     {}", config.execute_char);
+            println!("Keyboard layout:  {:?}", config.keyboard_layout);
+            println!("Expansion method: {:?}", config.expansion_method);
+            if config.whole_word {
+                println!("Whole word only:  yes");
+            }
+            if config.escaped_triggers {
+                println!("Escaped triggers: yes");
+            }
+            if !config.layout_overrides.is_empty() {
+                println!("Layout overrides: {}", config.layout_overrides.len());
+            }
+            if !config.app_blocklist.is_empty() {
+                println!("Blocked apps:     {}", config.app_blocklist.join(", "));
+            }
+            if !config.app_allowlist.is_empty() {
+                println!("Allowed apps:     {}", config.app_allowlist.join(", "));
+            }
+            Ok(())
+        }
+        ConfigAction::Validate => {
+            let path = get_config_file_path();
+            if !path.exists() {
+                println!("No config file at {}; using the defaults.", path.display());
+                return Ok(());
+            }
+            for key in check_config_file()? {
+                println!("Warning: unknown setting '{}' is ignored", key);
+            }
+            println!("{} is valid.", path.display());
+            Ok(())
+        }
+        ConfigAction::Set { key, value } => {
+            let mut config = load_config()?;
+            match key {
+                ConfigKey::Trigger => config.trigger_char = value,
+                ConfigKey::Execute => config.execute_char = value,
+            }
+            validate_trigger_chars(config.trigger_char, config.execute_char)?;
+            save_config(&config)?;
+
+            println!(
+                "Trigger char is now '{}', execute char is now '{}'.",
+                config.trigger_char, config.execute_char
+            );
+            println!("Existing snippets keep working with the new characters.");
+            if is_daemon_running()?.is_some() {
+                println!("The running daemon will pick up the change automatically.");
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Editor command from $VISUAL or $EDITOR, which may include arguments
+/// such as `code --wait`. It is split the way a shell would, so a quoted
+/// path with spaces stays one word.
+fn editor_command() -> Vec<String> {
+    let configured = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .map(|value| {
+            // Backslashes separate directories on Windows rather than escape
+            let value = if cfg!(windows) {
+                value.replace('\\', "\\\\")
+            } else {
+                value
+            };
+            // Unbalanced quotes: run the whole value as the program
+            shlex::split(&value).unwrap_or_else(|| vec![value.trim().to_string()])
+        })
+        .find(|command| !command.is_empty() && !command[0].is_empty());
+
+    configured.unwrap_or_else(|| {
+        let default = if cfg!(target_os = "windows") {
+            "notepad"
+        } else {
+            "vi"
+        };
+        vec![default.to_string()]
+    })
+}
+
+fn handle_edit_command(shortcut: &str) -> Result<()> {
+    require_terminal("Use 'snipt update' to change a snippet without an editor.")?;
+    let snippets = load_snippets()?;
+    let entry = snippets
+        .iter()
+        .find(|entry| entry.shortcut == shortcut)
+        .ok_or_else(|| shortcut_not_found(&snippets, shortcut))?;
+
+    let mut file = tempfile::Builder::new()
+        .prefix("snipt-")
+        .suffix(".txt")
+        .tempfile()?;
+    file.write_all(entry.snippet.as_bytes())?;
+    file.flush()?;
+
+    let editor = editor_command();
+    let status = Command::new(&editor[0])
+        .args(&editor[1..])
+        .arg(file.path())
+        .status()
+        .map_err(|e| {
+            SniptError::Other(format!("Failed to start the editor '{}': {}", editor[0], e))
+        })?;
+    if !status.success() {
+        return Err(SniptError::Other(format!(
+            "The editor exited with {}; snippet '{}' was not changed",
+            status, shortcut
+        )));
+    }
+
+    let mut edited = fs::read_to_string(file.path())?;
+    // Most editors end the file with a newline the snippet didn't have
+    if !entry.snippet.ends_with('\n') && edited.ends_with('\n') {
+        edited.pop();
+        if edited.ends_with('\r') {
+            edited.pop();
+        }
+    }
+
+    if edited == entry.snippet {
+        println!("No changes to snippet '{}'", shortcut);
+        return Ok(());
+    }
+    update_snippet(shortcut, edited)?;
+    println!("Snippet '{}' updated", shortcut);
+    Ok(())
+}
+
+fn handle_open_config_command() -> Result<()> {
+    let config_dir = ensure_config_dir()?;
+    println!("Config directory: {}", config_dir.display());
+
+    #[cfg(target_os = "macos")]
+    let opener = "open";
+    #[cfg(target_os = "windows")]
+    let opener = "explorer";
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let opener = "xdg-open";
+
+    Command::new(opener)
+        .arg(&config_dir)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| {
+            SniptError::Other(format!(
+                "Failed to open the file manager with '{}': {}",
+                opener, e
+            ))
+        })?;
+    Ok(())
+}
+
+fn handle_info_command() -> Result<()> {
+    let yes_no = |value: bool| if value { "yes" } else { "no" };
+
+    println!("snipt {}", env!("CARGO_PKG_VERSION"));
+    println!(
+        "Platform:         {}/{}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    #[cfg(target_os = "linux")]
+    {
+        let session = if std::env::var("WAYLAND_DISPLAY").is_ok() {
+            "Wayland"
+        } else if std::env::var("DISPLAY").is_ok() {
+            "X11"
+        } else {
+            "none detected"
+        };
+        println!("Display server:   {}", session);
+    }
+    println!("Clipboard:        {}", yes_no(clipboard_available()));
+    println!("Input permission: {}", yes_no(verify_permissions()));
+    println!(
+        "Profile:          {}",
+        active_profile().unwrap_or_else(|| "default".to_string())
+    );
+    println!("Config dir:       {}", get_config_dir().display());
+    println!("Trigger char:     {}", trigger_char());
+    println!("Execute char:     {}", execute_char());
+    println!(
+        "Daemon:           {}",
+        match is_daemon_running()? {
+            Some(pid) => format!("running (PID {})", pid),
+            None => "not running".to_string(),
+        }
+    );
+    Ok(())
+}
+
 fn handle_serve_command(port: u16) -> Result<()> {
     // Start API server only in a properly configured runtime
     let runtime = tokio::runtime::Builder::new_multi_thread()
@@ -103,6 +914,162 @@ fn handle_serve_command(port: u16) -> Result<()> {
     })
 }
 
+fn handle_copy_command(shortcut: &str) -> Result<()> {
+    let snippets = load_snippets()?;
+    let entry = snippets
+        .iter()
+        .find(|entry| entry.shortcut == shortcut)
+        .ok_or_else(|| shortcut_not_found(&snippets, shortcut))?;
+
+    let text = text_body(entry);
+    set_clipboard_text(&text).map_err(|e| match e {
+        SniptError::Clipboard(msg) if !clipboard_available() => SniptError::Clipboard(format!(
+            "{}. No clipboard is available; snipt copy needs a graphical session",
+            msg
+        )),
+        other => other,
+    })?;
+
+    // On X11 and Wayland the clipboard empties when this process exits, so
+    // leave a process behind that serves the text until something replaces it
+    #[cfg(target_os = "linux")]
+    spawn_clipboard_holder(&text)?;
+
+    println!("Copied '{}' to the clipboard", shortcut);
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn spawn_clipboard_holder(text: &str) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+
+    let mut holder = Command::new(std::env::current_exe()?)
+        .arg("hold-clipboard")
+        .env_remove(PASSPHRASE_ENV)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        // Its own process group keeps Ctrl+C in this terminal away from it
+        .process_group(0)
+        .spawn()?;
+    if let Some(mut input) = holder.stdin.take() {
+        input.write_all(text.as_bytes())?;
+    }
+    Ok(())
+}
+
+fn handle_hold_clipboard_command() -> Result<()> {
+    let mut text = String::new();
+    stdin().read_to_string(&mut text)?;
+    hold_clipboard_text(&text)
+}
+
+fn handle_pick_command(output: Option<&Path>) -> Result<()> {
+    require_terminal("Use 'snipt find' to search snippets instead.")?;
+
+    let shortcut = pick_snippet()?;
+    match output {
+        // An empty file tells the daemon the picker was cancelled
+        Some(path) => fs::write(path, shortcut.unwrap_or_default())?,
+        None => {
+            if let Some(shortcut) = shortcut {
+                println!("{}", shortcut);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Bash completion of shortcut names for the commands that take one. Wraps
+/// the `_snipt` function generated by clap for everything else.
+const BASH_SHORTCUT_COMPLETION: &str = r#"
+_snipt_with_shortcuts() {
+    local i cmd=""
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        case "${COMP_WORDS[i]}" in
+            --profile) ((i++)) ;;
+            -*) ;;
+            *) cmd="${COMP_WORDS[i]}"; break ;;
+        esac
+    done
+
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+    case "$cmd" in
+        delete|update)
+            [[ "$prev" == "--shortcut" || "$prev" == "-s" ]] || { _snipt "$@"; return; } ;;
+        copy|edit|paste|toggle|group|trigger|kind)
+            [[ "$prev" == "$cmd" ]] || { _snipt "$@"; return; } ;;
+        *)
+            _snipt "$@"; return ;;
+    esac
+    COMPREPLY=($(compgen -W "$(snipt complete-shortcuts 2>/dev/null)" -- "${COMP_WORDS[COMP_CWORD]}"))
+}
+complete -F _snipt_with_shortcuts -o bashdefault -o default snipt
+"#;
+
+/// Fish completion of shortcut names for the commands that take one
+const FISH_SHORTCUT_COMPLETION: &str = r#"
+complete -c snipt -n "__fish_seen_subcommand_from copy edit paste toggle group trigger kind" -f -a "(snipt complete-shortcuts)"
+complete -c snipt -n "__fish_seen_subcommand_from delete update" -s s -l shortcut -x -a "(snipt complete-shortcuts)"
+"#;
+
+fn handle_completions_command(shell: Shell) -> Result<()> {
+    let mut out = stdout();
+    generate(shell, &mut Snipt::command(), "snipt", &mut out);
+    match shell {
+        Shell::Bash => out.write_all(BASH_SHORTCUT_COMPLETION.as_bytes())?,
+        Shell::Fish => out.write_all(FISH_SHORTCUT_COMPLETION.as_bytes())?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Print every shortcut on its own line. An encrypted database without a
+/// cached passphrase just gives no suggestions.
+fn handle_complete_shortcuts_command() -> Result<()> {
+    if let Ok(snippets) = load_snippets() {
+        for entry in snippets {
+            println!("{}", entry.shortcut);
+        }
+    }
+    Ok(())
+}
+
+fn handle_expand_stdin_command() -> Result<()> {
+    let mut input = String::new();
+    stdin().read_to_string(&mut input)?;
+
+    let output = expand_template(&input, &load_snippets()?)?;
+    let mut out = stdout();
+    out.write_all(output.as_bytes())?;
+    out.flush()?;
+    Ok(())
+}
+
+fn handle_find_command(pattern: &str, body_only: bool, json: bool) -> Result<()> {
+    let regex = Regex::new(pattern)
+        .map_err(|e| SniptError::Other(format!("Invalid regex '{}': {}", pattern, e)))?;
+
+    let matches: Vec<SnippetEntry> = load_snippets()?
+        .into_iter()
+        .filter(|entry| {
+            regex.is_match(&entry.snippet)
+                || (!body_only
+                    && (regex.is_match(&entry.shortcut) || regex.is_match(&entry.description)))
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&matches)?);
+    } else {
+        for entry in &matches {
+            println!("{}", entry.shortcut);
+        }
+    }
+
+    Ok(())
+}
+
 fn handle_port_command() -> Result<()> {
     match get_api_server_port() {
         Ok(port) => {
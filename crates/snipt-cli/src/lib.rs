@@ -5,6 +5,9 @@ pub mod utils;
 use clap::Parser;
 use cli::Snipt;
 use commands::handle_command;
+use snipt_core::config::{
+    check_config_dir_writable, get_config_dir, set_active_profile, CONFIG_DIR_ENV,
+};
 use std::env;
 use std::process;
 
@@ -20,10 +23,24 @@ pub fn run_main() {
     }
 
     let args = Snipt::parse();
+    if let Some(profile) = &args.profile {
+        if let Err(e) = set_active_profile(profile) {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    }
+
     let result = handle_command(args.commands);
 
     if let Err(e) = result {
         eprintln!("Error: {}", e);
+        if check_config_dir_writable().is_err() {
+            eprintln!(
+                "Hint: {} is not writable. Set {} to use a different directory.",
+                get_config_dir().display(),
+                CONFIG_DIR_ENV
+            );
+        }
         process::exit(1);
     }
 }
@@ -1,10 +1,27 @@
 use crossterm::execute;
 use crossterm::terminal::{disable_raw_mode, LeaveAlternateScreen};
-use snipt_core::{is_daemon_running, Result};
+use snipt_core::{is_daemon_running, Result, SniptError};
 use snipt_ui::display_snipt_dashboard;
-use std::io::stdout;
+use std::env;
+use std::io::{stdin, stdout, IsTerminal};
+
+/// Fail with a helpful message when there is no interactive terminal to draw
+/// a UI on (e.g. when piped, run from CI or over SSH without a usable TERM)
+pub fn require_terminal(alternative: &str) -> Result<()> {
+    let dumb_term = env::var("TERM").map(|term| term == "dumb").unwrap_or(false);
+    if stdin().is_terminal() && stdout().is_terminal() && !dumb_term {
+        return Ok(());
+    }
+
+    Err(SniptError::Other(format!(
+        "This command needs an interactive terminal. {}",
+        alternative
+    )))
+}
 
 pub fn display_main_ui() -> Result<()> {
+    require_terminal("Run 'snipt --help' to see the commands that work without one.")?;
+
     // First check if daemon is running
     let daemon_status = is_daemon_running()?;
 
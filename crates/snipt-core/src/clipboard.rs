@@ -17,6 +17,29 @@ pub fn set_clipboard_text(text: &str) -> Result<()> {
         .map_err(|e| SniptError::Clipboard(e.to_string()))
 }
 
+/// Set the clipboard content as text and keep serving it until another
+/// program takes the clipboard over. On X11 and Wayland the text lives only as
+/// long as the process that set it, so this blocks there; elsewhere it returns
+/// right away.
+pub fn hold_clipboard_text(text: &str) -> Result<()> {
+    let mut clipboard = Clipboard::new().map_err(|e| SniptError::Clipboard(e.to_string()))?;
+
+    #[cfg(target_os = "linux")]
+    let result = {
+        use arboard::SetExtLinux;
+        clipboard.set().wait().text(text)
+    };
+    #[cfg(not(target_os = "linux"))]
+    let result = clipboard.set_text(text);
+
+    result.map_err(|e| SniptError::Clipboard(e.to_string()))
+}
+
+/// Check if the system clipboard can be opened at all (fails on headless systems)
+pub fn clipboard_available() -> bool {
+    Clipboard::new().is_ok()
+}
+
 /// Check if the clipboard contains text
 pub fn has_clipboard_text() -> bool {
     if let Ok(mut clipboard) = Clipboard::new() {
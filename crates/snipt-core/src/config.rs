@@ -1,25 +1,179 @@
-use crate::error::Result;
+use crate::error::{Result, SniptError};
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Once, RwLock};
 
 pub const SPECIAL_CHAR: char = ':';
 pub const PID_FILENAME: &str = "snipt-daemon.pid";
 pub const DB_FILENAME: &str = "snipt.json";
 pub const EXECUTE_CHAR: char = '!';
+pub const API_PORT_FILENAME: &str = "api_port.txt";
+pub const CONFIG_FILENAME: &str = "config.json";
+pub const CONFIG_TOML_FILENAME: &str = "config.toml";
+pub const HISTORY_FILENAME: &str = "history.jsonl";
+pub const USAGE_FILENAME: &str = "usage.json";
+pub const PAUSE_FILENAME: &str = "snipt.paused";
+pub const DAEMON_LOG_FILENAME: &str = "daemon_log.txt";
+pub const PICKER_SELECTION_FILENAME: &str = "picker_selection.txt";
+pub const PICKER_PASSPHRASE_FILENAME: &str = "picker_passphrase.fifo";
+pub const HEARTBEAT_FILENAME: &str = "daemon_heartbeat.txt";
+pub const METRICS_FILENAME: &str = "daemon_metrics.json";
+pub const TRASH_FILENAME: &str = "trash.json";
+pub const IMPORTS_FILENAME: &str = "imports.json";
+pub const CONTROL_SOCKET_FILENAME: &str = "snipt.sock";
+pub const SEED_FILENAME: &str = "seed.json";
+pub const SEEDED_FILENAME: &str = "snipt.seeded";
+/// Environment variable that overrides the configuration directory
+pub const CONFIG_DIR_ENV: &str = "SNIPT_CONFIG_DIR";
+/// Environment variable holding the active profile name. `--profile` sets it
+/// so that the daemon and API server processes inherit the profile.
+pub const PROFILE_ENV: &str = "SNIPT_PROFILE";
+/// Environment variable setting the daemon's log level, e.g. `debug` or
+/// `snipt_daemon=trace` (defaults to `info`)
+pub const LOG_ENV: &str = "SNIPT_LOG";
+/// Environment variable holding the passphrase of an encrypted database
+pub const PASSPHRASE_ENV: &str = "SNIPT_PASSPHRASE";
+/// Environment variable pointing at the seed file to import on first run,
+/// instead of `seed.json` in the config directory
+pub const SEED_ENV: &str = "SNIPT_SEED";
+pub const PROFILES_DIRNAME: &str = "profiles";
+pub const BACKUPS_DIRNAME: &str = "backups";
+
+/// Trigger and execute characters in effect for this process
+static ACTIVE_TRIGGER_CHARS: RwLock<(char, char)> = RwLock::new((SPECIAL_CHAR, EXECUTE_CHAR));
+
+/// Character that starts a text expansion (`:` unless configured otherwise)
+pub fn trigger_char() -> char {
+    ACTIVE_TRIGGER_CHARS
+        .read()
+        .map(|chars| chars.0)
+        .unwrap_or(SPECIAL_CHAR)
+}
+
+/// Character that starts an execution (`!` unless configured otherwise)
+pub fn execute_char() -> char {
+    ACTIVE_TRIGGER_CHARS
+        .read()
+        .map(|chars| chars.1)
+        .unwrap_or(EXECUTE_CHAR)
+}
+
+/// Change the trigger and execute characters for this process
+pub fn set_trigger_chars(trigger: char, execute: char) {
+    if let Ok(mut chars) = ACTIVE_TRIGGER_CHARS.write() {
+        *chars = (trigger, execute);
+    }
+}
+
+/// Directory from `$SNIPT_CONFIG_DIR`, made absolute. It is created by
+/// `ensure_config_dir` like the default one. Returns `None` when the variable
+/// is unset or points at something other than a directory.
+pub fn config_dir_override() -> Option<PathBuf> {
+    let dir = PathBuf::from(env::var_os(CONFIG_DIR_ENV).filter(|dir| !dir.is_empty())?);
+
+    // Resolve relative paths now so every process in this profile agrees on the location
+    let dir = if dir.is_absolute() {
+        dir
+    } else {
+        env::current_dir().ok()?.join(dir)
+    };
+
+    if dir.exists() && !dir.is_dir() {
+        static WARN_ONCE: Once = Once::new();
+        WARN_ONCE.call_once(|| {
+            eprintln!(
+                "Warning: {} ({}) is not a directory, using the default config directory",
+                CONFIG_DIR_ENV,
+                dir.display()
+            );
+        });
+        return None;
+    }
+
+    Some(dir)
+}
+
+/// Get the root snipt directory (`$SNIPT_CONFIG_DIR`, or `~/.snipt`) that
+/// holds the default profile and the `profiles` directory
+pub fn get_root_config_dir() -> PathBuf {
+    if let Some(dir) = config_dir_override() {
+        return dir;
+    }
 
-/// Get the snipt configuration directory
-pub fn get_config_dir() -> PathBuf {
     env::var("HOME")
         .map(|home| PathBuf::from(home).join(".snipt"))
         .unwrap_or_else(|_| PathBuf::from(".snipt"))
 }
 
+/// Check that a profile name is usable as a directory name
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(SniptError::InvalidConfig(format!(
+            "Invalid profile name '{}'. Use letters, digits, '-' and '_' only",
+            name
+        )))
+    }
+}
+
+/// Name of the active profile, or `None` for the default profile
+pub fn active_profile() -> Option<String> {
+    let name = env::var(PROFILE_ENV).ok().filter(|name| !name.is_empty())?;
+    if validate_profile_name(&name).is_err() {
+        static WARN_ONCE: Once = Once::new();
+        WARN_ONCE.call_once(|| {
+            eprintln!(
+                "Warning: {} ({}) is not a valid profile name, using the default profile",
+                PROFILE_ENV, name
+            );
+        });
+        return None;
+    }
+    Some(name)
+}
+
+/// Switch this process, and any daemon or server it starts, to a named profile
+pub fn set_active_profile(name: &str) -> Result<()> {
+    validate_profile_name(name)?;
+    env::set_var(PROFILE_ENV, name);
+    Ok(())
+}
+
+/// Get the snipt configuration directory for the active profile.
+/// Every snipt file path is derived from this, so the CLI, daemon and API
+/// server all use the same profile.
+pub fn get_config_dir() -> PathBuf {
+    let root = get_root_config_dir();
+    match active_profile() {
+        Some(profile) => root.join(PROFILES_DIRNAME).join(profile),
+        None => root,
+    }
+}
+
+/// Describe a failed filesystem operation on one of snipt's files, including
+/// the path and the OS error
+pub fn storage_error(action: &str, path: &Path, err: io::Error) -> SniptError {
+    SniptError::Other(format!(
+        "Failed to {} at {}: {}",
+        action,
+        path.display(),
+        err
+    ))
+}
+
 /// Ensure the configuration directory exists
 pub fn ensure_config_dir() -> Result<PathBuf> {
     let config_dir = get_config_dir();
     if !config_dir.exists() {
-        fs::create_dir_all(&config_dir)?;
+        fs::create_dir_all(&config_dir)
+            .map_err(|e| storage_error("create the config directory", &config_dir, e))?;
     }
 
     let db_path = get_db_file_path();
@@ -33,7 +187,26 @@ pub fn ensure_config_dir() -> Result<PathBuf> {
 /// Create an empty config file at the specified path
 pub fn create_empty_file(path: &PathBuf, description: &str) -> Result<()> {
     println!("Creating {} at: {}", description, path.display());
-    fs::write(path, "")?;
+    fs::write(path, "").map_err(|e| storage_error(&format!("create the {}", description), path, e))
+}
+
+/// Check, without changing anything, that the configuration directory is a
+/// writable directory. Before it exists, the nearest existing directory it
+/// would be created in is checked instead.
+pub fn check_config_dir_writable() -> Result<()> {
+    let config_dir = get_config_dir();
+    let existing = config_dir
+        .ancestors()
+        .find(|dir| dir.exists())
+        .unwrap_or(&config_dir);
+    let metadata = fs::metadata(existing)
+        .map_err(|e| storage_error("read the config directory", existing, e))?;
+    if !metadata.is_dir() || metadata.permissions().readonly() {
+        return Err(SniptError::Other(format!(
+            "{} is not a writable directory",
+            existing.display()
+        )));
+    }
     Ok(())
 }
 
@@ -47,6 +220,94 @@ pub fn get_db_file_path() -> PathBuf {
     get_config_dir().join(DB_FILENAME)
 }
 
+/// Get the path to the file holding deleted snippets
+pub fn get_trash_file_path() -> PathBuf {
+    get_config_dir().join(TRASH_FILENAME)
+}
+
+/// Get the path to the journal of recent imports, used to undo them
+pub fn get_import_journal_path() -> PathBuf {
+    get_config_dir().join(IMPORTS_FILENAME)
+}
+
+/// Get the directory holding database backups
+pub fn get_backups_dir() -> PathBuf {
+    get_config_dir().join(BACKUPS_DIRNAME)
+}
+
+/// Get the path to the file holding the API server port
+pub fn get_api_port_file_path() -> PathBuf {
+    get_config_dir().join(API_PORT_FILENAME)
+}
+
+/// Get the path to the file the detached daemon writes its output to
+pub fn get_daemon_log_path() -> PathBuf {
+    get_config_dir().join(DAEMON_LOG_FILENAME)
+}
+
+/// Get the path to the file the daemon updates while its keyboard listener works
+pub fn get_heartbeat_file_path() -> PathBuf {
+    get_config_dir().join(HEARTBEAT_FILENAME)
+}
+
+/// Get the path to the file the daemon shares its counters in
+pub fn get_metrics_file_path() -> PathBuf {
+    get_config_dir().join(METRICS_FILENAME)
+}
+
+/// Get the path to the socket the daemon takes control commands on
+pub fn get_control_socket_path() -> PathBuf {
+    get_config_dir().join(CONTROL_SOCKET_FILENAME)
+}
+
+/// Get the path to the file the snippet picker writes the chosen shortcut to
+pub fn get_picker_selection_path() -> PathBuf {
+    get_config_dir().join(PICKER_SELECTION_FILENAME)
+}
+
+/// Get the path to the named pipe the daemon hands the picker the passphrase
+/// through
+pub fn get_picker_passphrase_path() -> PathBuf {
+    get_config_dir().join(PICKER_PASSPHRASE_FILENAME)
+}
+
+/// Get the path to the snippets imported on first run
+pub fn get_seed_file_path() -> PathBuf {
+    env::var_os(SEED_ENV)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| get_config_dir().join(SEED_FILENAME))
+}
+
+/// Get the path to the file recording that the seed file was imported
+pub fn get_seeded_marker_path() -> PathBuf {
+    get_config_dir().join(SEEDED_FILENAME)
+}
+
+/// Get the path to the file whose presence pauses expansion
+pub fn get_pause_file_path() -> PathBuf {
+    get_config_dir().join(PAUSE_FILENAME)
+}
+
+/// Check if expansion has been paused with `snipt pause`
+pub fn is_paused() -> bool {
+    get_pause_file_path().exists()
+}
+
+/// Pause or resume expansion. The state is kept on disk so it survives daemon restarts.
+pub fn set_paused(paused: bool) -> Result<()> {
+    let pause_file = get_pause_file_path();
+    if paused {
+        if !pause_file.exists() {
+            fs::create_dir_all(get_config_dir())?;
+            fs::write(&pause_file, "")?;
+        }
+    } else if pause_file.exists() {
+        fs::remove_file(&pause_file)?;
+    }
+    Ok(())
+}
+
 /// Check if the database file exists
 pub fn db_file_exists() -> bool {
     get_db_file_path().exists()
@@ -0,0 +1,221 @@
+//! Optional passphrase encryption of the snippet database.
+//!
+//! An encrypted database is a JSON document holding the scrypt parameters,
+//! salt and nonce next to the ChaCha20-Poly1305 ciphertext of the regular
+//! database contents. The passphrase comes from `SNIPT_PASSPHRASE` or is
+//! asked for once per process.
+
+use crate::config::{get_db_file_path, PASSPHRASE_ENV};
+use crate::error::{Result, SniptError};
+use crate::settings::current_config;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::ChaCha20Poly1305;
+use scrypt::Params;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io::{stdin, BufRead, IsTerminal};
+use std::sync::RwLock;
+
+/// scrypt cost for newly encrypted files (2^15 iterations, about 32 MiB)
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedDatabase {
+    encrypted: EncryptedData,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedData {
+    kdf: String,
+    log_n: u8,
+    r: u32,
+    p: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Passphrase entered or read from the environment in this process
+static PASSPHRASE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Check whether file contents are an encrypted database
+pub fn is_encrypted(content: &str) -> bool {
+    serde_json::from_str::<EncryptedDatabase>(content).is_ok()
+}
+
+fn encryption_error(msg: impl Into<String>) -> SniptError {
+    SniptError::Encryption(msg.into())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], log_n: u8, r: u32, p: u32) -> Result<[u8; 32]> {
+    let params = Params::new(log_n, r, p, 32)
+        .map_err(|e| encryption_error(format!("Invalid key parameters: {}", e)))?;
+    let mut key = [0u8; 32];
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+        .map_err(|e| encryption_error(format!("Failed to derive the key: {}", e)))?;
+    Ok(key)
+}
+
+fn encrypt_with_cost(plaintext: &str, passphrase: &str, log_n: u8) -> Result<String> {
+    let salt: [u8; 16] = rand_bytes();
+    let key = derive_key(passphrase, &salt, log_n, SCRYPT_R, SCRYPT_P)?;
+    let cipher = ChaCha20Poly1305::new(&key.into());
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|_| encryption_error("Failed to encrypt the snippet database"))?;
+
+    Ok(serde_json::to_string_pretty(&EncryptedDatabase {
+        encrypted: EncryptedData {
+            kdf: "scrypt".to_string(),
+            log_n,
+            r: SCRYPT_R,
+            p: SCRYPT_P,
+            salt: BASE64.encode(salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        },
+    })?)
+}
+
+/// Random bytes from the OS for salts
+fn rand_bytes<const N: usize>() -> [u8; N] {
+    use chacha20poly1305::aead::rand_core::RngCore;
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Encrypt database contents with a passphrase
+pub fn encrypt(plaintext: &str, passphrase: &str) -> Result<String> {
+    encrypt_with_cost(plaintext, passphrase, SCRYPT_LOG_N)
+}
+
+/// Decrypt an encrypted database written by `encrypt`
+pub fn decrypt(content: &str, passphrase: &str) -> Result<String> {
+    let database: EncryptedDatabase = serde_json::from_str(content)?;
+    let data = database.encrypted;
+    if data.kdf != "scrypt" {
+        return Err(encryption_error(format!(
+            "Unsupported key derivation '{}'",
+            data.kdf
+        )));
+    }
+
+    let decode = |field: &str| {
+        BASE64
+            .decode(field)
+            .map_err(|_| encryption_error("The encrypted database is damaged"))
+    };
+    let salt = decode(&data.salt)?;
+    let nonce = decode(&data.nonce)?;
+    let ciphertext = decode(&data.ciphertext)?;
+    if nonce.len() != 12 {
+        return Err(encryption_error("The encrypted database is damaged"));
+    }
+
+    let key = derive_key(passphrase, &salt, data.log_n, data.r, data.p)?;
+    let plaintext = ChaCha20Poly1305::new(&key.into())
+        .decrypt(nonce.as_slice().into(), ciphertext.as_slice())
+        .map_err(|_| encryption_error("Wrong passphrase, or the database is damaged"))?;
+
+    String::from_utf8(plaintext)
+        .map_err(|_| encryption_error("The decrypted database is not valid text"))
+}
+
+/// Remember the passphrase for the rest of this process
+pub fn set_passphrase(passphrase: String) {
+    if let Ok(mut current) = PASSPHRASE.write() {
+        *current = Some(passphrase);
+    }
+}
+
+/// The passphrase set in this process or in `SNIPT_PASSPHRASE`
+pub fn cached_passphrase() -> Option<String> {
+    PASSPHRASE
+        .read()
+        .ok()
+        .and_then(|current| current.clone())
+        .or_else(|| env::var(PASSPHRASE_ENV).ok().filter(|p| !p.is_empty()))
+}
+
+/// Keep a passphrase given in `SNIPT_PASSPHRASE` in this process only, so
+/// that commands run by snippets and other child processes don't inherit it
+pub fn take_passphrase_from_env() {
+    if let Some(passphrase) = env::var(PASSPHRASE_ENV).ok().filter(|p| !p.is_empty()) {
+        set_passphrase(passphrase);
+    }
+    env::remove_var(PASSPHRASE_ENV);
+}
+
+/// Read the passphrase that the `snipt start` process writes to the stdin of
+/// the daemon and API server it launches
+pub fn read_passphrase_from_stdin() -> Result<()> {
+    read_passphrase_from(stdin().lock())
+}
+
+/// Use the first line of `reader` as the passphrase, if it isn't empty
+pub fn read_passphrase_from(mut reader: impl BufRead) -> Result<()> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let passphrase = line.trim_end_matches(['\r', '\n']);
+    if !passphrase.is_empty() {
+        set_passphrase(passphrase.to_string());
+    }
+    Ok(())
+}
+
+/// The passphrase for the database, or an error explaining how to supply it
+pub fn passphrase() -> Result<String> {
+    cached_passphrase().ok_or_else(|| {
+        encryption_error(format!(
+            "The snippet database is encrypted. Set {} or run snipt from a terminal to enter the passphrase",
+            PASSPHRASE_ENV
+        ))
+    })
+}
+
+/// Check whether the database is encrypted on disk or encryption is turned on
+pub fn encryption_in_use() -> bool {
+    current_config().encrypt_database
+        || fs::read_to_string(get_db_file_path()).is_ok_and(|content| is_encrypted(&content))
+}
+
+/// Ask for the passphrase once if the database needs one and none was given.
+/// Without a terminal nothing is asked and loading the database will explain
+/// how to provide it.
+pub fn ensure_passphrase() -> Result<()> {
+    if cached_passphrase().is_some() || !encryption_in_use() || !stdin().is_terminal() {
+        return Ok(());
+    }
+
+    set_passphrase(prompt_passphrase("Snippet database passphrase: ")?);
+    Ok(())
+}
+
+/// Read a passphrase from the terminal without echoing it
+pub fn prompt_passphrase(prompt: &str) -> Result<String> {
+    Ok(rpassword::prompt_password(prompt)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encryption_roundtrip() {
+        let plaintext = r#"{"version": 2, "snippets": []}"#;
+        // Low cost keeps the test fast; the cost is stored in the file
+        let encrypted = encrypt_with_cost(plaintext, "hunter2", 4).unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.contains("snippets"));
+        assert!(!is_encrypted(plaintext));
+
+        assert_eq!(decrypt(&encrypted, "hunter2").unwrap(), plaintext);
+        assert!(decrypt(&encrypted, "wrong").is_err());
+    }
+}
@@ -0,0 +1,144 @@
+//! Date and time placeholders in snippet bodies.
+//!
+//! - `{{date}}` and `{{time}}`: the current date (`2024-05-31`) or time (`14:05`)
+//! - `{{date:FORMAT}}` and `{{time:FORMAT}}`: formatted with a `strftime`
+//!   string such as `%d.%m.%Y` or `%H:%M:%S %Z`
+//!
+//! Times are in the configured `timezone`, or the local one when it isn't set.
+//! A format chrono doesn't understand falls back to the default and is logged.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, TimeZone, Utc};
+use chrono_tz::Tz;
+use log::warn;
+use std::fmt::Display;
+
+use crate::error::{Result, SniptError};
+use crate::settings::current_config;
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+const DEFAULT_TIME_FORMAT: &str = "%H:%M";
+
+/// Parse an IANA timezone name such as `Europe/Berlin` or `UTC`
+pub fn parse_timezone(name: &str) -> Result<Tz> {
+    name.parse::<Tz>().map_err(|_| {
+        SniptError::InvalidConfig(format!(
+            "Unknown timezone '{}'. Use a name such as \"UTC\" or \"Europe/Berlin\"",
+            name
+        ))
+    })
+}
+
+/// Replace the date and time placeholders in `text` with the current time
+pub fn resolve_date_placeholders(text: &str) -> String {
+    if !text.contains("{{") {
+        return text.to_string();
+    }
+
+    let timezone = current_config().timezone;
+    match timezone.as_deref().map(parse_timezone) {
+        Some(Ok(tz)) => resolve_date_placeholders_at(text, &Utc::now().with_timezone(&tz)),
+        Some(Err(e)) => {
+            warn!("{}; using local time", e);
+            resolve_date_placeholders_at(text, &Local::now())
+        }
+        None => resolve_date_placeholders_at(text, &Local::now()),
+    }
+}
+
+/// Replace the date and time placeholders in `text` with `now`
+pub fn resolve_date_placeholders_at<Z: TimeZone>(text: &str, now: &DateTime<Z>) -> String
+where
+    Z::Offset: Display,
+{
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("}}") else {
+            break;
+        };
+
+        let inner = &after_open[..end];
+        let (name, format) = match inner.split_once(':') {
+            Some((name, format)) => (name, Some(format)),
+            None => (inner, None),
+        };
+        let default_format = match name {
+            "date" => DEFAULT_DATE_FORMAT,
+            "time" => DEFAULT_TIME_FORMAT,
+            _ => {
+                // Not a placeholder; keep the braces and look further on
+                output.push_str(&rest[..start + 2]);
+                rest = after_open;
+                continue;
+            }
+        };
+
+        output.push_str(&rest[..start]);
+        output.push_str(&format_time(
+            now,
+            format.unwrap_or(default_format),
+            default_format,
+        ));
+        rest = &after_open[end + 2..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Format `now`, using `fallback` if `format` isn't a valid format string
+fn format_time<Z: TimeZone>(now: &DateTime<Z>, format: &str, fallback: &str) -> String
+where
+    Z::Offset: Display,
+{
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        warn!(
+            "Invalid date format '{}'; using '{}' instead",
+            format, fallback
+        );
+        return now.format(fallback).to_string();
+    }
+    now.format_with_items(items.into_iter()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_placeholders() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 31, 22, 5, 9).unwrap();
+        assert_eq!(
+            resolve_date_placeholders_at("Sent {{date}} at {{time}}", &now),
+            "Sent 2024-05-31 at 22:05"
+        );
+        assert_eq!(
+            resolve_date_placeholders_at("{{date:%d.%m.%Y}} {{time:%H:%M:%S %Z}}", &now),
+            "31.05.2024 22:05:09 UTC"
+        );
+
+        // Another timezone moves the date along with the time
+        let tokyo = now.with_timezone(&parse_timezone("Asia/Tokyo").unwrap());
+        assert_eq!(
+            resolve_date_placeholders_at("{{date}} {{time}}", &tokyo),
+            "2024-06-01 07:05"
+        );
+        assert!(parse_timezone("Mars/Olympus").is_err());
+
+        // An invalid format falls back to the default instead of panicking
+        assert_eq!(
+            resolve_date_placeholders_at("{{date:%Q}}", &now),
+            "2024-05-31"
+        );
+
+        // Other braces are left alone
+        assert_eq!(
+            resolve_date_placeholders_at("{{name}} {{os:linux}}x{{/os}} {{date", &now),
+            "{{name}} {{os:linux}}x{{/os}} {{date"
+        );
+    }
+}
@@ -15,6 +15,12 @@ pub enum SniptError {
     Clipboard(String),
     Other(String),
     PermissionDenied(String),
+    ExpansionSkipped(String),
+    SecureInput,
+    QuietHours,
+    ExpansionAborted,
+    CommandTimedOut(u64),
+    Encryption(String),
 }
 
 impl fmt::Display for SniptError {
@@ -34,6 +40,16 @@ impl fmt::Display for SniptError {
             SniptError::Clipboard(msg) => write!(f, "Clipboard error: {}", msg),
             SniptError::Other(msg) => write!(f, "Error: {}", msg),
             SniptError::PermissionDenied(msg) => write!(f, "Permission denied: {}", msg),
+            SniptError::ExpansionSkipped(app) => write!(f, "Expansion is disabled in '{}'", app),
+            SniptError::QuietHours => write!(f, "Expansion is off during quiet hours"),
+            SniptError::SecureInput => {
+                write!(f, "Expansion is disabled while a password is being entered")
+            }
+            SniptError::ExpansionAborted => write!(f, "Expansion was interrupted"),
+            SniptError::CommandTimedOut(secs) => {
+                write!(f, "Command was stopped after running for {} seconds", secs)
+            }
+            SniptError::Encryption(msg) => write!(f, "Encryption error: {}", msg),
         }
     }
 }
@@ -1,17 +1,27 @@
 use enigo::{Direction, Key, Keyboard};
 use std::env;
 use std::fs::{self, Permissions};
-use std::io::Write;
+use std::io::{self, Read, Write};
 use std::os::unix::fs::PermissionsExt;
-use std::process::{Command, Stdio};
+use std::process::{Command, Output, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tempfile::NamedTempFile;
 
-use crate::expansion::type_text_with_formatting;
+use crate::expansion::{insert_text, type_text_with_formatting};
 use crate::keyboard::{create_keyboard_controller, send_backspace};
+use crate::settings::current_config;
 use crate::{Result, SniptError};
 
+/// How long the output of a command that has exited may keep arriving, even
+/// when its timeout has already run out
+const OUTPUT_GRACE: Duration = Duration::from_millis(100);
+
+/// Marks an execute snippet such as `$ date +%F` whose command output is
+/// typed in place of the shortcut
+const INSERT_OUTPUT_PREFIX: &str = "$ ";
+
 /// Determine if a string is a URL
 pub fn is_url(content: &str) -> bool {
     let content = content.trim();
@@ -134,14 +144,196 @@ pub fn execute_snippet(
                 return execute_script(&mut keyboard, &modified_content, None);
             }
 
-            return type_text_with_formatting(&mut keyboard, &formatted_content);
+            return insert_text(&mut keyboard, &formatted_content);
         } else {
             // Just do normal text expansion for simple strings
-            return type_text_with_formatting(&mut keyboard, content);
+            return insert_text(&mut keyboard, content);
+        }
+    }
+}
+
+/// The command of an execute snippet written as `$ command`
+pub fn insert_output_command(content: &str) -> Option<&str> {
+    content
+        .strip_prefix(INSERT_OUTPUT_PREFIX)
+        .map(str::trim)
+        .filter(|command| !command.is_empty())
+}
+
+/// Run a `$ command` and type its standard output in place of the shortcut.
+/// The shortcut is only erased once the command has succeeded, so a failing
+/// or hanging command leaves the typed text alone.
+pub fn execute_and_insert(to_delete: usize, command: &str, params: &[String]) -> Result<()> {
+    let command = if params.is_empty() {
+        command.to_string()
+    } else {
+        apply_parameter_substitution(command, params)
+    };
+    let output = run_captured(shell_command(&command))?;
+    if !output.status.success() {
+        return Err(SniptError::Other(format!(
+            "Command failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    let mut keyboard = create_keyboard_controller()?;
+    send_backspace(&mut keyboard, to_delete)?;
+
+    // Small delay to ensure UI state is stable
+    thread::sleep(Duration::from_millis(10));
+
+    type_text_with_formatting(
+        &mut keyboard,
+        stdout.trim_end(),
+        current_config().typing_delay_ms,
+    )
+}
+
+/// Build a command that runs `command` through the user's shell with its
+/// output captured
+fn shell_command(command: &str) -> Command {
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut command_obj = Command::new("cmd");
+        command_obj.args(["/c", command]);
+        command_obj
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = {
+        let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+        let mut command_obj = Command::new(&shell);
+        command_obj.args(["-c", command]);
+        command_obj
+    };
+
+    // Pipe everything to avoid shell window flashing
+    cmd.stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .stdin(Stdio::null());
+    cmd
+}
+
+/// Run a command with its output captured, within the configured
+/// `execute_timeout_secs` and `execute_max_output_bytes`
+fn run_captured(cmd: Command) -> Result<Output> {
+    let config = current_config();
+    run_with_limits(
+        cmd,
+        Duration::from_secs(config.execute_timeout_secs),
+        config.execute_max_output_bytes,
+    )
+}
+
+/// Run a command, killing it if it takes longer than `timeout` and keeping
+/// at most `max_output` bytes of each output stream. A zero limit disables it.
+fn run_with_limits(mut cmd: Command, timeout: Duration, max_output: usize) -> Result<Output> {
+    let mut child = cmd.spawn()?;
+
+    // Drain the pipes while waiting so a chatty command can't fill them and stall
+    let stdout = child
+        .stdout
+        .take()
+        .map(|pipe| read_in_background(pipe, max_output));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|pipe| read_in_background(pipe, max_output));
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if !timeout.is_zero() && started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(SniptError::CommandTimedOut(timeout.as_secs()));
         }
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    // A process the command left running in the background can hold the
+    // pipes open after it exits, so only wait for them until the timeout
+    let deadline =
+        (!timeout.is_zero()).then(|| (started + timeout).max(Instant::now() + OUTPUT_GRACE));
+    let collect = |reader: Option<PipeReader>| {
+        reader
+            .map(|reader| reader.collect(deadline))
+            .unwrap_or_default()
+    };
+    Ok(Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+/// A pipe being read on another thread
+struct PipeReader {
+    output: Arc<Mutex<Vec<u8>>>,
+    /// Disconnects once the pipe is closed
+    closed: mpsc::Receiver<()>,
+}
+
+impl PipeReader {
+    /// Wait until the pipe is closed, or at most until `deadline`, and take
+    /// what has been read
+    fn collect(self, deadline: Option<Instant>) -> Vec<u8> {
+        match deadline {
+            Some(deadline) => {
+                let _ = self
+                    .closed
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now()));
+            }
+            None => {
+                let _ = self.closed.recv();
+            }
+        }
+        let mut buffer = std::mem::take(&mut *self.output.lock().unwrap());
+
+        // Don't leave half a character at the cut
+        if let Err(e) = std::str::from_utf8(&buffer) {
+            if e.error_len().is_none() {
+                buffer.truncate(e.valid_up_to());
+            }
+        }
+        buffer
     }
 }
 
+/// Read a pipe to the end on another thread, keeping at most `limit` bytes
+/// (all of them if `limit` is 0)
+fn read_in_background(mut pipe: impl Read + Send + 'static, limit: usize) -> PipeReader {
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let (closed_tx, closed) = mpsc::channel();
+    let shared = Arc::clone(&output);
+    thread::spawn(move || {
+        // Dropped when the pipe is done, which disconnects `closed`
+        let _closed_tx: mpsc::Sender<()> = closed_tx;
+        let mut chunk = [0; 8192];
+        loop {
+            let read = match pipe.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            };
+            // Past the limit keep reading, so the command isn't blocked on a
+            // full pipe
+            let mut output = shared.lock().unwrap();
+            let keep = match limit {
+                0 => read,
+                limit => read.min(limit.saturating_sub(output.len())),
+            };
+            output.extend_from_slice(&chunk[..keep]);
+        }
+    });
+    PipeReader { output, closed }
+}
+
 /// Execute content as a command directly in the current shell
 fn execute_command(
     keyboard: &mut impl Keyboard,
@@ -156,32 +348,10 @@ fn execute_command(
     };
 
     // Create a command with proper pipes to avoid shell window flashing
-    #[cfg(target_os = "windows")]
-    let mut cmd = Command::new("cmd");
-
-    #[cfg(target_os = "windows")]
-    cmd.args(["/c", &command])
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .stdin(Stdio::null());
-
-    #[cfg(not(target_os = "windows"))]
-    let mut cmd = {
-        let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
-        let mut command_obj = Command::new(&shell);
-        command_obj
-            .args(["-c", &command])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .stdin(Stdio::null());
-        command_obj
-    };
+    let cmd = shell_command(&command);
 
     // Execute with timeout protection
-    let output = match cmd.output() {
-        Ok(output) => output,
-        Err(e) => return Err(SniptError::Io(e)),
-    };
+    let output = run_captured(cmd)?;
 
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout).to_string();
@@ -192,7 +362,7 @@ fn execute_command(
         thread::sleep(Duration::from_millis(10));
 
         // Type the output
-        type_text_with_formatting(keyboard, &trimmed_stdout)
+        insert_text(keyboard, &trimmed_stdout)
     } else {
         Err(SniptError::Other(format!(
             "Command failed: {}",
@@ -245,10 +415,7 @@ fn execute_script(
         .stdin(Stdio::null());
 
     // Execute and handle output
-    let output = match cmd.output() {
-        Ok(output) => output,
-        Err(e) => return Err(SniptError::Io(e)),
-    };
+    let output = run_captured(cmd)?;
 
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout).to_string();
@@ -309,7 +476,7 @@ fn execute_script(
         thread::sleep(Duration::from_millis(10));
 
         // Type the output
-        type_text_with_formatting(keyboard, &trimmed_stdout)
+        insert_text(keyboard, &trimmed_stdout)
     } else {
         Err(SniptError::Other(format!(
             "Script failed: {}",
@@ -333,3 +500,47 @@ fn apply_parameter_substitution(content: &str, params: &[String]) -> String {
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_output_command() {
+        assert_eq!(insert_output_command("$ date +%F"), Some("date +%F"));
+        assert_eq!(insert_output_command("$  "), None);
+        assert_eq!(insert_output_command("date"), None);
+        assert_eq!(insert_output_command("$HOME"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_with_limits() {
+        let timeout = Duration::from_secs(5);
+        let output = run_with_limits(shell_command("printf 'a\\nb\\n'"), timeout, 0).unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"a\nb\n");
+        let output = run_with_limits(shell_command("exit 3"), timeout, 0).unwrap();
+        assert!(!output.status.success());
+
+        // Output is cut at the cap, never inside a character
+        let output = run_with_limits(shell_command("yes é | head -c 100000"), timeout, 4).unwrap();
+        assert_eq!(output.stdout, "é\n".as_bytes());
+
+        let started = Instant::now();
+        let result = run_with_limits(shell_command("sleep 5"), Duration::from_millis(200), 0);
+        assert!(matches!(result, Err(SniptError::CommandTimedOut(_))));
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        // A background process holding the output open doesn't hold up the result
+        let started = Instant::now();
+        let output = run_with_limits(
+            shell_command("echo hi; sleep 5 &"),
+            Duration::from_millis(300),
+            0,
+        )
+        .unwrap();
+        assert_eq!(output.stdout, b"hi\n");
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
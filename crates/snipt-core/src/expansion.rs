@@ -1,13 +1,22 @@
 use enigo::{Direction, Key, Keyboard};
+use std::collections::HashMap;
 use std::fmt;
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
 
-use crate::config::{EXECUTE_CHAR, SPECIAL_CHAR};
+use crate::config::{execute_char, trigger_char};
+use crate::dates::resolve_date_placeholders;
 use crate::error::Result;
-use crate::execution::execute_snippet;
-use crate::keyboard::{create_keyboard_controller, send_backspace};
-use crate::models::SnippetEntry;
+use crate::execution::{execute_and_insert, execute_snippet, insert_output_command};
+use crate::keyboard::{create_keyboard_controller, send_backspace, SelfTyping};
+use crate::models::{SnippetEntry, SnippetKind};
+use crate::quiet_hours::current_quiet_window;
+use crate::secure_input::secure_input_active;
+use crate::settings::{current_config, ExpansionMethod};
+use crate::tabstops::{parse_tab_stops, TabStops};
 use crate::SniptError;
+use arboard::{Clipboard, ImageData};
 use std::thread;
 use std::time::Duration;
 
@@ -18,6 +27,8 @@ pub enum ExpansionStyle {
     /// Hyperlink style - used for platforms like  Linear, Slack, Teams
     /// that support hyperlinks rather than direct content pasting
     Hyperlink,
+    /// Paste the given HTML with the snippet text as the plain-text fallback
+    RichText(String),
 }
 
 /// Represents the type of expansion to perform
@@ -25,16 +36,23 @@ pub enum ExpansionType {
     Text(String, ExpansionStyle, String), // Expand as text with style and original shortcut
     Execute(String, ExpansionStyle, String), // Execute as script/URL/command with style and original shortcut
     ExecuteWithParams(String, Vec<String>, ExpansionStyle, String), // Execute with parameters with style and original shortcut
+    ExecuteAndInsert(String, Vec<String>, ExpansionStyle, String), // Run a `$ command` and type its output, with parameters, style and original shortcut
 }
 
 impl fmt::Display for ExpansionType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let formatted = match self {
-            ExpansionType::Text(content, _, _) => format!("{}{}", SPECIAL_CHAR, content),
-            ExpansionType::Execute(content, _, _) => format!("{}{}", EXECUTE_CHAR, content),
+            ExpansionType::Text(content, _, _) => format!("{}{}", trigger_char(), content),
+            ExpansionType::Execute(content, _, _) => format!("{}{}", execute_char(), content),
             ExpansionType::ExecuteWithParams(content, params, _, _) => {
                 let params_str = params.join(",");
-                format!("{}{}({})", EXECUTE_CHAR, content, params_str)
+                format!("{}{}({})", execute_char(), content, params_str)
+            }
+            ExpansionType::ExecuteAndInsert(command, params, _, _) if params.is_empty() => {
+                format!("{}$ {}", execute_char(), command)
+            }
+            ExpansionType::ExecuteAndInsert(command, params, _, _) => {
+                format!("{}$ {}({})", execute_char(), command, params.join(","))
             }
         };
         write!(f, "{}", formatted)
@@ -48,6 +66,7 @@ impl ExpansionType {
             ExpansionType::Text(content, _, _) => content,
             ExpansionType::Execute(content, _, _) => content,
             ExpansionType::ExecuteWithParams(content, _, _, _) => content,
+            ExpansionType::ExecuteAndInsert(command, _, _, _) => command,
         }
     }
 
@@ -55,6 +74,7 @@ impl ExpansionType {
     pub fn params(&self) -> Option<&Vec<String>> {
         match self {
             ExpansionType::ExecuteWithParams(_, params, _, _) => Some(params),
+            ExpansionType::ExecuteAndInsert(_, params, _, _) => Some(params),
             _ => None,
         }
     }
@@ -65,6 +85,7 @@ impl ExpansionType {
             ExpansionType::Text(_, style, _) => style,
             ExpansionType::Execute(_, style, _) => style,
             ExpansionType::ExecuteWithParams(_, _, style, _) => style,
+            ExpansionType::ExecuteAndInsert(_, _, style, _) => style,
         }
     }
 
@@ -74,6 +95,7 @@ impl ExpansionType {
             ExpansionType::Text(_, _, shortcut) => Some(shortcut),
             ExpansionType::Execute(_, _, shortcut) => Some(shortcut),
             ExpansionType::ExecuteWithParams(_, _, _, shortcut) => Some(shortcut),
+            ExpansionType::ExecuteAndInsert(_, _, _, shortcut) => Some(shortcut),
         }
     }
 
@@ -86,7 +108,9 @@ impl ExpansionType {
     pub fn is_execute(&self) -> bool {
         matches!(
             self,
-            ExpansionType::Execute(_, _, _) | ExpansionType::ExecuteWithParams(_, _, _, _)
+            ExpansionType::Execute(_, _, _)
+                | ExpansionType::ExecuteWithParams(_, _, _, _)
+                | ExpansionType::ExecuteAndInsert(_, _, _, _)
         )
     }
 }
@@ -185,6 +209,112 @@ pub fn determine_expansion_style() -> ExpansionStyle {
     ExpansionStyle::Default
 }
 
+/// Lowercased (blocklist, allowlist) of application names set from the user configuration
+static APP_RULES: RwLock<(Vec<String>, Vec<String>)> = RwLock::new((Vec::new(), Vec::new()));
+
+/// Change the application blocklist and allowlist for this process
+pub fn set_app_rules(blocklist: &[String], allowlist: &[String]) {
+    let normalize = |list: &[String]| -> Vec<String> {
+        list.iter()
+            .map(|app| app.trim().to_lowercase())
+            .filter(|app| !app.is_empty())
+            .collect()
+    };
+
+    if let Ok(mut rules) = APP_RULES.write() {
+        *rules = (normalize(blocklist), normalize(allowlist));
+    }
+}
+
+/// Check whether expansion is allowed in an application.
+///
+/// Entries match as case-insensitive substrings of the app name. The blocklist
+/// always wins; a non-empty allowlist additionally requires a match, so an app
+/// whose name can't be determined is skipped when an allowlist is configured.
+pub fn is_app_allowed(app_name: &str, blocklist: &[String], allowlist: &[String]) -> bool {
+    let app_name = app_name.to_lowercase();
+    let matches = |app: &String| !app_name.is_empty() && app_name.contains(app.as_str());
+
+    if blocklist.iter().any(matches) {
+        return false;
+    }
+
+    allowlist.is_empty() || allowlist.iter().any(matches)
+}
+
+/// Fail with `ExpansionSkipped` if the frontmost app is excluded by the configured
+/// rules, with `SecureInput` if a password seems to be being entered, or with
+/// `QuietHours` during the configured quiet hours
+pub fn check_app_rules() -> Result<()> {
+    if current_quiet_window().is_some() {
+        return Err(SniptError::QuietHours);
+    }
+    if current_config().block_secure_input && secure_input_active() {
+        return Err(SniptError::SecureInput);
+    }
+
+    let rules = match APP_RULES.read() {
+        Ok(rules) => rules,
+        Err(_) => return Ok(()),
+    };
+    let (blocklist, allowlist) = &*rules;
+    if blocklist.is_empty() && allowlist.is_empty() {
+        return Ok(());
+    }
+
+    let app_name = get_frontmost_app();
+    if is_app_allowed(&app_name, blocklist, allowlist) {
+        Ok(())
+    } else {
+        Err(SniptError::ExpansionSkipped(app_name))
+    }
+}
+
+/// Characters that can start an expansion: the global trigger and execute
+/// characters, and the triggers of enabled snippets that set their own
+pub fn trigger_chars(snippets: &[SnippetEntry]) -> Vec<char> {
+    let mut chars = vec![trigger_char(), execute_char()];
+    for entry in snippets.iter().filter(|entry| entry.enabled) {
+        if let Some(trigger) = entry.trigger {
+            if !chars.contains(&trigger) {
+                chars.push(trigger);
+            }
+        }
+    }
+    chars
+}
+
+/// Characters a doubled copy of escapes with `escaped_triggers`: the trigger
+/// character and the triggers of enabled snippets that set their own. The
+/// execute character isn't one, so `!!` can still be typed.
+pub fn escapable_trigger_chars(snippets: &[SnippetEntry]) -> Vec<char> {
+    let mut chars = vec![trigger_char()];
+    for entry in snippets.iter().filter(|entry| entry.enabled) {
+        if let Some(trigger) = entry.trigger {
+            if !chars.contains(&trigger) {
+                chars.push(trigger);
+            }
+        }
+    }
+    chars
+}
+
+/// Number of enabled snippets whose shortcut starts with what was typed after
+/// the trigger in `typed`, e.g. `:longsh` for `longshortcut`
+pub fn count_prefix_matches(typed: &str, snippets: &[SnippetEntry]) -> usize {
+    let mut chars = typed.chars();
+    let Some(first_char) = chars.next() else {
+        return 0;
+    };
+    let prefix = chars.as_str();
+    snippets
+        .iter()
+        .filter(|entry| entry.enabled)
+        .filter(|entry| first_char == entry.text_trigger() || first_char == execute_char())
+        .filter(|entry| entry.shortcut.starts_with(prefix))
+        .count()
+}
+
 /// Process text buffer to check for text expansion trigger
 pub fn process_expansion(buffer: &str, snippets: &[SnippetEntry]) -> Result<Option<ExpansionType>> {
     // Check if the buffer is valid for expansion
@@ -193,50 +323,89 @@ pub fn process_expansion(buffer: &str, snippets: &[SnippetEntry]) -> Result<Opti
     }
 
     let first_char = buffer.chars().next().unwrap();
-    if first_char != SPECIAL_CHAR && first_char != EXECUTE_CHAR {
+    let execute_char = execute_char();
+    if !trigger_chars(snippets).contains(&first_char) {
         return Ok(None);
     }
 
-    if buffer.len() <= 1 {
+    if buffer.chars().count() <= 1 {
         return Ok(None);
     }
 
-    // Extract the shortcut without the special character
-    let shortcut = &buffer[1..];
+    // Extract the shortcut without the trigger character
+    let shortcut = &buffer[first_char.len_utf8()..];
 
     // Determine expansion style based on current application
     let expansion_style = determine_expansion_style();
 
     // Look for exact matches first (original behavior)
-    for entry in snippets {
-        if entry.shortcut == shortcut {
-            return if first_char == SPECIAL_CHAR {
-                // Expansion trigger
+    for entry in snippets.iter().filter(|entry| entry.enabled) {
+        let text_trigger = entry.text_trigger();
+        if entry.shortcut == shortcut && (first_char == text_trigger || first_char == execute_char)
+        {
+            let body = resolve_body(&entry.snippet);
+            return if first_char == text_trigger {
+                // Expansion trigger, as rich text when the snippet has an HTML
+                // body. Links are formatted the same way in every app.
+                let style = match (&entry.html, entry.kind) {
+                    (_, SnippetKind::Link) => ExpansionStyle::Default,
+                    (Some(html), _) => ExpansionStyle::RichText(resolve_body(html)),
+                    (None, _) => expansion_style,
+                };
                 Ok(Some(ExpansionType::Text(
-                    entry.snippet.clone(),
+                    text_body(entry),
+                    style,
+                    shortcut.to_string(),
+                )))
+            } else if let Some(command) = insert_output_command(&body) {
+                // Execution trigger for a `$ command` whose output is typed in
+                Ok(Some(ExpansionType::ExecuteAndInsert(
+                    command.to_string(),
+                    Vec::new(),
                     expansion_style,
                     shortcut.to_string(),
                 )))
-            } else if first_char == EXECUTE_CHAR {
+            } else {
                 // Execution trigger
                 Ok(Some(ExpansionType::Execute(
-                    entry.snippet.clone(),
+                    body,
                     expansion_style,
                     shortcut.to_string(),
                 )))
-            } else {
-                // Unreachable based on our earlier filter, but here for safety
-                Ok(None)
             };
         }
     }
 
+    // A capitalized or all-caps trigger can expand a lowercase shortcut, with
+    // the casing carried over to the expansion
+    if first_char != execute_char && current_config().case_preserving {
+        let lowercase = shortcut.to_lowercase();
+        if lowercase != shortcut {
+            if let Some(entry) = snippets.iter().find(|entry| {
+                entry.enabled && entry.shortcut == lowercase && entry.text_trigger() == first_char
+            }) {
+                let body = text_body(entry);
+                let (text, style) = match (&entry.html, entry.kind) {
+                    (_, SnippetKind::Link) => (body, ExpansionStyle::Default),
+                    // Formatting can't be recased reliably, so rich text is kept as is
+                    (Some(html), _) => (body, ExpansionStyle::RichText(resolve_body(html))),
+                    (None, _) => (match_trigger_case(shortcut, &body), expansion_style),
+                };
+                return Ok(Some(ExpansionType::Text(
+                    text,
+                    style,
+                    entry.shortcut.clone(),
+                )));
+            }
+        }
+    }
+
     // Only check for parameterized snippets if relevant
-    if first_char == EXECUTE_CHAR && shortcut.contains('(') && shortcut.ends_with(')') {
+    if first_char == execute_char && shortcut.contains('(') && shortcut.ends_with(')') {
         // Extract the base shortcut from the input (without parameters)
         if let Some(input_base) = extract_base_shortcut(shortcut) {
             // Look for matching base shortcuts
-            for entry in snippets {
+            for entry in snippets.iter().filter(|entry| entry.enabled) {
                 // Check if the snippet entry has parameters (contains '(' and ')')
                 if entry.shortcut.contains('(') && entry.shortcut.contains(')') {
                     if let Some(entry_base) = extract_base_shortcut(&entry.shortcut) {
@@ -244,15 +413,21 @@ pub fn process_expansion(buffer: &str, snippets: &[SnippetEntry]) -> Result<Opti
                         if input_base == entry_base {
                             // Extract parameters from the user input
                             if let Some(params) = extract_params_from_input(shortcut) {
-                                // Extract placeholders from the shortcut definition
-                                let placeholders = extract_placeholders(&entry.shortcut);
+                                // Apply parameter substitution to the snippet content,
+                                // filling in defaults for anything left out
+                                let modified_content = expand_text(entry, &params)?;
 
-                                // Create a mapping from placeholders to actual values
-                                let param_map = create_param_mapping(&placeholders, &params);
+                                let placeholders = extract_placeholders(&entry.shortcut);
+                                let params = resolve_params(&placeholders, &params);
 
-                                // Apply parameter substitution to the snippet content
-                                let modified_content =
-                                    apply_param_mapping(&entry.snippet, &param_map);
+                                if let Some(command) = insert_output_command(&modified_content) {
+                                    return Ok(Some(ExpansionType::ExecuteAndInsert(
+                                        command.to_string(),
+                                        params,
+                                        expansion_style,
+                                        entry_base.to_string(),
+                                    )));
+                                }
 
                                 return Ok(Some(ExpansionType::ExecuteWithParams(
                                     modified_content,
@@ -272,6 +447,89 @@ pub fn process_expansion(buffer: &str, snippets: &[SnippetEntry]) -> Result<Opti
     Ok(None)
 }
 
+/// Marks the end of an OS-specific section
+const OS_SECTION_END: &str = "{{/os}}";
+
+/// Name of the current platform as used in `{{os:...}}` sections
+pub fn current_os() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "macos"
+    } else if cfg!(target_os = "windows") {
+        "windows"
+    } else if cfg!(target_os = "linux") {
+        "linux"
+    } else {
+        std::env::consts::OS
+    }
+}
+
+/// Resolve the platform sections and date placeholders of a snippet body
+fn resolve_body(text: &str) -> String {
+    resolve_date_placeholders(&resolve_os_sections(text))
+}
+
+/// Keep the sections of a snippet body meant for the current platform, see
+/// `resolve_os_sections_for`
+pub fn resolve_os_sections(text: &str) -> String {
+    resolve_os_sections_for(text, current_os())
+}
+
+/// Keep the `{{os:NAME}}...{{/os}}` sections meant for `os` and drop the
+/// others. NAME is `macos`, `linux` or `windows`, or several of them separated
+/// by commas. Text outside sections always stays, sections can't be nested,
+/// and a section without its `{{/os}}` is left as written.
+pub fn resolve_os_sections_for(text: &str, os: &str) -> String {
+    const OPEN: &str = "{{os:";
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(OPEN) {
+        let after_open = &rest[start + OPEN.len()..];
+        let Some(names_end) = after_open.find("}}") else {
+            break;
+        };
+        let section = &after_open[names_end + 2..];
+        let Some(section_end) = section.find(OS_SECTION_END) else {
+            break;
+        };
+
+        output.push_str(&rest[..start]);
+        let names = &after_open[..names_end];
+        if names
+            .split(',')
+            .any(|name| name.trim().eq_ignore_ascii_case(os))
+        {
+            output.push_str(&section[..section_end]);
+        }
+        rest = &section[section_end + OS_SECTION_END.len()..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Adjust the casing of an expansion to how its trigger was typed: an all-caps
+/// trigger (`:BRB`) uppercases the whole text, a leading capital (`:Brb`)
+/// capitalizes its first letter, and anything else leaves it unchanged
+pub fn match_trigger_case(typed: &str, text: &str) -> String {
+    let letters: Vec<char> = typed.chars().filter(|c| c.is_alphabetic()).collect();
+    let Some(first) = letters.first() else {
+        return text.to_string();
+    };
+
+    if letters.len() > 1 && letters.iter().all(|c| c.is_uppercase()) {
+        text.to_uppercase()
+    } else if first.is_uppercase() {
+        let mut chars = text.chars();
+        match chars.next() {
+            Some(c) => c.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    } else {
+        text.to_string()
+    }
+}
+
 /// Extract the base shortcut from a parameterized shortcut like "sum(a,b)" -> "sum"
 fn extract_base_shortcut(shortcut: &str) -> Option<&str> {
     if shortcut.contains('(') {
@@ -288,34 +546,195 @@ fn extract_params_from_input(input: &str) -> Option<Vec<String>> {
     if let Some(open_idx) = input.find('(') {
         if let Some(close_idx) = input.rfind(')') {
             if close_idx > open_idx {
-                let params_str = &input[open_idx + 1..close_idx];
-                // Handle empty params case
-                if params_str.trim().is_empty() {
-                    return Some(Vec::new());
-                }
+                return Some(split_params(&input[open_idx + 1..close_idx]));
+            }
+        }
+    }
+    None
+}
 
-                let params: Vec<String> = params_str
-                    .split(',')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty()) // Filter out empty strings
-                    .collect();
+/// Split a comma separated parameter list like "2, 3" -> ["2", "3"]
+pub fn split_params(params_str: &str) -> Vec<String> {
+    params_str
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty()) // Filter out empty strings
+        .collect()
+}
+
+/// Find a snippet by its shortcut, or by the base name of a parameterized
+/// shortcut ("greet" finds "greet(name)")
+pub fn resolve_snippet<'a>(snippets: &'a [SnippetEntry], name: &str) -> Option<&'a SnippetEntry> {
+    snippets
+        .iter()
+        .find(|entry| entry.shortcut == name)
+        .or_else(|| {
+            snippets
+                .iter()
+                .find(|entry| extract_base_shortcut(&entry.shortcut) == Some(name))
+        })
+}
+
+/// Body typed for a text expansion: a markdown link for link snippets, the
+/// snippet text otherwise. Parameter placeholders are left in place.
+pub fn text_body(entry: &SnippetEntry) -> String {
+    match entry.kind {
+        SnippetKind::Link => resolve_body(&entry.markdown_link()),
+        _ => resolve_body(&entry.snippet),
+    }
+}
+
+/// Resolve the text a snippet expands to with the given parameter values,
+/// without typing anything. This is the same substitution a real expansion
+/// performs, so it can be used for previews.
+pub fn expand_text(entry: &SnippetEntry, params: &[String]) -> Result<String> {
+    let body = text_body(entry);
+    let placeholders = extract_placeholders(&entry.shortcut);
+    if placeholders.is_empty() {
+        return Ok(body);
+    }
 
-                return Some(params);
+    let param_map = create_param_mapping(&placeholders, params)?;
+    Ok(apply_param_mapping(&body, &param_map))
+}
+
+/// Replace every snippet reference in a block of text, for using snipt as a
+/// template filter.
+///
+/// A reference is a whitespace-separated word starting with the trigger
+/// character, such as `:sig` or `:greet(Ada,Hi)`. A parameter list may contain
+/// spaces; the word then runs to the closing parenthesis. Words that don't name
+/// a snippet, and all whitespace, are copied unchanged. Execute references are
+/// never run.
+///
+/// With `escaped_triggers` on, a word starting with a doubled trigger such as
+/// `::sig` is copied with one trigger removed (`:sig`) instead of expanding.
+pub fn expand_template(input: &str, snippets: &[SnippetEntry]) -> Result<String> {
+    expand_template_with(input, snippets, current_config().escaped_triggers)
+}
+
+/// Replace every snippet reference in `input`, optionally treating doubled
+/// triggers as escapes
+pub fn expand_template_with(
+    input: &str,
+    snippets: &[SnippetEntry],
+    escaped_triggers: bool,
+) -> Result<String> {
+    // The same triggers the daemon lets a doubled copy escape
+    let text_triggers = escapable_trigger_chars(snippets);
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    loop {
+        let word_start = rest
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(rest.len());
+        output.push_str(&rest[..word_start]);
+        rest = &rest[word_start..];
+        if rest.is_empty() {
+            return Ok(output);
+        }
+
+        let mut word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let word = &rest[..word_end];
+        let word_trigger = word.chars().next().filter(|c| text_triggers.contains(c));
+        if word_trigger.is_some() && word.contains('(') && !word.contains(')') {
+            if let Some(close) = rest.find(')') {
+                word_end = close + 1;
+            }
+        }
+
+        let word = &rest[..word_end];
+        if let Some(literal) = word_trigger
+            .filter(|_| escaped_triggers)
+            .and_then(|trigger| unescape_trigger(word, trigger))
+        {
+            output.push_str(literal);
+            rest = &rest[word_end..];
+            continue;
+        }
+        let expanded = word_trigger
+            .and_then(|trigger| expand_reference(&word[trigger.len_utf8()..], trigger, snippets));
+        match expanded {
+            Some(Ok(text)) => output.push_str(&text),
+            Some(Err(SniptError::Other(msg))) => {
+                return Err(SniptError::Other(format!(
+                    "Failed to expand '{}': {}",
+                    word, msg
+                )))
             }
+            Some(Err(e)) => return Err(e),
+            None => output.push_str(word),
         }
+        rest = &rest[word_end..];
     }
-    None
 }
 
-/// Extract placeholders from shortcut like "sum(a,b)" -> ["a", "b"]
-fn extract_placeholders(shortcut: &str) -> Vec<String> {
+/// The text an escaped word is typed as: `::foo` becomes `:foo`. `None` if
+/// `word` doesn't start with a doubled `trigger`.
+pub fn unescape_trigger(word: &str, trigger: char) -> Option<&str> {
+    let rest = word.strip_prefix(trigger)?;
+    rest.starts_with(trigger).then_some(rest)
+}
+
+/// Expand a snippet reference (the text after `trigger`), or `None` if it
+/// doesn't name an enabled snippet started by `trigger`
+fn expand_reference(
+    reference: &str,
+    trigger: char,
+    snippets: &[SnippetEntry],
+) -> Option<Result<String>> {
+    let (name, params) = if reference.contains('(') && reference.ends_with(')') {
+        (
+            extract_base_shortcut(reference)?,
+            extract_params_from_input(reference)?,
+        )
+    } else {
+        (reference, Vec::new())
+    };
+
+    if name.is_empty() {
+        return None;
+    }
+    let candidates = || {
+        snippets
+            .iter()
+            .filter(|entry| entry.enabled && entry.text_trigger() == trigger)
+    };
+    candidates()
+        .find(|entry| entry.shortcut == name)
+        .or_else(|| candidates().find(|entry| extract_base_shortcut(&entry.shortcut) == Some(name)))
+        .map(|entry| expand_text(entry, &params))
+}
+
+/// A parameter declared in a shortcut, optionally with a default value
+#[derive(Debug, Clone, PartialEq)]
+struct Placeholder {
+    name: String,
+    default: Option<String>,
+}
+
+/// Extract placeholders from shortcut like "greet(name, greeting=Hello)"
+/// -> [name (required), greeting (defaults to "Hello")]
+fn extract_placeholders(shortcut: &str) -> Vec<Placeholder> {
     if let Some(open_idx) = shortcut.find('(') {
         if let Some(close_idx) = shortcut.rfind(')') {
             if close_idx > open_idx {
                 let params_str = &shortcut[open_idx + 1..close_idx];
                 return params_str
                     .split(',')
-                    .map(|s| s.trim().to_string())
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| match s.split_once('=') {
+                        Some((name, default)) => Placeholder {
+                            name: name.trim().to_string(),
+                            default: Some(default.trim().to_string()),
+                        },
+                        None => Placeholder {
+                            name: s.to_string(),
+                            default: None,
+                        },
+                    })
                     .collect();
             }
         }
@@ -323,20 +742,47 @@ fn extract_placeholders(shortcut: &str) -> Vec<String> {
     Vec::new()
 }
 
-/// Create a mapping from placeholders to actual values
+/// Create a mapping from placeholders to actual values, using defaults for
+/// values that weren't given. Fails if a required parameter is missing.
 fn create_param_mapping(
-    placeholders: &[String],
+    placeholders: &[Placeholder],
     values: &[String],
-) -> std::collections::HashMap<String, String> {
+) -> Result<std::collections::HashMap<String, String>> {
     let mut map = std::collections::HashMap::new();
 
     for (i, placeholder) in placeholders.iter().enumerate() {
-        if i < values.len() {
-            map.insert(placeholder.clone(), values[i].clone());
-        }
+        let value = match (values.get(i), &placeholder.default) {
+            (Some(value), _) => value.clone(),
+            (None, Some(default)) => default.clone(),
+            (None, None) => {
+                return Err(SniptError::Other(format!(
+                    "Missing value for required parameter '{}'",
+                    placeholder.name
+                )))
+            }
+        };
+        map.insert(placeholder.name.clone(), value);
     }
 
-    map
+    Ok(map)
+}
+
+/// Positional values passed to a script: one per placeholder (defaults
+/// included), followed by any extra values that were typed
+fn resolve_params(placeholders: &[Placeholder], values: &[String]) -> Vec<String> {
+    let mut params: Vec<String> = placeholders
+        .iter()
+        .enumerate()
+        .map(|(i, placeholder)| {
+            values
+                .get(i)
+                .or(placeholder.default.as_ref())
+                .cloned()
+                .unwrap_or_default()
+        })
+        .collect();
+    params.extend(values.iter().skip(placeholders.len()).cloned());
+    params
 }
 
 /// Apply parameter mapping to snippet content
@@ -387,11 +833,44 @@ fn apply_param_mapping(
     result
 }
 
-/// Handle text expansion or script execution based on the expansion style
+/// Set to stop the expansion currently being typed
+static ABORT_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Stop the expansion currently being typed after its next keystroke. The
+/// expansion fails with `ExpansionAborted`.
+pub fn abort_expansion() {
+    ABORT_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Fail with `ExpansionAborted` once `abort_expansion` was called
+pub(crate) fn check_not_aborted() -> Result<()> {
+    if ABORT_REQUESTED.load(Ordering::SeqCst) {
+        Err(SniptError::ExpansionAborted)
+    } else {
+        Ok(())
+    }
+}
+
+/// Handle text expansion or script execution based on the expansion style.
+/// `to_delete` is the number of typed characters (not bytes) to erase first.
 pub fn handle_expansion(to_delete: usize, expansion_type: ExpansionType) -> Result<()> {
+    check_app_rules()?;
+    perform_expansion(to_delete, expansion_type)
+}
+
+/// Like `handle_expansion`, for callers that already ran `check_app_rules`
+pub fn perform_expansion(to_delete: usize, expansion_type: ExpansionType) -> Result<()> {
+    ABORT_REQUESTED.store(false, Ordering::SeqCst);
+
     match expansion_type {
         ExpansionType::Text(text, style, shortcut) => {
-            match style {
+            let tab_stops = expansion_tab_stops(&text, &style);
+            let text = match &tab_stops {
+                Some(tab_stops) => tab_stops.text.clone(),
+                None => text,
+            };
+
+            let result = match style {
                 ExpansionStyle::Default => {
                     // Original text expansion behavior
                     replace_text(to_delete, &text)
@@ -414,11 +893,17 @@ pub fn handle_expansion(to_delete: usize, expansion_type: ExpansionType) -> Resu
                         replace_text(to_delete, &text)
                     }
                 }
+                ExpansionStyle::RichText(html) => replace_rich_text(to_delete, &text, &html),
+            };
+
+            match tab_stops {
+                Some(tab_stops) if result.is_ok() => move_to_first_stop(&tab_stops),
+                _ => result,
             }
         }
         ExpansionType::Execute(content, style, shortcut) => {
             match style {
-                ExpansionStyle::Default => {
+                ExpansionStyle::Default | ExpansionStyle::RichText(_) => {
                     // Original execution behavior
                     execute_snippet(to_delete, &content, None)
                 }
@@ -444,7 +929,7 @@ pub fn handle_expansion(to_delete: usize, expansion_type: ExpansionType) -> Resu
         }
         ExpansionType::ExecuteWithParams(content, params, style, shortcut) => {
             match style {
-                ExpansionStyle::Default => {
+                ExpansionStyle::Default | ExpansionStyle::RichText(_) => {
                     // Original parameterized execution behavior
                     execute_snippet(to_delete, &content, Some(&params))
                 }
@@ -468,7 +953,69 @@ pub fn handle_expansion(to_delete: usize, expansion_type: ExpansionType) -> Resu
                 }
             }
         }
+        ExpansionType::ExecuteAndInsert(command, params, _, _) => {
+            // The output is always typed as plain text, whatever the app
+            execute_and_insert(to_delete, &command, &params)
+        }
+    }
+}
+
+/// Tab stops of a text expansion when `tab_stops` is on. Rich text snippets
+/// are pasted with their markers, since the HTML can't be edited reliably.
+pub fn expansion_tab_stops(text: &str, style: &ExpansionStyle) -> Option<TabStops> {
+    if matches!(style, ExpansionStyle::RichText(_)) || !current_config().tab_stops {
+        return None;
+    }
+    Some(parse_tab_stops(text))
+}
+
+/// Pick the expansion method for `app`: the override with the longest name
+/// contained in it, or `default`
+pub fn expansion_method_for(
+    app: &str,
+    default: ExpansionMethod,
+    overrides: &HashMap<String, ExpansionMethod>,
+) -> ExpansionMethod {
+    let app = app.to_lowercase();
+    overrides
+        .iter()
+        .filter(|(name, _)| !name.is_empty() && app.contains(&name.to_lowercase()))
+        .max_by_key(|(name, _)| name.len())
+        .map(|(_, method)| *method)
+        .unwrap_or(default)
+}
+
+/// The expansion method for the frontmost app
+fn current_expansion_method() -> ExpansionMethod {
+    let config = current_config();
+    if config.app_expansion_methods.is_empty() {
+        config.expansion_method
+    } else {
+        expansion_method_for(
+            &get_frontmost_app(),
+            config.expansion_method,
+            &config.app_expansion_methods,
+        )
+    }
+}
+
+/// Move the cursor back from the end of the expansion to its first tab stop.
+/// Only done after pasting: apps may auto-indent typed lines, which would
+/// throw the offsets off.
+fn move_to_first_stop(tab_stops: &TabStops) -> Result<()> {
+    if current_expansion_method() != ExpansionMethod::Clipboard {
+        return Ok(());
+    }
+
+    let mut keyboard = create_keyboard_controller()?;
+    for _ in 0..tab_stops.chars_after_cursor() {
+        check_not_aborted()?;
+        keyboard
+            .key(Key::LeftArrow, Direction::Click)
+            .map_err(|err| SniptError::Enigo(format!("Failed to move the cursor: {}", err)))?;
+        thread::sleep(Duration::from_millis(2));
     }
+    Ok(())
 }
 
 /// Format a hyperlink based on the specific application's native link format
@@ -494,13 +1041,23 @@ fn format_app_specific_hyperlink(app_name: &str, display_text: &str, url: &str)
     }
 }
 
-pub fn type_text_with_formatting(keyboard: &mut impl Keyboard, text: &str) -> Result<()> {
+/// Type text line by line. With a non-zero `delay_ms` every character is
+/// typed separately with that pause in between, for setups (remote desktops,
+/// VMs) that drop keystrokes sent in bulk.
+pub fn type_text_with_formatting(
+    keyboard: &mut impl Keyboard,
+    text: &str,
+    delay_ms: u64,
+) -> Result<()> {
     // Set a reasonable chunk size to avoid overwhelming the keyboard buffer
     // Increased chunk size for better performance
     const CHUNK_SIZE: usize = 1024;
+    let delay = Duration::from_millis(delay_ms);
+    let _self_typing = SelfTyping::start();
 
     // Split into lines and type each line with proper newlines
     for (i, line) in text.split('\n').enumerate() {
+        check_not_aborted()?;
         if i > 0 {
             // Type a newline between lines (not before the first line)
             match keyboard.key(Key::Return, Direction::Click) {
@@ -514,12 +1071,22 @@ pub fn type_text_with_formatting(keyboard: &mut impl Keyboard, text: &str) -> Re
             }
 
             // Reduced delay after newline
-            thread::sleep(Duration::from_millis(5));
+            thread::sleep(Duration::from_millis(5).max(delay));
         }
 
-        // If line is very long, split it into manageable chunks
-        if line.len() > CHUNK_SIZE {
+        if !delay.is_zero() {
+            let mut buf = [0; 4];
+            for c in line.chars() {
+                check_not_aborted()?;
+                keyboard
+                    .text(c.encode_utf8(&mut buf))
+                    .map_err(|err| SniptError::Enigo(format!("Failed to type text: {}", err)))?;
+                thread::sleep(delay);
+            }
+        } else if line.len() > CHUNK_SIZE {
+            // If line is very long, split it into manageable chunks
             for chunk in line.chars().collect::<Vec<_>>().chunks(CHUNK_SIZE) {
+                check_not_aborted()?;
                 let chunk_str: String = chunk.iter().collect();
                 match keyboard.text(&chunk_str) {
                     Ok(_) => {}
@@ -545,6 +1112,102 @@ pub fn type_text_with_formatting(keyboard: &mut impl Keyboard, text: &str) -> Re
     Ok(())
 }
 
+/// Insert text into the focused application using the configured expansion method
+pub fn insert_text(keyboard: &mut impl Keyboard, text: &str) -> Result<()> {
+    let config = current_config();
+    match current_expansion_method() {
+        ExpansionMethod::Typing => {
+            type_text_with_formatting(keyboard, text, config.typing_delay_ms)
+        }
+        ExpansionMethod::Clipboard => paste_text(keyboard, text, None, config.restore_clipboard),
+    }
+}
+
+/// Insert text by placing it on the clipboard and sending the paste shortcut,
+/// optionally restoring the previous clipboard text afterwards. With `html`,
+/// both an HTML and a plain-text flavor are offered and the app picks one.
+fn paste_text(
+    keyboard: &mut impl Keyboard,
+    text: &str,
+    html: Option<&str>,
+    restore: bool,
+) -> Result<()> {
+    // Keep one clipboard handle alive until the paste is done; on X11 the
+    // contents are only served while the owning handle exists
+    let mut clipboard = Clipboard::new().map_err(|e| SniptError::Clipboard(e.to_string()))?;
+    let previous = if restore {
+        SavedClipboard::save(&mut clipboard)
+    } else {
+        None
+    };
+
+    match html {
+        Some(html) => clipboard.set_html(html, Some(text)),
+        None => clipboard.set_text(text),
+    }
+    .map_err(|e| SniptError::Clipboard(e.to_string()))?;
+    thread::sleep(Duration::from_millis(20));
+
+    let result = send_paste_shortcut(keyboard);
+
+    if let Some(previous) = previous {
+        // Give the target app time to read the clipboard before restoring it
+        thread::sleep(Duration::from_millis(
+            current_config().restore_clipboard_delay_ms,
+        ));
+        previous.restore(&mut clipboard);
+    }
+
+    result
+}
+
+/// Clipboard contents saved before a paste expansion overwrites them
+enum SavedClipboard {
+    Text(String),
+    Image(ImageData<'static>),
+}
+
+impl SavedClipboard {
+    /// Save text or an image. Other contents, such as copied files, can't be
+    /// read back and are lost; so is an empty clipboard's emptiness, since
+    /// arboard reports both the same way.
+    fn save(clipboard: &mut Clipboard) -> Option<Self> {
+        if let Ok(text) = clipboard.get_text() {
+            return Some(Self::Text(text));
+        }
+        clipboard
+            .get_image()
+            .ok()
+            .map(|image| Self::Image(image.to_owned_img()))
+    }
+
+    /// Put the saved contents back, best effort
+    fn restore(self, clipboard: &mut Clipboard) {
+        let _ = match self {
+            Self::Text(text) => clipboard.set_text(text),
+            Self::Image(image) => clipboard.set_image(image),
+        };
+    }
+}
+
+/// Send Cmd+V on macOS and Ctrl+V elsewhere
+fn send_paste_shortcut(keyboard: &mut impl Keyboard) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    let modifier = Key::Meta;
+    #[cfg(not(target_os = "macos"))]
+    let modifier = Key::Control;
+
+    let to_error = |err| SniptError::Enigo(format!("Failed to send paste shortcut: {}", err));
+    let _self_typing = SelfTyping::start();
+    keyboard.key(modifier, Direction::Press).map_err(to_error)?;
+    let result = keyboard.key(Key::Unicode('v'), Direction::Click);
+    // Always release the modifier, even if the click failed
+    keyboard
+        .key(modifier, Direction::Release)
+        .map_err(to_error)?;
+    result.map_err(to_error)
+}
+
 /// Replace text in the editor by sending keyboard events
 pub fn replace_text(to_delete: usize, replacement: &str) -> Result<()> {
     let mut keyboard = create_keyboard_controller()?;
@@ -553,17 +1216,51 @@ pub fn replace_text(to_delete: usize, replacement: &str) -> Result<()> {
     send_backspace(&mut keyboard, to_delete)?;
 
     // Minimal delay before typing the replacement (reduced from 10ms)
-    thread::sleep(Duration::from_millis(3));
+    let delay = Duration::from_millis(current_config().typing_delay_ms);
+    thread::sleep(Duration::from_millis(3).max(delay));
 
-    // Type the expanded text with formatting preserved
-    type_text_with_formatting(&mut keyboard, replacement)?;
+    // Type or paste the expanded text with formatting preserved
+    insert_text(&mut keyboard, replacement)?;
 
     Ok(())
 }
 
+/// Paste a snippet into the focused application through the clipboard,
+/// whatever the configured expansion method. Nothing typed is deleted first.
+/// Parameters get their default values, as when the shortcut is typed without
+/// any.
+pub fn paste_snippet(entry: &SnippetEntry) -> Result<()> {
+    check_app_rules()?;
+    let text = expand_text(entry, &[])?;
+    let mut keyboard = create_keyboard_controller()?;
+    let html = entry.html.as_deref().map(resolve_body);
+    paste_text(
+        &mut keyboard,
+        &text,
+        html.as_deref(),
+        current_config().restore_clipboard,
+    )
+}
+
+/// Replace the typed shortcut with rich text. Formatting can only be
+/// transferred through the clipboard, so this always pastes.
+fn replace_rich_text(to_delete: usize, text: &str, html: &str) -> Result<()> {
+    let mut keyboard = create_keyboard_controller()?;
+    send_backspace(&mut keyboard, to_delete)?;
+    thread::sleep(Duration::from_millis(3));
+    paste_text(
+        &mut keyboard,
+        text,
+        Some(html),
+        current_config().restore_clipboard,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{EXECUTE_CHAR, SPECIAL_CHAR};
+    use crate::keyboard::is_self_typing;
     use crate::models::SnippetEntry;
 
     #[test]
@@ -641,16 +1338,8 @@ mod tests {
     #[test]
     fn test_hyperlink_expansion() {
         let snippets = vec![
-            SnippetEntry {
-                shortcut: "hello".to_string(),
-                snippet: "Hello, world!".to_string(),
-                timestamp: "2023-01-01T00:00:00+00:00".to_string(),
-            },
-            SnippetEntry {
-                shortcut: "link".to_string(),
-                snippet: "https://example.com".to_string(),
-                timestamp: "2023-01-01T00:00:00+00:00".to_string(),
-            },
+            SnippetEntry::new("hello".to_string(), "Hello, world!".to_string()),
+            SnippetEntry::new("link".to_string(), "https://example.com".to_string()),
         ];
 
         // In our tests, use the actual SPECIAL_CHAR constant from config
@@ -811,16 +1500,11 @@ mod tests {
     #[test]
     fn test_parameterized_shortcuts() {
         let snippets = vec![
-            SnippetEntry {
-                shortcut: "sum(a,b)".to_string(),
-                snippet: "The sum of $a and $b is ${a+b}".to_string(),
-                timestamp: "2023-01-01T00:00:00+00:00".to_string(),
-            },
-            SnippetEntry {
-                shortcut: "greet(name)".to_string(),
-                snippet: "Hello, $name!".to_string(),
-                timestamp: "2023-01-01T00:00:00+00:00".to_string(),
-            },
+            SnippetEntry::new(
+                "sum(a,b)".to_string(),
+                "The sum of $a and $b is ${a+b}".to_string(),
+            ),
+            SnippetEntry::new("greet(name)".to_string(), "Hello, $name!".to_string()),
         ];
 
         // Test parameterized expansion with sum
@@ -856,6 +1540,182 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_execute_and_insert() {
+        let snippets = vec![
+            SnippetEntry::new("date".to_string(), "$ date +%F".to_string()),
+            SnippetEntry::new("plain".to_string(), "date".to_string()),
+            SnippetEntry::new("echo(word)".to_string(), "$ echo $word".to_string()),
+        ];
+
+        let expansion = process_expansion(&format!("{}date", EXECUTE_CHAR), &snippets)
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            expansion,
+            ExpansionType::ExecuteAndInsert(_, _, _, _)
+        ));
+        assert_eq!(expansion.content(), "date +%F");
+        assert_eq!(expansion.shortcut(), Some("date"));
+
+        // Without the `$ ` prefix the snippet runs the old way
+        let expansion = process_expansion(&format!("{}plain", EXECUTE_CHAR), &snippets)
+            .unwrap()
+            .unwrap();
+        assert!(matches!(expansion, ExpansionType::Execute(_, _, _)));
+
+        // The text trigger still types the snippet body as is
+        let expansion = process_expansion(&format!("{}date", SPECIAL_CHAR), &snippets)
+            .unwrap()
+            .unwrap();
+        assert_eq!(expansion.content(), "$ date +%F");
+
+        let expansion = process_expansion(&format!("{}echo(hi)", EXECUTE_CHAR), &snippets)
+            .unwrap()
+            .unwrap();
+        assert_eq!(expansion.content(), "echo hi");
+        assert_eq!(expansion.params(), Some(&vec!["hi".to_string()]));
+    }
+
+    #[test]
+    fn test_snippet_trigger() {
+        let mut macro_entry = SnippetEntry::new("dbg".to_string(), "dbg!()".to_string());
+        macro_entry.trigger = Some(';');
+        let snippets = vec![
+            macro_entry,
+            SnippetEntry::new("sig".to_string(), "Best regards".to_string()),
+        ];
+        assert_eq!(
+            trigger_chars(&snippets),
+            vec![SPECIAL_CHAR, EXECUTE_CHAR, ';']
+        );
+
+        let expansion = process_expansion(";dbg", &snippets).unwrap().unwrap();
+        assert_eq!(expansion.content(), "dbg!()");
+        // The snippet no longer answers to the global trigger, and others
+        // don't answer to its trigger
+        assert!(
+            process_expansion(&format!("{}dbg", SPECIAL_CHAR), &snippets)
+                .unwrap()
+                .is_none()
+        );
+        assert!(process_expansion(";sig", &snippets).unwrap().is_none());
+        let expansion = process_expansion(&format!("{}sig", SPECIAL_CHAR), &snippets)
+            .unwrap()
+            .unwrap();
+        assert_eq!(expansion.content(), "Best regards");
+
+        // The execute trigger is unaffected
+        let expansion = process_expansion(&format!("{}dbg", EXECUTE_CHAR), &snippets)
+            .unwrap()
+            .unwrap();
+        assert!(matches!(expansion, ExpansionType::Execute(_, _, _)));
+    }
+
+    #[test]
+    fn test_count_prefix_matches() {
+        let mut macro_entry = SnippetEntry::new("dbg".to_string(), "dbg!()".to_string());
+        macro_entry.trigger = Some(';');
+        let mut disabled = SnippetEntry::new("longsig".to_string(), "Old".to_string());
+        disabled.enabled = false;
+        let snippets = vec![
+            macro_entry,
+            SnippetEntry::new("longshortcut".to_string(), "Long".to_string()),
+            SnippetEntry::new("lunch".to_string(), "Lunch".to_string()),
+            disabled,
+        ];
+
+        let typed = |text: &str| format!("{}{}", SPECIAL_CHAR, text);
+        assert_eq!(count_prefix_matches(&typed("longsh"), &snippets), 1);
+        assert_eq!(count_prefix_matches(&typed("l"), &snippets), 2);
+        assert_eq!(count_prefix_matches(&typed("longshortcutx"), &snippets), 0);
+        // A snippet with its own trigger only counts for that trigger
+        assert_eq!(count_prefix_matches(&typed("d"), &snippets), 0);
+        assert_eq!(count_prefix_matches(";d", &snippets), 1);
+        assert_eq!(
+            count_prefix_matches(&format!("{}d", EXECUTE_CHAR), &snippets),
+            1
+        );
+        assert_eq!(count_prefix_matches("", &snippets), 0);
+    }
+
+    #[test]
+    fn test_link_snippet() {
+        let mut docs = SnippetEntry::new("docs".to_string(), "https://example.com/a b".to_string());
+        docs.kind = SnippetKind::Link;
+        docs.label = Some("Docs [v2]".to_string());
+        let mut home = SnippetEntry::new("home".to_string(), " https://example.com ".to_string());
+        home.kind = SnippetKind::Link;
+        let snippets = vec![docs, home];
+
+        assert_eq!(
+            expand_text(&snippets[0], &[]).unwrap(),
+            "[Docs \\[v2\\]](<https://example.com/a b>)"
+        );
+        // Copying and pasting a link snippet give the same markdown link
+        assert_eq!(text_body(&snippets[1]), "[home](https://example.com)");
+
+        // The link doesn't depend on the frontmost app; the label defaults to the shortcut
+        match process_expansion(&format!("{}home", SPECIAL_CHAR), &snippets).unwrap() {
+            Some(ExpansionType::Text(text, ExpansionStyle::Default, _)) => {
+                assert_eq!(text, "[home](https://example.com)")
+            }
+            _ => panic!("expected a plain text expansion"),
+        }
+
+        // The execute trigger still gets the URL itself
+        let expansion = process_expansion(&format!("{}home", EXECUTE_CHAR), &snippets)
+            .unwrap()
+            .unwrap();
+        assert_eq!(expansion.content(), " https://example.com ");
+    }
+
+    #[test]
+    fn test_os_sections() {
+        let body = "config: {{os:macos}}~/Library{{/os}}{{os:linux}}~/.config{{/os}}{{os:windows}}%APPDATA%{{/os}}/app";
+        assert_eq!(
+            resolve_os_sections_for(body, "macos"),
+            "config: ~/Library/app"
+        );
+        assert_eq!(
+            resolve_os_sections_for(body, "linux"),
+            "config: ~/.config/app"
+        );
+        assert_eq!(
+            resolve_os_sections_for(body, "windows"),
+            "config: %APPDATA%/app"
+        );
+
+        // Several platforms in one section, and sections left as written
+        let body = "{{os:macos, linux}}unix{{/os}} {{os:LINUX}}tux{{/os}}";
+        assert_eq!(resolve_os_sections_for(body, "macos"), "unix ");
+        assert_eq!(resolve_os_sections_for(body, "linux"), "unix tux");
+        assert_eq!(resolve_os_sections_for(body, "windows"), " ");
+        let unclosed = "{{os:linux}}never closed";
+        assert_eq!(resolve_os_sections_for(unclosed, "linux"), unclosed);
+        assert_eq!(
+            resolve_os_sections_for("no sections", "linux"),
+            "no sections"
+        );
+
+        // Expansions resolve against the platform snipt was built for
+        let snippets = vec![SnippetEntry::new(
+            "open".to_string(),
+            "{{os:macos}}open{{/os}}{{os:linux}}xdg-open{{/os}}{{os:windows}}start{{/os}} ."
+                .to_string(),
+        )];
+        let expansion = process_expansion(&format!("{}open", EXECUTE_CHAR), &snippets)
+            .unwrap()
+            .unwrap();
+        #[cfg(target_os = "macos")]
+        assert_eq!(expansion.content(), "open .");
+        #[cfg(target_os = "linux")]
+        assert_eq!(expansion.content(), "xdg-open .");
+        #[cfg(target_os = "windows")]
+        assert_eq!(expansion.content(), "start .");
+        assert_eq!(expand_text(&snippets[0], &[]).unwrap(), expansion.content());
+    }
+
     #[test]
     fn test_parameter_mapping() {
         // Test basic parameter mapping
@@ -895,4 +1755,286 @@ mod tests {
         assert!(result.starts_with("All params: "));
         assert!(result.contains("All params with braces: "));
     }
+
+    #[test]
+    fn test_parameter_defaults() {
+        let snippets = vec![SnippetEntry::new(
+            "greet(name, greeting=Hello, punct=!)".to_string(),
+            "$greeting, $name$punct".to_string(),
+        )];
+
+        let expand =
+            |args: &str| process_expansion(&format!("{}greet({})", EXECUTE_CHAR, args), &snippets);
+
+        // Missing: the required parameter has no default
+        match expand("") {
+            Err(SniptError::Other(msg)) => assert!(msg.contains("'name'")),
+            _ => panic!("expected a missing parameter error"),
+        }
+
+        // Partial: defaults fill the rest, and are passed on as positional params
+        match expand("World").unwrap() {
+            Some(ExpansionType::ExecuteWithParams(content, params, _, shortcut)) => {
+                assert_eq!(content, "Hello, World!");
+                assert_eq!(params, vec!["World", "Hello", "!"]);
+                assert_eq!(shortcut, "greet");
+            }
+            _ => panic!("expected a parameterized expansion"),
+        }
+
+        // Full: every default is overridden
+        match expand("Ada, Hi, ?").unwrap() {
+            Some(ExpansionType::ExecuteWithParams(content, params, _, _)) => {
+                assert_eq!(content, "Hi, Ada?");
+                assert_eq!(params, vec!["Ada", "Hi", "?"]);
+            }
+            _ => panic!("expected a parameterized expansion"),
+        }
+    }
+
+    #[test]
+    fn test_expand_template() {
+        let snippets = vec![
+            SnippetEntry::new("sig".to_string(), "Jane".to_string()),
+            SnippetEntry::new(
+                "greet(name, greeting=Hello)".to_string(),
+                "$greeting, $name".to_string(),
+            ),
+        ];
+        let t = SPECIAL_CHAR;
+
+        let input = format!("Hi {t}greet(Ada)\n\n{t}greet(Bo, Hey) -- {t}sig\t{t}nope sig{t}sig");
+        assert_eq!(
+            expand_template(&input, &snippets).unwrap(),
+            format!("Hi Hello, Ada\n\nHey, Bo -- Jane\t{t}nope sig{t}sig")
+        );
+
+        // A missing required parameter is an error rather than silent output
+        assert!(expand_template(&format!("{t}greet"), &snippets).is_err());
+    }
+
+    #[test]
+    fn test_expand_template_own_trigger() {
+        let mut custom = SnippetEntry::new("d".to_string(), "done".to_string());
+        custom.trigger = Some(';');
+        let snippets = vec![
+            custom,
+            SnippetEntry::new("sig".to_string(), "Jane".to_string()),
+        ];
+        let t = SPECIAL_CHAR;
+
+        // Each snippet expands only after its own trigger
+        let input = format!(";d {t}sig {t}d ;sig");
+        assert_eq!(
+            expand_template_with(&input, &snippets, false).unwrap(),
+            format!("done Jane {t}d ;sig")
+        );
+    }
+
+    #[test]
+    fn test_escaped_trigger() {
+        let snippets = vec![SnippetEntry::new("foo".to_string(), "Foo!".to_string())];
+        let t = SPECIAL_CHAR;
+
+        assert_eq!(
+            unescape_trigger(&format!("{t}{t}foo"), t),
+            Some(&*format!("{t}foo"))
+        );
+        assert_eq!(unescape_trigger(&format!("{t}foo"), t), None);
+        assert_eq!(unescape_trigger(&format!("a{t}{t}foo"), t), None);
+
+        // The doubled trigger types one literal trigger and `foo` stays as is
+        let input = format!("{t}{t}foo and {t}foo");
+        assert_eq!(
+            expand_template_with(&input, &snippets, true).unwrap(),
+            format!("{t}foo and Foo!")
+        );
+        // Without escapes the doubled trigger is just an unknown reference
+        assert_eq!(
+            expand_template_with(&input, &snippets, false).unwrap(),
+            format!("{t}{t}foo and Foo!")
+        );
+
+        // A snippet's own trigger escapes the same way, the execute
+        // character doesn't
+        let mut custom = SnippetEntry::new("d".to_string(), "done".to_string());
+        custom.trigger = Some(';');
+        let snippets = vec![custom];
+        let input = format!(";;d ;d {e}{e}d", e = EXECUTE_CHAR);
+        assert_eq!(
+            expand_template_with(&input, &snippets, true).unwrap(),
+            format!(";d done {e}{e}d", e = EXECUTE_CHAR)
+        );
+    }
+
+    #[test]
+    fn test_match_trigger_case() {
+        // Lowercase trigger: unchanged
+        assert_eq!(match_trigger_case("brb", "be right back"), "be right back");
+        // Leading capital: capitalize the first letter
+        assert_eq!(match_trigger_case("Brb", "be right back"), "Be right back");
+        // All caps: uppercase everything
+        assert_eq!(match_trigger_case("BRB", "be right back"), "BE RIGHT BACK");
+
+        // A single capital letter is a leading capital, not all caps
+        assert_eq!(match_trigger_case("B", "be right back"), "Be right back");
+        assert_eq!(match_trigger_case("T2", "thanks"), "Thanks");
+        assert_eq!(match_trigger_case("42", "answer"), "answer");
+    }
+
+    #[test]
+    fn test_disabled_snippet() {
+        let mut entry = SnippetEntry::new("old".to_string(), "Season's greetings".to_string());
+        entry.enabled = false;
+        let snippets = vec![entry];
+
+        let buffer = format!("{}old", SPECIAL_CHAR);
+        assert!(process_expansion(&buffer, &snippets).unwrap().is_none());
+        assert_eq!(expand_template(&buffer, &snippets).unwrap(), buffer);
+    }
+
+    #[test]
+    fn test_rich_text_expansion() {
+        let mut entry = SnippetEntry::new("sig".to_string(), "Jane Doe".to_string());
+        entry.html = Some("<b>Jane Doe</b>".to_string());
+        let snippets = vec![entry];
+
+        // The plain text stays the fallback while the HTML rides along in the style
+        match process_expansion(&format!("{}sig", SPECIAL_CHAR), &snippets).unwrap() {
+            Some(ExpansionType::Text(text, ExpansionStyle::RichText(html), _)) => {
+                assert_eq!(text, "Jane Doe");
+                assert_eq!(html, "<b>Jane Doe</b>");
+            }
+            _ => panic!("expected a rich text expansion"),
+        }
+
+        // Executing the same snippet ignores the HTML body
+        assert!(matches!(
+            process_expansion(&format!("{}sig", EXECUTE_CHAR), &snippets).unwrap(),
+            Some(ExpansionType::Execute(_, ExpansionStyle::Default, _))
+                | Some(ExpansionType::Execute(_, ExpansionStyle::Hyperlink, _))
+        ));
+    }
+
+    #[test]
+    fn test_app_rules() {
+        let blocklist = vec!["1password".to_string(), "terminal".to_string()];
+        let allowlist = vec!["code".to_string(), "terminal".to_string()];
+
+        // No rules: everything is allowed, even an unknown app
+        assert!(is_app_allowed("Slack", &[], &[]));
+        assert!(is_app_allowed("", &[], &[]));
+
+        // Case-insensitive substring match against the blocklist
+        assert!(!is_app_allowed("1Password 7", &blocklist, &[]));
+        assert!(is_app_allowed("Slack", &blocklist, &[]));
+
+        // Allowlist restricts to matching apps; the blocklist still wins
+        assert!(is_app_allowed("Visual Studio Code", &blocklist, &allowlist));
+        assert!(!is_app_allowed("Slack", &blocklist, &allowlist));
+        assert!(!is_app_allowed("Terminal", &blocklist, &allowlist));
+        assert!(!is_app_allowed("", &[], &allowlist));
+    }
+
+    #[test]
+    fn test_expansion_method_for() {
+        let overrides = HashMap::from([
+            ("term".to_string(), ExpansionMethod::Typing),
+            ("Windows Terminal".to_string(), ExpansionMethod::Clipboard),
+        ]);
+        let default = ExpansionMethod::Clipboard;
+
+        // An override beats the global default
+        assert_eq!(
+            expansion_method_for("gnome-terminal-server", default, &overrides),
+            ExpansionMethod::Typing
+        );
+        // The most specific name wins
+        assert_eq!(
+            expansion_method_for("windows terminal", default, &overrides),
+            ExpansionMethod::Clipboard
+        );
+        // Apps without an override use the default
+        assert_eq!(
+            expansion_method_for("Code", default, &overrides),
+            ExpansionMethod::Clipboard
+        );
+        assert_eq!(
+            expansion_method_for("Terminal", ExpansionMethod::Typing, &HashMap::new()),
+            ExpansionMethod::Typing
+        );
+        assert_eq!(
+            expansion_method_for("", default, &overrides),
+            ExpansionMethod::Clipboard
+        );
+    }
+
+    /// Records typed text, and how many keystrokes were sent without the
+    /// self-typing flag set
+    #[derive(Default)]
+    struct RecordingKeyboard {
+        typed: String,
+        unflagged: usize,
+    }
+
+    impl RecordingKeyboard {
+        fn record(&mut self) {
+            if !is_self_typing() {
+                self.unflagged += 1;
+            }
+        }
+    }
+
+    impl Keyboard for RecordingKeyboard {
+        fn fast_text(&mut self, text: &str) -> enigo::InputResult<Option<()>> {
+            self.record();
+            self.typed.push_str(text);
+            Ok(Some(()))
+        }
+
+        fn key(&mut self, key: Key, _direction: Direction) -> enigo::InputResult<()> {
+            self.record();
+            match key {
+                Key::Return => self.typed.push('\n'),
+                Key::Unicode(c) => self.typed.push(c),
+                _ => {}
+            }
+            Ok(())
+        }
+
+        fn raw(&mut self, _keycode: u16, _direction: Direction) -> enigo::InputResult<()> {
+            self.record();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_typed_expansion_is_not_reexpanded() {
+        let snippets = vec![
+            SnippetEntry::new("greet".to_string(), format!("Hi,\nsee {}sig", SPECIAL_CHAR)),
+            SnippetEntry::new("sig".to_string(), "Regards".to_string()),
+        ];
+        let text = process_expansion(&format!("{}greet", SPECIAL_CHAR), &snippets)
+            .unwrap()
+            .unwrap()
+            .content()
+            .to_string();
+
+        // The expanded text ends in another shortcut, which would expand
+        // again if the listener buffered it
+        let tail = &text[text.rfind(SPECIAL_CHAR).unwrap()..];
+        assert!(process_expansion(tail, &snippets).unwrap().is_some());
+
+        // Every keystroke is sent with the flag the listener checks, both
+        // typed at once and one character at a time
+        for delay_ms in [0, 1] {
+            let mut keyboard = RecordingKeyboard::default();
+            type_text_with_formatting(&mut keyboard, &text, delay_ms).unwrap();
+            assert_eq!(keyboard.typed, text);
+            assert_eq!(keyboard.unflagged, 0);
+        }
+
+        // The flag is down again as soon as the typing is done
+        assert!(!is_self_typing());
+    }
 }
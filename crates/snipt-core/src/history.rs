@@ -0,0 +1,222 @@
+//! Rolling log of performed expansions, stored as JSON lines in the config directory.
+//!
+//! Only the shortcut and how it was expanded are recorded, never the expanded text.
+//! How often each shortcut was expanded is counted separately in `usage.json`,
+//! which isn't capped by `history_limit` or turned off with the history.
+
+use crate::config::{get_config_dir, HISTORY_FILENAME, USAGE_FILENAME};
+use crate::error::Result;
+use crate::expansion::{expansion_tab_stops, ExpansionStyle, ExpansionType};
+use crate::storage::write_atomically;
+use crate::tabstops::TabStop;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    pub timestamp: String,
+    pub shortcut: String,
+    /// "text" or "execute"
+    pub kind: String,
+    /// "default", "hyperlink" or "richtext"
+    pub style: String,
+    /// Tab stops of the expanded text in the order they are visited, when
+    /// `tab_stops` is on. The cursor was left at the first one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tab_stops: Vec<TabStop>,
+}
+
+impl HistoryEntry {
+    /// Describe an expansion that is about to be performed
+    pub fn for_expansion(expansion: &ExpansionType) -> Self {
+        let kind = if expansion.is_text() {
+            "text"
+        } else {
+            "execute"
+        };
+        let style = match expansion.style() {
+            ExpansionStyle::Default => "default",
+            ExpansionStyle::Hyperlink => "hyperlink",
+            ExpansionStyle::RichText(_) => "richtext",
+        };
+
+        let tab_stops = match expansion {
+            ExpansionType::Text(text, style, _) => expansion_tab_stops(text, style)
+                .map(|tab_stops| tab_stops.stops)
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+
+        Self {
+            timestamp: Local::now().to_rfc3339(),
+            shortcut: expansion.shortcut().unwrap_or_default().to_string(),
+            kind: kind.to_string(),
+            style: style.to_string(),
+            tab_stops,
+        }
+    }
+
+    /// Local time of the expansion in a human readable format
+    pub fn formatted_time(&self) -> String {
+        DateTime::parse_from_rfc3339(&self.timestamp)
+            .map(|dt| {
+                dt.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| self.timestamp.clone())
+    }
+}
+
+/// Get the path to the expansion history file
+pub fn get_history_file_path() -> PathBuf {
+    get_config_dir().join(HISTORY_FILENAME)
+}
+
+/// Load the expansion history, oldest first. Unreadable lines are skipped.
+pub fn load_history() -> Result<Vec<HistoryEntry>> {
+    let path = get_history_file_path();
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Append an entry to the history, keeping at most `limit` entries
+pub fn append_history(entry: HistoryEntry, limit: usize) -> Result<()> {
+    append_history_to(&get_history_file_path(), &entry, limit)
+}
+
+/// Append `entry` as one line. Only once the file holds more than `limit`
+/// entries is it rewritten, keeping the newest ones with some room to spare so
+/// the next appends don't rewrite it again.
+fn append_history_to(path: &Path, entry: &HistoryEntry, limit: usize) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    drop(file);
+
+    let content = fs::read_to_string(path)?;
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.len() <= limit {
+        return Ok(());
+    }
+    let keep = limit - limit / 10;
+    let mut trimmed = lines[lines.len() - keep..].join("\n");
+    if !trimmed.is_empty() {
+        trimmed.push('\n');
+    }
+    write_atomically(path, trimmed.as_bytes())?;
+    Ok(())
+}
+
+/// Get the path to the usage counts file
+pub fn get_usage_file_path() -> PathBuf {
+    get_config_dir().join(USAGE_FILENAME)
+}
+
+/// Expansions per shortcut. Until `usage.json` exists they are counted from
+/// the history, where older versions kept them.
+fn load_usage() -> Result<HashMap<String, usize>> {
+    let path = get_usage_file_path();
+    if path.exists() {
+        return Ok(serde_json::from_str(&fs::read_to_string(path)?)?);
+    }
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for entry in load_history()? {
+        *counts.entry(entry.shortcut).or_insert(0) += 1;
+    }
+    Ok(counts)
+}
+
+fn save_usage(counts: &HashMap<String, usize>) -> Result<()> {
+    write_atomically(
+        &get_usage_file_path(),
+        serde_json::to_string_pretty(counts)?.as_bytes(),
+    )?;
+    Ok(())
+}
+
+/// Count one more expansion of `shortcut`
+pub fn record_usage(shortcut: &str) -> Result<()> {
+    let mut counts = load_usage()?;
+    *counts.entry(shortcut.to_string()).or_insert(0) += 1;
+    save_usage(&counts)
+}
+
+/// Number of expansions per shortcut, most used first
+pub fn usage_counts() -> Result<Vec<(String, usize)>> {
+    let mut counts: Vec<(String, usize)> = load_usage()?.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(counts)
+}
+
+/// Set every usage count back to zero. The history is left alone.
+pub fn reset_usage_counts() -> Result<()> {
+    save_usage(&HashMap::new())
+}
+
+/// Remove all recorded history
+pub fn clear_history() -> Result<()> {
+    let path = get_history_file_path();
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(shortcut: &str) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: "2026-01-01T12:00:00+00:00".to_string(),
+            shortcut: shortcut.to_string(),
+            kind: "text".to_string(),
+            style: "default".to_string(),
+            tab_stops: Vec::new(),
+        }
+    }
+
+    fn shortcuts(path: &Path) -> Vec<String> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<HistoryEntry>(line).unwrap().shortcut)
+            .collect()
+    }
+
+    #[test]
+    fn test_append_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(HISTORY_FILENAME);
+
+        // Entries are appended one line each while under the limit
+        for i in 0..10 {
+            append_history_to(&path, &entry(&format!("s{}", i)), 10).unwrap();
+        }
+        let expected: Vec<String> = (0..10).map(|i| format!("s{}", i)).collect();
+        assert_eq!(shortcuts(&path), expected);
+
+        // Going over the limit keeps the newest entries, with room to spare
+        append_history_to(&path, &entry("s10"), 10).unwrap();
+        let expected: Vec<String> = (2..11).map(|i| format!("s{}", i)).collect();
+        assert_eq!(shortcuts(&path), expected);
+        append_history_to(&path, &entry("s11"), 10).unwrap();
+        assert_eq!(shortcuts(&path).len(), 10);
+
+        // A limit of zero keeps nothing
+        append_history_to(&path, &entry("s12"), 0).unwrap();
+        assert!(shortcuts(&path).is_empty());
+    }
+}
@@ -0,0 +1,469 @@
+//! Importers for snippet collections from snipt and other expansion tools.
+
+use crate::crypto::{decrypt, is_encrypted, passphrase};
+use crate::error::{Result, SniptError};
+use crate::models::SnippetEntry;
+use crate::storage::parse_database;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Snippets parsed from an import source, along with anything worth telling the user
+#[derive(Debug, Default)]
+pub struct ParsedImport {
+    pub entries: Vec<SnippetEntry>,
+    pub warnings: Vec<String>,
+}
+
+/// Read a snipt JSON database (as written by `save_snippets`)
+pub fn read_snipt_json(path: &Path) -> Result<ParsedImport> {
+    let mut content = fs::read_to_string(path)?;
+    if is_encrypted(&content) {
+        content = decrypt(&content, &passphrase()?)?;
+    }
+    let entries = parse_database(&content)?;
+
+    Ok(ParsedImport {
+        entries,
+        warnings: vec![],
+    })
+}
+
+#[derive(Deserialize)]
+struct EspansoFile {
+    #[serde(default)]
+    matches: Vec<EspansoMatch>,
+}
+
+#[derive(Deserialize)]
+struct EspansoMatch {
+    trigger: Option<String>,
+    #[serde(default)]
+    triggers: Vec<String>,
+    replace: Option<String>,
+    markdown: Option<String>,
+    html: Option<String>,
+    #[serde(default)]
+    vars: Vec<EspansoVar>,
+}
+
+#[derive(Deserialize)]
+struct EspansoVar {
+    name: String,
+    #[serde(rename = "type")]
+    var_type: String,
+}
+
+/// Read Espanso match files from a single YAML file or a directory of them
+/// (such as `~/.config/espanso/match`). When importing a directory, snippets
+/// are grouped by the file they came from (`email.yml` becomes the `email`
+/// group), except for Espanso's default `base.yml`.
+pub fn read_espanso_matches(path: &Path) -> Result<ParsedImport> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        collect_yaml_files(path, &mut files)?;
+        files.sort();
+    } else {
+        files.push(path.to_path_buf());
+    }
+
+    let mut parsed = ParsedImport::default();
+    for file in files {
+        let content = fs::read_to_string(&file)?;
+        let file_import = parse_espanso(&content).map_err(|e| {
+            SniptError::Other(format!(
+                "Failed to parse Espanso file {}: {}",
+                file.display(),
+                e
+            ))
+        })?;
+        let group = espanso_file_group(path, &file);
+        parsed
+            .entries
+            .extend(file_import.entries.into_iter().map(|mut entry| {
+                entry.group = group.clone();
+                entry
+            }));
+        parsed.warnings.extend(file_import.warnings);
+    }
+
+    Ok(parsed)
+}
+
+/// Group for snippets from `file` inside the imported directory `root`
+fn espanso_file_group(root: &Path, file: &Path) -> Option<String> {
+    let relative = file.strip_prefix(root).ok()?.with_extension("");
+    let group = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    (!group.is_empty() && group != "base").then_some(group)
+}
+
+fn collect_yaml_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_yaml_files(&path, files)?;
+        } else if matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("yml") | Some("yaml")
+        ) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Parse the contents of an Espanso match file.
+///
+/// Each trigger becomes a shortcut with its leading `:` removed. Espanso
+/// variables (`{{name}}`) have no snipt equivalent yet, so they are kept as
+/// literal text and reported as warnings.
+pub fn parse_espanso(content: &str) -> std::result::Result<ParsedImport, String> {
+    let file: EspansoFile = serde_yaml::from_str(content).map_err(|e| e.to_string())?;
+    let mut parsed = ParsedImport::default();
+
+    for espanso_match in file.matches {
+        let mut triggers = espanso_match.triggers;
+        if let Some(trigger) = espanso_match.trigger {
+            triggers.insert(0, trigger);
+        }
+
+        let body = match espanso_match
+            .replace
+            .or(espanso_match.markdown)
+            .or(espanso_match.html)
+        {
+            Some(body) => body,
+            None => {
+                parsed.warnings.push(format!(
+                    "Skipped '{}': only text replacements can be imported",
+                    triggers.join(", ")
+                ));
+                continue;
+            }
+        };
+
+        for trigger in triggers {
+            let shortcut = trigger.strip_prefix(':').unwrap_or(&trigger).to_string();
+            if shortcut.is_empty() {
+                continue;
+            }
+
+            for var in &espanso_match.vars {
+                parsed.warnings.push(format!(
+                    "'{}': {} variable '{{{{{}}}}}' kept as literal text",
+                    shortcut, var.var_type, var.name
+                ));
+            }
+
+            parsed
+                .entries
+                .push(SnippetEntry::new(shortcut, body.clone()));
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// A column of a CSV or TSV file, chosen by header name or 1-based position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    Name(String),
+    Index(usize),
+}
+
+impl Column {
+    /// Parse a `--*-col` value: a number is a position, anything else a header
+    pub fn parse(value: &str) -> Column {
+        match value.trim().parse::<usize>() {
+            Ok(index) => Column::Index(index),
+            Err(_) => Column::Name(value.trim().to_string()),
+        }
+    }
+
+    /// Find the column's 0-based position in the header row
+    fn position(&self, header: &[String]) -> Result<usize> {
+        let position = match self {
+            Column::Index(index) => index.checked_sub(1).filter(|&i| i < header.len()),
+            Column::Name(name) => header
+                .iter()
+                .position(|field| field.trim().eq_ignore_ascii_case(name)),
+        };
+        position.ok_or_else(|| {
+            SniptError::Other(format!(
+                "Column {} not found. The file's columns are: {}",
+                self,
+                header.join(", ")
+            ))
+        })
+    }
+}
+
+impl std::fmt::Display for Column {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Column::Name(name) => write!(f, "'{}'", name),
+            Column::Index(index) => write!(f, "{}", index),
+        }
+    }
+}
+
+/// Which columns of a CSV or TSV file hold the shortcut, the snippet body and
+/// optionally the tags
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMapping {
+    pub shortcut: Column,
+    pub body: Column,
+    pub tags: Option<Column>,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        Self {
+            shortcut: Column::Index(1),
+            body: Column::Index(2),
+            tags: None,
+        }
+    }
+}
+
+/// Read snippets from a CSV (`delimiter` `,`) or TSV (`\t`) file. The first
+/// row names the columns.
+pub fn read_delimited(
+    path: &Path,
+    delimiter: char,
+    mapping: &ColumnMapping,
+) -> Result<ParsedImport> {
+    parse_delimited(&fs::read_to_string(path)?, delimiter, mapping)
+}
+
+/// Parse CSV or TSV content with a header row into snippets. Every row must
+/// have a shortcut and a body; blank rows are ignored.
+pub fn parse_delimited(
+    content: &str,
+    delimiter: char,
+    mapping: &ColumnMapping,
+) -> Result<ParsedImport> {
+    // Spreadsheet apps often start their exports with a byte order mark
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut rows = split_records(content, delimiter)?.into_iter();
+    let Some((_, header)) = rows.next() else {
+        return Err(SniptError::Other("The file is empty".to_string()));
+    };
+
+    let shortcut_col = mapping.shortcut.position(&header)?;
+    let body_col = mapping.body.position(&header)?;
+    let tags_col = mapping
+        .tags
+        .as_ref()
+        .map(|column| column.position(&header))
+        .transpose()?;
+
+    let mut parsed = ParsedImport::default();
+    for (line, row) in rows {
+        if row.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+
+        let field = |col: usize| row.get(col).map(String::as_str).unwrap_or("");
+        let shortcut = field(shortcut_col).trim();
+        let body = field(body_col);
+        if shortcut.is_empty() || body.is_empty() {
+            return Err(SniptError::Other(format!(
+                "Row on line {} has an empty {}",
+                line,
+                if shortcut.is_empty() {
+                    "shortcut"
+                } else {
+                    "body"
+                }
+            )));
+        }
+
+        let mut entry = SnippetEntry::new(shortcut.to_string(), body.to_string());
+        if let Some(col) = tags_col {
+            entry.tags = split_tags(field(col));
+        }
+        parsed.entries.push(entry);
+    }
+
+    Ok(parsed)
+}
+
+/// Split a tags field such as "work, email; Customer Support" into tags.
+/// Tags are single words, so spaces inside one become dashes.
+fn split_tags(field: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in field.split([',', ';']) {
+        let tag = tag.split_whitespace().collect::<Vec<_>>().join("-");
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Split delimited text into records, each with the line it starts on.
+/// Fields may be quoted with `"` to hold delimiters, line breaks or `""`.
+fn split_records(content: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut record)));
+                line += 1;
+                record_line = line;
+            }
+            c if c == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(SniptError::Other(format!(
+            "Unclosed quote in the row starting on line {}",
+            record_line
+        )));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_espanso() {
+        let yaml = r#"
+matches:
+  - trigger: ":hello"
+    replace: "Hello, world!"
+  - triggers: [":sig", "signature"]
+    replace: |
+      Best regards,
+      Jane
+  - trigger: ":now"
+    replace: "It is {{mytime}}"
+    vars:
+      - name: mytime
+        type: date
+        params:
+          format: "%H:%M"
+  - trigger: ":pic"
+    image_path: "/tmp/cat.png"
+"#;
+
+        let parsed = parse_espanso(yaml).unwrap();
+        let shortcuts: Vec<&str> = parsed
+            .entries
+            .iter()
+            .map(|entry| entry.shortcut.as_str())
+            .collect();
+        assert_eq!(shortcuts, vec!["hello", "sig", "signature", "now"]);
+
+        assert_eq!(parsed.entries[0].snippet, "Hello, world!");
+        assert_eq!(parsed.entries[1].snippet, "Best regards,\nJane\n");
+        assert_eq!(parsed.entries[3].snippet, "It is {{mytime}}");
+
+        // One warning for the literal date variable, one for the image match
+        assert_eq!(parsed.warnings.len(), 2);
+    }
+
+    #[test]
+    fn test_espanso_file_group() {
+        let root = Path::new("match");
+        let group = |file: &str| espanso_file_group(root, &root.join(file));
+
+        // base.yml holds ungrouped matches, other files name their group
+        assert_eq!(group("base.yml"), None);
+        assert_eq!(group("emails.yml").as_deref(), Some("emails"));
+        assert_eq!(group("code/rust.yaml").as_deref(), Some("code/rust"));
+        assert_eq!(group("code/base.yml").as_deref(), Some("code/base"));
+
+        // Files outside the imported directory get no group
+        assert_eq!(
+            espanso_file_group(root, Path::new("other/emails.yml")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_delimited() {
+        let csv = "\u{feff}Tags,Body,Name\r\n\"work, email; Customer  Support;work\",\"Hi, \"\"team\"\"\nBye\",greet\r\n,,\r\n,plain,sig\n";
+        let mapping = ColumnMapping {
+            shortcut: Column::parse("name"),
+            body: Column::parse("2"),
+            tags: Some(Column::parse("Tags")),
+        };
+        let parsed = parse_delimited(csv, ',', &mapping).unwrap();
+        assert_eq!(parsed.entries.len(), 2);
+        assert_eq!(parsed.entries[0].shortcut, "greet");
+        assert_eq!(parsed.entries[0].snippet, "Hi, \"team\"\nBye");
+        assert_eq!(
+            parsed.entries[0].tags,
+            vec!["work", "email", "Customer-Support"]
+        );
+        assert_eq!(parsed.entries[0].group, None);
+        assert_eq!(parsed.entries[1].shortcut, "sig");
+        assert!(parsed.entries[1].tags.is_empty());
+
+        // The default mapping is shortcut then body, and TSV works the same way
+        let parsed = parse_delimited("a\tb\nsig\tBest,\n", '\t', &ColumnMapping::default());
+        assert_eq!(parsed.unwrap().entries[0].snippet, "Best,");
+
+        // Missing columns, empty fields and broken quoting are errors
+        let missing = ColumnMapping {
+            body: Column::parse("text"),
+            ..ColumnMapping::default()
+        };
+        assert!(parse_delimited("shortcut,body\n", ',', &missing).is_err());
+        let out_of_range = ColumnMapping {
+            body: Column::Index(3),
+            ..ColumnMapping::default()
+        };
+        assert!(parse_delimited("shortcut,body\n", ',', &out_of_range).is_err());
+        let error =
+            parse_delimited("shortcut,body\nsig,\n", ',', &ColumnMapping::default()).unwrap_err();
+        assert_eq!(error.to_string(), "Error: Row on line 2 has an empty body");
+        assert!(parse_delimited(
+            "shortcut,body\nsig,\"open\n",
+            ',',
+            &ColumnMapping::default()
+        )
+        .is_err());
+    }
+}
@@ -1,7 +1,11 @@
 use crate::error::{Result, SniptError};
+use crate::expansion::{check_not_aborted, get_frontmost_app};
+use crate::settings::{current_config, DeletionMethod, SniptConfig};
 use enigo::Keyboard;
 use enigo::{Direction, Enigo, Key, Settings};
 use rdev::{self, Key as RdevKey};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread;
 use std::time::Duration;
 
@@ -50,21 +54,297 @@ pub fn rdev_key_to_char(key: &RdevKey, event: &rdev::Event) -> Option<char> {
     None
 }
 
-/// Create a keyboard controller
+/// Modifier keys held down while another key is pressed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    /// Cmd on macOS, the Windows/Super key elsewhere
+    pub meta: bool,
+}
+
+impl Modifiers {
+    /// Whether a key pressed with these modifiers is a command such as Ctrl+C
+    /// rather than typed text. Option types characters on macOS, and Windows
+    /// reports AltGr as Ctrl+Alt, so neither counts there. Elsewhere Ctrl+Alt
+    /// is a chord.
+    pub fn is_chord(&self) -> bool {
+        let alt = self.alt && !cfg!(target_os = "macos");
+        let alt_gr = cfg!(windows) && self.ctrl && self.alt;
+        self.meta || ((self.ctrl || alt) && !alt_gr)
+    }
+}
+
+/// Modifier keys tracked by [`ModifierState`], left and right separately
+const MODIFIER_KEYS: [RdevKey; 7] = [
+    RdevKey::ShiftLeft,
+    RdevKey::ShiftRight,
+    RdevKey::ControlLeft,
+    RdevKey::ControlRight,
+    RdevKey::Alt,
+    RdevKey::MetaLeft,
+    RdevKey::MetaRight,
+];
+
+/// Modifier keys held down, kept up to date from key press and release
+/// events. Letting go of one Shift while the other is still held keeps
+/// Shift down.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModifierState {
+    held: [bool; MODIFIER_KEYS.len()],
+}
+
+impl ModifierState {
+    /// Record a press or release of `key`, returning whether it is a modifier
+    pub fn update(&mut self, key: &RdevKey, pressed: bool) -> bool {
+        match MODIFIER_KEYS.iter().position(|modifier| modifier == key) {
+            Some(i) => {
+                self.held[i] = pressed;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The modifiers currently held
+    pub fn modifiers(&self) -> Modifiers {
+        let [shift_left, shift_right, ctrl_left, ctrl_right, alt, meta_left, meta_right] =
+            self.held;
+        Modifiers {
+            ctrl: ctrl_left || ctrl_right,
+            alt,
+            shift: shift_left || shift_right,
+            meta: meta_left || meta_right,
+        }
+    }
+}
+
+/// Whether `key` is itself a modifier
+pub fn is_modifier_key(key: &RdevKey) -> bool {
+    MODIFIER_KEYS.contains(key) || *key == RdevKey::AltGr
+}
+
+/// A global key combination such as `Ctrl+Alt+Space`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hotkey {
+    modifiers: Modifiers,
+    key: RdevKey,
+}
+
+impl Hotkey {
+    /// Parse a `+`-separated combination of modifiers (`Ctrl`, `Alt`, `Shift`,
+    /// `Cmd`/`Super`/`Meta`) and one key: a letter, digit, `F1`-`F12`, `Space`,
+    /// `Tab`, `Enter` or `Esc`. Anything but a function key needs a modifier
+    /// so the hotkey can't swallow ordinary typing.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let invalid = |reason: &str| {
+            SniptError::InvalidConfig(format!("Invalid hotkey '{}': {}", spec, reason))
+        };
+
+        let mut modifiers = Modifiers::default();
+        let mut key = None;
+        for part in spec.split('+').map(str::trim) {
+            match part.to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers.ctrl = true,
+                "alt" | "option" => modifiers.alt = true,
+                "shift" => modifiers.shift = true,
+                "cmd" | "command" | "super" | "meta" | "win" => modifiers.meta = true,
+                name => {
+                    if key.is_some() {
+                        return Err(invalid("only one non-modifier key is allowed"));
+                    }
+                    key = Some(hotkey_key(name).ok_or_else(|| invalid("unknown key"))?);
+                }
+            }
+        }
+
+        let key = key.ok_or_else(|| invalid("missing a key after the modifiers"))?;
+        let is_function_key = matches!(
+            key,
+            RdevKey::F1
+                | RdevKey::F2
+                | RdevKey::F3
+                | RdevKey::F4
+                | RdevKey::F5
+                | RdevKey::F6
+                | RdevKey::F7
+                | RdevKey::F8
+                | RdevKey::F9
+                | RdevKey::F10
+                | RdevKey::F11
+                | RdevKey::F12
+        );
+        if modifiers == Modifiers::default() && !is_function_key {
+            return Err(invalid("add a modifier such as Ctrl or Alt"));
+        }
+
+        Ok(Self { modifiers, key })
+    }
+
+    /// Check whether a key press with the given modifiers held triggers this hotkey
+    pub fn matches(&self, key: &RdevKey, modifiers: Modifiers) -> bool {
+        *key == self.key && modifiers == self.modifiers
+    }
+}
+
+/// The hotkeys set in a config, parsed once when it is loaded rather than on
+/// every key event
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Hotkeys {
+    pub picker: Option<Hotkey>,
+    pub abort: Option<Hotkey>,
+    pub expand: Option<Hotkey>,
+    pub repeat: Option<Hotkey>,
+}
+
+impl Hotkeys {
+    pub fn from_config(config: &SniptConfig) -> Result<Self> {
+        let parse = |spec: &Option<String>| spec.as_deref().map(Hotkey::parse).transpose();
+        Ok(Self {
+            picker: parse(&config.picker_hotkey)?,
+            abort: parse(&config.abort_hotkey)?,
+            expand: parse(&config.expand_hotkey)?,
+            repeat: parse(&config.repeat_hotkey)?,
+        })
+    }
+}
+
+/// Key for a (lowercase) hotkey key name
+fn hotkey_key(name: &str) -> Option<RdevKey> {
+    let key = match name {
+        "a" => RdevKey::KeyA,
+        "b" => RdevKey::KeyB,
+        "c" => RdevKey::KeyC,
+        "d" => RdevKey::KeyD,
+        "e" => RdevKey::KeyE,
+        "f" => RdevKey::KeyF,
+        "g" => RdevKey::KeyG,
+        "h" => RdevKey::KeyH,
+        "i" => RdevKey::KeyI,
+        "j" => RdevKey::KeyJ,
+        "k" => RdevKey::KeyK,
+        "l" => RdevKey::KeyL,
+        "m" => RdevKey::KeyM,
+        "n" => RdevKey::KeyN,
+        "o" => RdevKey::KeyO,
+        "p" => RdevKey::KeyP,
+        "q" => RdevKey::KeyQ,
+        "r" => RdevKey::KeyR,
+        "s" => RdevKey::KeyS,
+        "t" => RdevKey::KeyT,
+        "u" => RdevKey::KeyU,
+        "v" => RdevKey::KeyV,
+        "w" => RdevKey::KeyW,
+        "x" => RdevKey::KeyX,
+        "y" => RdevKey::KeyY,
+        "z" => RdevKey::KeyZ,
+        "0" => RdevKey::Num0,
+        "1" => RdevKey::Num1,
+        "2" => RdevKey::Num2,
+        "3" => RdevKey::Num3,
+        "4" => RdevKey::Num4,
+        "5" => RdevKey::Num5,
+        "6" => RdevKey::Num6,
+        "7" => RdevKey::Num7,
+        "8" => RdevKey::Num8,
+        "9" => RdevKey::Num9,
+        "f1" => RdevKey::F1,
+        "f2" => RdevKey::F2,
+        "f3" => RdevKey::F3,
+        "f4" => RdevKey::F4,
+        "f5" => RdevKey::F5,
+        "f6" => RdevKey::F6,
+        "f7" => RdevKey::F7,
+        "f8" => RdevKey::F8,
+        "f9" => RdevKey::F9,
+        "f10" => RdevKey::F10,
+        "f11" => RdevKey::F11,
+        "f12" => RdevKey::F12,
+        "space" => RdevKey::Space,
+        "tab" => RdevKey::Tab,
+        "enter" | "return" => RdevKey::Return,
+        "esc" | "escape" => RdevKey::Escape,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// Key for a `terminator_keys` setting entry: `space`, `enter` or `tab`
+pub fn terminator_key(name: &str) -> Option<RdevKey> {
+    match name.to_lowercase().as_str() {
+        "space" => Some(RdevKey::Space),
+        "enter" | "return" => Some(RdevKey::Return),
+        "tab" => Some(RdevKey::Tab),
+        _ => None,
+    }
+}
+
+/// Number of `SelfTyping` guards alive
+static SELF_TYPING: AtomicUsize = AtomicUsize::new(0);
+
+/// Marks keystrokes snipt sends itself while it's alive, so the keyboard
+/// listener doesn't buffer them and a snippet whose text contains another
+/// shortcut doesn't expand again as it's typed. The mark goes away as soon as
+/// the guard is dropped, so the user's own typing right after an expansion
+/// is never mistaken for snipt's.
+pub struct SelfTyping(());
+
+impl SelfTyping {
+    pub fn start() -> Self {
+        SELF_TYPING.fetch_add(1, Ordering::SeqCst);
+        SelfTyping(())
+    }
+}
+
+impl Drop for SelfTyping {
+    fn drop(&mut self) {
+        SELF_TYPING.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Whether snipt is typing right now, so a key event is probably its own
+pub fn is_self_typing() -> bool {
+    SELF_TYPING.load(Ordering::SeqCst) > 0
+}
+
+/// How many times to try creating the keyboard controller before giving up
+const CONTROLLER_ATTEMPTS: u32 = 4;
+
+/// Delay before the first retry; doubled after each failed attempt
+const CONTROLLER_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Create a keyboard controller.
+///
+/// The input subsystem may not be ready yet right after login, so creation is
+/// retried a few times with backoff (about 350ms in total) before failing.
 pub fn create_keyboard_controller() -> Result<Enigo> {
     // For Enigo 0.3.0 which requires Settings
     let settings = Settings::default();
-    match Enigo::new(&settings) {
-        Ok(enigo) => Ok(enigo),
-        Err(err) => Err(SniptError::Enigo(format!(
-            "Failed to create keyboard controller: {}",
-            err
-        ))),
+    let mut delay = CONTROLLER_RETRY_DELAY;
+    let mut attempt = 1;
+
+    loop {
+        match Enigo::new(&settings) {
+            Ok(enigo) => return Ok(enigo),
+            Err(err) if attempt >= CONTROLLER_ATTEMPTS => {
+                return Err(SniptError::Enigo(format!(
+                    "Failed to create keyboard controller after {} attempts: {}",
+                    CONTROLLER_ATTEMPTS, err
+                )));
+            }
+            Err(_) => {
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+        }
     }
 }
 
 /// Type text using the keyboard controller
 pub fn type_text(keyboard: &mut Enigo, text: &str) -> Result<()> {
+    let _self_typing = SelfTyping::start();
     // For Enigo 0.3.0 which has a text method
     match keyboard.text(text) {
         Ok(_) => Ok(()),
@@ -72,22 +352,244 @@ pub fn type_text(keyboard: &mut Enigo, text: &str) -> Result<()> {
     }
 }
 
-/// Send backspace key presses
+/// Number of backspaces needed to erase typed text. Each backspace removes a
+/// character, so multibyte characters (accents, emoji) count once.
+pub fn deletion_count(typed: &str) -> usize {
+    typed.chars().count()
+}
+
+/// Pick the deletion method for `app`: the override with the longest name
+/// contained in it, or `default`
+pub fn deletion_method_for(
+    app: &str,
+    default: DeletionMethod,
+    overrides: &HashMap<String, DeletionMethod>,
+) -> DeletionMethod {
+    let app = app.to_lowercase();
+    overrides
+        .iter()
+        .filter(|(name, _)| !name.is_empty() && app.contains(&name.to_lowercase()))
+        .max_by_key(|(name, _)| name.len())
+        .map(|(_, method)| *method)
+        .unwrap_or(default)
+}
+
+/// Delete the `count` characters before the cursor with the configured
+/// deletion method
 pub fn send_backspace(keyboard: &mut Enigo, count: usize) -> Result<()> {
-    for _ in 0..count {
-        // Reduced delay to speed up deletion
-        thread::sleep(Duration::from_millis(2));
-
-        // Use the key method with Direction::Click for Enigo 0.3.0
-        match keyboard.key(Key::Backspace, Direction::Click) {
-            Ok(_) => {}
-            Err(err) => {
-                return Err(SniptError::Enigo(format!(
-                    "Failed to send backspace: {}",
-                    err
-                )))
+    if count == 0 {
+        return Ok(());
+    }
+    let _self_typing = SelfTyping::start();
+
+    let config = current_config();
+    let method = if config.app_deletion_methods.is_empty() {
+        config.deletion_method
+    } else {
+        deletion_method_for(
+            &get_frontmost_app(),
+            config.deletion_method,
+            &config.app_deletion_methods,
+        )
+    };
+    let delay = Duration::from_millis(config.deletion_delay_ms);
+    let to_error = |what: &str, err| SniptError::Enigo(format!("Failed to send {}: {}", what, err));
+
+    match method {
+        DeletionMethod::Backspace => {
+            for _ in 0..count {
+                check_not_aborted()?;
+                thread::sleep(delay);
+                keyboard
+                    .key(Key::Backspace, Direction::Click)
+                    .map_err(|err| to_error("backspace", err))?;
             }
+            Ok(())
+        }
+        DeletionMethod::Select => {
+            keyboard
+                .key(Key::Shift, Direction::Press)
+                .map_err(|err| to_error("Shift", err))?;
+            let selected = (0..count).try_for_each(|_| {
+                check_not_aborted()?;
+                thread::sleep(delay);
+                keyboard
+                    .key(Key::LeftArrow, Direction::Click)
+                    .map_err(|err| to_error("Shift+Left", err))
+            });
+            // Always release Shift, even if selecting failed or was aborted
+            keyboard
+                .key(Key::Shift, Direction::Release)
+                .map_err(|err| to_error("Shift", err))?;
+            selected?;
+
+            thread::sleep(delay);
+            keyboard
+                .key(Key::Backspace, Direction::Click)
+                .map_err(|err| to_error("backspace", err))
         }
     }
-    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deletion_count() {
+        // ":café" is 5 characters but 6 bytes
+        assert_eq!(deletion_count(":café"), 5);
+        assert_eq!(deletion_count(":🎉party"), 7);
+        assert_eq!(deletion_count(":sig"), 4);
+    }
+
+    #[test]
+    fn test_deletion_method_for() {
+        let overrides = HashMap::from([
+            ("code".to_string(), DeletionMethod::Select),
+            ("Visual Studio Code".to_string(), DeletionMethod::Backspace),
+        ]);
+        let default = DeletionMethod::Backspace;
+        assert_eq!(
+            deletion_method_for("Code - Insiders", default, &overrides),
+            DeletionMethod::Select
+        );
+        // The most specific name wins
+        assert_eq!(
+            deletion_method_for("visual studio code", default, &overrides),
+            DeletionMethod::Backspace
+        );
+        assert_eq!(
+            deletion_method_for("Firefox", DeletionMethod::Select, &overrides),
+            DeletionMethod::Select
+        );
+        assert_eq!(
+            deletion_method_for("Firefox", default, &HashMap::new()),
+            DeletionMethod::Backspace
+        );
+    }
+
+    #[test]
+    fn test_modifiers_is_chord() {
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Modifiers::default()
+        };
+        assert!(ctrl.is_chord());
+        assert!(Modifiers {
+            meta: true,
+            ..Modifiers::default()
+        }
+        .is_chord());
+        // Shift types characters
+        assert!(!Modifiers {
+            shift: true,
+            ..Modifiers::default()
+        }
+        .is_chord());
+        // Alt is a chord except for macOS's Option key
+        let alt = Modifiers {
+            alt: true,
+            ..Modifiers::default()
+        };
+        assert_eq!(alt.is_chord(), !cfg!(target_os = "macos"));
+        // Ctrl+Alt is AltGr only on Windows
+        let ctrl_alt = Modifiers { alt: true, ..ctrl };
+        assert_eq!(ctrl_alt.is_chord(), !cfg!(windows));
+        assert!(is_modifier_key(&RdevKey::ControlLeft));
+        assert!(!is_modifier_key(&RdevKey::KeyC));
+    }
+
+    #[test]
+    fn test_modifier_state() {
+        let mut state = ModifierState::default();
+        assert!(state.update(&RdevKey::ShiftLeft, true));
+        assert!(state.update(&RdevKey::ShiftRight, true));
+        assert!(state.update(&RdevKey::ControlRight, true));
+        assert!(!state.update(&RdevKey::KeyA, true));
+        assert_eq!(
+            state.modifiers(),
+            Modifiers {
+                ctrl: true,
+                shift: true,
+                ..Modifiers::default()
+            }
+        );
+
+        // The other Shift is still down
+        state.update(&RdevKey::ShiftLeft, false);
+        state.update(&RdevKey::ControlRight, false);
+        assert!(state.modifiers().shift);
+        assert!(!state.modifiers().ctrl);
+        state.update(&RdevKey::ShiftRight, false);
+        assert_eq!(state.modifiers(), Modifiers::default());
+    }
+
+    #[test]
+    fn test_hotkey_parse() {
+        let hotkey = Hotkey::parse("Ctrl+Alt+Space").unwrap();
+        let ctrl_alt = Modifiers {
+            ctrl: true,
+            alt: true,
+            ..Default::default()
+        };
+        assert!(hotkey.matches(&RdevKey::Space, ctrl_alt));
+        assert!(!hotkey.matches(&RdevKey::Space, Modifiers::default()));
+        assert!(!hotkey.matches(&RdevKey::Tab, ctrl_alt));
+
+        assert!(Hotkey::parse("F9").is_ok());
+        assert!(Hotkey::parse("cmd + shift + p").is_ok());
+        // A bare letter would fire while typing
+        assert!(Hotkey::parse("p").is_err());
+        assert!(Hotkey::parse("Ctrl+Alt").is_err());
+        assert!(Hotkey::parse("Ctrl+P+Q").is_err());
+        assert!(Hotkey::parse("Ctrl+Banana").is_err());
+    }
+
+    #[test]
+    fn test_terminator_key() {
+        assert_eq!(terminator_key("space"), Some(RdevKey::Space));
+        assert_eq!(terminator_key("Enter"), Some(RdevKey::Return));
+        assert_eq!(terminator_key("return"), Some(RdevKey::Return));
+        assert_eq!(terminator_key("TAB"), Some(RdevKey::Tab));
+        // Symbols complete shortcuts through expand_on_non_alphanumeric instead
+        assert_eq!(terminator_key("."), None);
+        assert_eq!(terminator_key("esc"), None);
+
+        // By default Space, Enter and Tab end a shortcut and symbols don't
+        let config = SniptConfig::default();
+        let keys: Vec<RdevKey> = config
+            .terminator_keys
+            .iter()
+            .filter_map(|name| terminator_key(name))
+            .collect();
+        assert_eq!(keys, [RdevKey::Space, RdevKey::Return, RdevKey::Tab]);
+        assert!(!config.expand_on_non_alphanumeric);
+    }
+
+    #[test]
+    fn test_hotkeys_from_config() {
+        let config = SniptConfig {
+            repeat_hotkey: Some("Ctrl+Alt+R".to_string()),
+            ..SniptConfig::default()
+        };
+        let hotkeys = Hotkeys::from_config(&config).unwrap();
+        let ctrl_alt = Modifiers {
+            ctrl: true,
+            alt: true,
+            ..Default::default()
+        };
+        assert!(hotkeys
+            .repeat
+            .is_some_and(|hotkey| hotkey.matches(&RdevKey::KeyR, ctrl_alt)));
+        assert_eq!(hotkeys.picker, None);
+        assert_eq!(hotkeys.expand, None);
+
+        // An invalid hotkey fails the whole config instead of being skipped
+        let config = SniptConfig {
+            abort_hotkey: Some("x".to_string()),
+            ..config
+        };
+        assert!(Hotkeys::from_config(&config).is_err());
+    }
 }
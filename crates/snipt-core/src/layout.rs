@@ -0,0 +1,301 @@
+//! Keyboard layout handling for turning physical key presses into characters.
+//!
+//! With the default `auto` layout the character reported by the OS is used.
+//! On systems where that is unreliable, a fixed layout can be chosen and
+//! individual keys can be remapped through `layout_overrides`.
+
+use crate::error::{Result, SniptError};
+use crate::keyboard::rdev_key_to_char;
+use crate::settings::SniptConfig;
+use rdev::{self, Key as RdevKey};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Layouts snipt knows how to map without help from the OS
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LayoutKind {
+    /// Trust the character the OS reports for each key event
+    #[default]
+    Auto,
+    /// US QWERTY
+    Us,
+    /// German QWERTZ
+    #[serde(alias = "de")]
+    Qwertz,
+    /// French AZERTY
+    #[serde(alias = "fr")]
+    Azerty,
+}
+
+/// Resolved layout including any user overrides
+#[derive(Debug, Clone, Default)]
+pub struct KeyboardLayout {
+    kind: LayoutKind,
+    overrides: HashMap<String, char>,
+}
+
+impl KeyboardLayout {
+    pub fn new(kind: LayoutKind) -> Self {
+        Self {
+            kind,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Build the layout from the user configuration, validating overrides
+    pub fn from_config(config: &SniptConfig) -> Result<Self> {
+        let mut layout = Self::new(config.keyboard_layout);
+
+        for (key, value) in &config.layout_overrides {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => {
+                    layout.overrides.insert(key.clone(), c);
+                }
+                _ => {
+                    return Err(SniptError::InvalidConfig(format!(
+                        "Layout override for '{}' must be a single character, got '{}'",
+                        key, value
+                    )))
+                }
+            }
+        }
+
+        Ok(layout)
+    }
+
+    /// Convert a key press to the character it produces on this layout
+    pub fn key_to_char(&self, key: &RdevKey, event: &rdev::Event, shift: bool) -> Option<char> {
+        let key_name = format!("{:?}", key);
+        let override_name = if shift {
+            format!("Shift+{}", key_name)
+        } else {
+            key_name
+        };
+        if let Some(c) = self.overrides.get(&override_name) {
+            return Some(*c);
+        }
+
+        let mapped = match self.kind {
+            LayoutKind::Auto => None,
+            LayoutKind::Us => us_char(key, shift),
+            LayoutKind::Qwertz => qwertz_char(key, shift),
+            LayoutKind::Azerty => azerty_char(key, shift),
+        };
+
+        mapped.or_else(|| rdev_key_to_char(key, event))
+    }
+}
+
+/// Letter printed on a key in the US layout
+fn us_letter(key: &RdevKey) -> Option<char> {
+    let c = match key {
+        RdevKey::KeyA => 'a',
+        RdevKey::KeyB => 'b',
+        RdevKey::KeyC => 'c',
+        RdevKey::KeyD => 'd',
+        RdevKey::KeyE => 'e',
+        RdevKey::KeyF => 'f',
+        RdevKey::KeyG => 'g',
+        RdevKey::KeyH => 'h',
+        RdevKey::KeyI => 'i',
+        RdevKey::KeyJ => 'j',
+        RdevKey::KeyK => 'k',
+        RdevKey::KeyL => 'l',
+        RdevKey::KeyM => 'm',
+        RdevKey::KeyN => 'n',
+        RdevKey::KeyO => 'o',
+        RdevKey::KeyP => 'p',
+        RdevKey::KeyQ => 'q',
+        RdevKey::KeyR => 'r',
+        RdevKey::KeyS => 's',
+        RdevKey::KeyT => 't',
+        RdevKey::KeyU => 'u',
+        RdevKey::KeyV => 'v',
+        RdevKey::KeyW => 'w',
+        RdevKey::KeyX => 'x',
+        RdevKey::KeyY => 'y',
+        RdevKey::KeyZ => 'z',
+        _ => return None,
+    };
+    Some(c)
+}
+
+fn shifted_letter(c: char, shift: bool) -> char {
+    if shift {
+        c.to_ascii_uppercase()
+    } else {
+        c
+    }
+}
+
+fn us_char(key: &RdevKey, shift: bool) -> Option<char> {
+    if let Some(c) = us_letter(key) {
+        return Some(shifted_letter(c, shift));
+    }
+
+    let (plain, shifted) = match key {
+        RdevKey::Num1 => ('1', '!'),
+        RdevKey::Num2 => ('2', '@'),
+        RdevKey::Num3 => ('3', '#'),
+        RdevKey::Num4 => ('4', '$'),
+        RdevKey::Num5 => ('5', '%'),
+        RdevKey::Num6 => ('6', '^'),
+        RdevKey::Num7 => ('7', '&'),
+        RdevKey::Num8 => ('8', '*'),
+        RdevKey::Num9 => ('9', '('),
+        RdevKey::Num0 => ('0', ')'),
+        RdevKey::Minus => ('-', '_'),
+        RdevKey::Equal => ('=', '+'),
+        RdevKey::LeftBracket => ('[', '{'),
+        RdevKey::RightBracket => (']', '}'),
+        RdevKey::SemiColon => (';', ':'),
+        RdevKey::Quote => ('\'', '"'),
+        RdevKey::BackQuote => ('`', '~'),
+        RdevKey::BackSlash => ('\\', '|'),
+        RdevKey::Comma => (',', '<'),
+        RdevKey::Dot => ('.', '>'),
+        RdevKey::Slash => ('/', '?'),
+        _ => return None,
+    };
+    Some(if shift { shifted } else { plain })
+}
+
+fn qwertz_char(key: &RdevKey, shift: bool) -> Option<char> {
+    if let Some(c) = us_letter(key) {
+        let c = match c {
+            'y' => 'z',
+            'z' => 'y',
+            other => other,
+        };
+        return Some(shifted_letter(c, shift));
+    }
+
+    // Dead keys (´ and ^) are left to the OS
+    let (plain, shifted) = match key {
+        RdevKey::Num1 => ('1', '!'),
+        RdevKey::Num2 => ('2', '"'),
+        RdevKey::Num3 => ('3', '§'),
+        RdevKey::Num4 => ('4', '$'),
+        RdevKey::Num5 => ('5', '%'),
+        RdevKey::Num6 => ('6', '&'),
+        RdevKey::Num7 => ('7', '/'),
+        RdevKey::Num8 => ('8', '('),
+        RdevKey::Num9 => ('9', ')'),
+        RdevKey::Num0 => ('0', '='),
+        RdevKey::Minus => ('ß', '?'),
+        RdevKey::LeftBracket => ('ü', 'Ü'),
+        RdevKey::RightBracket => ('+', '*'),
+        RdevKey::SemiColon => ('ö', 'Ö'),
+        RdevKey::Quote => ('ä', 'Ä'),
+        RdevKey::BackSlash => ('#', '\''),
+        RdevKey::IntlBackslash => ('<', '>'),
+        RdevKey::Comma => (',', ';'),
+        RdevKey::Dot => ('.', ':'),
+        RdevKey::Slash => ('-', '_'),
+        _ => return None,
+    };
+    Some(if shift { shifted } else { plain })
+}
+
+fn azerty_char(key: &RdevKey, shift: bool) -> Option<char> {
+    if let Some(c) = us_letter(key) {
+        let c = match c {
+            'q' => 'a',
+            'a' => 'q',
+            'w' => 'z',
+            'z' => 'w',
+            'm' => return Some(if shift { '?' } else { ',' }),
+            other => other,
+        };
+        return Some(shifted_letter(c, shift));
+    }
+
+    // Dead keys (^ and ¨) are left to the OS
+    let (plain, shifted) = match key {
+        RdevKey::Num1 => ('&', '1'),
+        RdevKey::Num2 => ('é', '2'),
+        RdevKey::Num3 => ('"', '3'),
+        RdevKey::Num4 => ('\'', '4'),
+        RdevKey::Num5 => ('(', '5'),
+        RdevKey::Num6 => ('-', '6'),
+        RdevKey::Num7 => ('è', '7'),
+        RdevKey::Num8 => ('_', '8'),
+        RdevKey::Num9 => ('ç', '9'),
+        RdevKey::Num0 => ('à', '0'),
+        RdevKey::Minus => (')', '°'),
+        RdevKey::Equal => ('=', '+'),
+        RdevKey::RightBracket => ('$', '£'),
+        RdevKey::SemiColon => ('m', 'M'),
+        RdevKey::Quote => ('ù', '%'),
+        RdevKey::BackSlash => ('*', 'µ'),
+        RdevKey::IntlBackslash => ('<', '>'),
+        RdevKey::Comma => (';', '.'),
+        RdevKey::Dot => (':', '/'),
+        RdevKey::Slash => ('!', '§'),
+        _ => return None,
+    };
+    Some(if shift { shifted } else { plain })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rdev::{Event, EventType};
+    use std::time::SystemTime;
+
+    fn press(key: RdevKey, name: Option<&str>) -> Event {
+        Event {
+            time: SystemTime::now(),
+            name: name.map(|n| n.to_string()),
+            event_type: EventType::KeyPress(key),
+        }
+    }
+
+    #[test]
+    fn test_layout_mappings() {
+        let event = press(RdevKey::KeyY, Some("y"));
+
+        // Auto trusts the OS-provided name
+        let auto = KeyboardLayout::new(LayoutKind::Auto);
+        assert_eq!(auto.key_to_char(&RdevKey::KeyY, &event, false), Some('y'));
+
+        let qwertz = KeyboardLayout::new(LayoutKind::Qwertz);
+        assert_eq!(qwertz.key_to_char(&RdevKey::KeyY, &event, false), Some('z'));
+        assert_eq!(qwertz.key_to_char(&RdevKey::Dot, &event, true), Some(':'));
+        assert_eq!(
+            qwertz.key_to_char(&RdevKey::Quote, &event, false),
+            Some('ä')
+        );
+
+        let azerty = KeyboardLayout::new(LayoutKind::Azerty);
+        assert_eq!(azerty.key_to_char(&RdevKey::KeyQ, &event, false), Some('a'));
+        assert_eq!(azerty.key_to_char(&RdevKey::Dot, &event, false), Some(':'));
+        assert_eq!(
+            azerty.key_to_char(&RdevKey::Slash, &event, false),
+            Some('!')
+        );
+    }
+
+    #[test]
+    fn test_layout_overrides() {
+        let mut config = SniptConfig {
+            keyboard_layout: LayoutKind::Us,
+            ..Default::default()
+        };
+        config
+            .layout_overrides
+            .insert("Shift+Num7".to_string(), "/".to_string());
+
+        let layout = KeyboardLayout::from_config(&config).unwrap();
+        let event = press(RdevKey::Num7, None);
+        assert_eq!(layout.key_to_char(&RdevKey::Num7, &event, true), Some('/'));
+        assert_eq!(layout.key_to_char(&RdevKey::Num7, &event, false), Some('7'));
+
+        config
+            .layout_overrides
+            .insert("KeyQ".to_string(), "too long".to_string());
+        assert!(KeyboardLayout::from_config(&config).is_err());
+    }
+}
@@ -1,17 +1,38 @@
 pub mod clipboard;
 pub mod config;
+pub mod crypto;
+pub mod dates;
 pub mod error;
 pub mod execution;
 pub mod expansion;
+pub mod history;
+pub mod import;
 pub mod keyboard;
+pub mod layout;
+pub mod metrics;
 pub mod models;
+pub mod quiet_hours;
+pub mod secure_input;
+pub mod settings;
 pub mod storage;
+pub mod tabstops;
 
 // Re-export common items for convenience
-pub use clipboard::{get_clipboard_text, has_clipboard_text, set_clipboard_text};
-pub use config::{get_config_dir, is_daemon_running, EXECUTE_CHAR, SPECIAL_CHAR};
+pub use clipboard::{
+    get_clipboard_text, has_clipboard_text, hold_clipboard_text, set_clipboard_text,
+};
+pub use config::{
+    execute_char, get_config_dir, is_daemon_running, trigger_char, EXECUTE_CHAR, SPECIAL_CHAR,
+};
 pub use error::{Result, SniptError};
 pub use execution::is_url;
 pub use expansion::{determine_expansion_style, handle_expansion, ExpansionStyle, ExpansionType};
-pub use models::SnippetEntry;
-pub use storage::{add_snippet, delete_snippet, load_snippets, update_snippet};
+pub use models::{ImportRecord, PackInfo, SnippetEntry, SnippetKind, SnippetPack, TrashEntry};
+pub use settings::{apply_config, load_config, save_config, SniptConfig};
+pub use storage::{
+    add_entry, add_snippet, add_tag, backup_database, delete_snippet, duplicate_snippet,
+    empty_trash, import_snippets, load_snippets, load_trash, remove_tag, restore_snippet,
+    set_snippet_description, set_snippet_group, set_snippet_kind, set_snippet_trigger,
+    suggest_shortcuts, tag_counts, toggle_pin, toggle_snippet, undo_import, update_snippet,
+    upsert_snippet, ImportSummary, UndoSummary,
+};
@@ -0,0 +1,140 @@
+//! Counters the running daemon shares with the API server, which serves them
+//! in the Prometheus text format on `GET /metrics`.
+//!
+//! The daemon rewrites `daemon_metrics.json` in the config directory about
+//! once a second and removes it when it stops. Expansions per snippet come
+//! from the usage counts instead, so they survive restarts.
+
+use crate::config::get_metrics_file_path;
+use crate::error::Result;
+use crate::storage::write_atomically;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::fs;
+
+/// Counters of the running daemon
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct DaemonMetrics {
+    /// When the daemon started, in seconds since the Unix epoch
+    pub started_at: u64,
+    /// Expansions completed since the daemon started
+    pub expansions: u64,
+    /// Config and snippet reloads since the daemon started
+    pub reloads: u64,
+}
+
+/// Save the daemon's counters for the API server. The file is replaced in one
+/// step so the server never reads a half-written one.
+pub fn write_daemon_metrics(metrics: &DaemonMetrics) -> Result<()> {
+    write_atomically(
+        &get_metrics_file_path(),
+        serde_json::to_string(metrics)?.as_bytes(),
+    )?;
+    Ok(())
+}
+
+/// Counters last saved by the daemon, if any
+pub fn read_daemon_metrics() -> Option<DaemonMetrics> {
+    let content = fs::read_to_string(get_metrics_file_path()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Remove the counters when the daemon stops
+pub fn clear_daemon_metrics() {
+    let _ = fs::remove_file(get_metrics_file_path());
+}
+
+/// Render metrics in the Prometheus text format. `daemon` is `None` when the
+/// daemon isn't running; `usage` is the number of expansions per shortcut.
+pub fn render_metrics(
+    daemon: Option<&DaemonMetrics>,
+    usage: &[(String, usize)],
+    now: u64,
+) -> String {
+    let mut out = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, samples: Vec<(String, u64)>| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        for (labels, value) in samples {
+            let _ = writeln!(out, "{}{} {}", name, labels, value);
+        }
+    };
+
+    metric(
+        "snipt_daemon_up",
+        "gauge",
+        "Whether the daemon is running",
+        vec![(String::new(), daemon.is_some() as u64)],
+    );
+    if let Some(daemon) = daemon {
+        metric(
+            "snipt_daemon_uptime_seconds",
+            "gauge",
+            "Seconds since the daemon started",
+            vec![(String::new(), now.saturating_sub(daemon.started_at))],
+        );
+        metric(
+            "snipt_expansions_total",
+            "counter",
+            "Expansions completed since the daemon started",
+            vec![(String::new(), daemon.expansions)],
+        );
+        metric(
+            "snipt_reloads_total",
+            "counter",
+            "Config and snippet reloads since the daemon started",
+            vec![(String::new(), daemon.reloads)],
+        );
+    }
+    metric(
+        "snipt_snippet_expansions",
+        "gauge",
+        "Expansions per shortcut in the usage counts",
+        usage
+            .iter()
+            .map(|(shortcut, count)| {
+                (
+                    format!("{{shortcut=\"{}\"}}", escape_label(shortcut)),
+                    *count as u64,
+                )
+            })
+            .collect(),
+    );
+    out
+}
+
+/// Escape a label value as the text format requires
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_metrics() {
+        let daemon = DaemonMetrics {
+            started_at: 1_000,
+            expansions: 42,
+            reloads: 3,
+        };
+        let usage = vec![("sig".to_string(), 12), ("a\"b".to_string(), 1)];
+        let text = render_metrics(Some(&daemon), &usage, 1_090);
+        assert!(text.contains("# TYPE snipt_expansions_total counter\nsnipt_expansions_total 42\n"));
+        assert!(text.contains("snipt_daemon_up 1\n"));
+        assert!(text.contains("snipt_daemon_uptime_seconds 90\n"));
+        assert!(text.contains("snipt_reloads_total 3\n"));
+        assert!(text.contains("snipt_snippet_expansions{shortcut=\"sig\"} 12\n"));
+        assert!(text.contains("snipt_snippet_expansions{shortcut=\"a\\\"b\"} 1\n"));
+
+        // Without the daemon only the usage counts are reported
+        let text = render_metrics(None, &usage, 1_090);
+        assert!(text.contains("snipt_daemon_up 0\n"));
+        assert!(!text.contains("snipt_expansions_total"));
+        assert!(text.contains("snipt_snippet_expansions{shortcut=\"sig\"} 12\n"));
+    }
+}
@@ -1,19 +1,221 @@
-use chrono::{DateTime, Local};
+use chrono::{DateTime, FixedOffset, Local};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::config::trigger_char;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SnippetEntry {
     pub shortcut: String,
     pub snippet: String,
+    /// When the snippet was last changed
     pub timestamp: String,
+    /// When the snippet was first added. Databases written before this field
+    /// existed get the update time instead (see `fill_created_at`).
+    #[serde(default)]
+    pub created_at: String,
+    /// Optional language used to syntax highlight the snippet in the UI
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Optional HTML version of the snippet, pasted as rich text so apps that
+    /// understand formatting keep bold text, links and lists
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub html: Option<String>,
+    /// Disabled snippets are kept but never expanded
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Optional `/`-separated folder such as `email` or `code/rust`, used to
+    /// organize the snippet list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Pinned snippets are listed first
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pinned: bool,
+    /// Character that starts this snippet's text expansion instead of the
+    /// global trigger character
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<char>,
+    /// What the snippet holds. A `link` snippet stores its URL as the snippet
+    /// text.
+    #[serde(default, skip_serializing_if = "SnippetKind::is_text")]
+    pub kind: SnippetKind,
+    /// Link text of a `link` snippet; the shortcut is used when it's not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Free-form labels; unlike groups, a snippet can have any number of them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// What the snippet is for, shown in the snippet manager and searched
+    /// along with the shortcut
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+}
+
+/// What a snippet holds. Text, URL and command snippets expand as they always
+/// have, with URLs formatted for the frontmost app; a link always expands to
+/// a markdown link, whatever the app.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SnippetKind {
+    #[default]
+    Text,
+    Url,
+    Command,
+    Link,
+}
+
+impl SnippetKind {
+    pub const ALL: [SnippetKind; 4] = [
+        SnippetKind::Text,
+        SnippetKind::Url,
+        SnippetKind::Command,
+        SnippetKind::Link,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SnippetKind::Text => "text",
+            SnippetKind::Url => "url",
+            SnippetKind::Command => "command",
+            SnippetKind::Link => "link",
+        }
+    }
+
+    fn is_text(&self) -> bool {
+        *self == SnippetKind::Text
+    }
+}
+
+impl fmt::Display for SnippetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SnippetKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SnippetKind::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                let names: Vec<&str> = SnippetKind::ALL.iter().map(|kind| kind.name()).collect();
+                format!("Unknown snippet kind '{}'. Use {}", s, names.join(", "))
+            })
+    }
+}
+
+/// A deleted snippet, kept in the trash until it is restored or the trash is
+/// emptied
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrashEntry {
+    /// When the snippet was deleted
+    pub deleted_at: String,
+    #[serde(flatten)]
+    pub entry: SnippetEntry,
+}
+
+/// What an import changed, kept in the import journal so it can be undone
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ImportRecord {
+    pub id: String,
+    /// When the import ran
+    pub imported_at: String,
+    /// Shortcuts the import added
+    pub added: Vec<String>,
+    /// The snippets the import overwrote, as they were before
+    pub replaced: Vec<SnippetEntry>,
+}
+
+/// Who made a snippet pack and what it is for
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PackInfo {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub author: String,
+}
+
+/// A curated, shareable collection of snippets, stored as a `.sniptpack` file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SnippetPack {
+    /// Pack format version, see `storage::PACK_VERSION`
+    pub version: u32,
+    #[serde(flatten)]
+    pub info: PackInfo,
+    pub snippets: Vec<SnippetEntry>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
 }
 
 impl SnippetEntry {
     pub fn new(shortcut: String, snippet: String) -> Self {
+        let now = Local::now().to_rfc3339();
         Self {
             shortcut,
             snippet,
-            timestamp: Local::now().to_rfc3339(),
+            timestamp: now.clone(),
+            created_at: now,
+            language: None,
+            html: None,
+            enabled: true,
+            group: None,
+            pinned: false,
+            trigger: None,
+            kind: SnippetKind::Text,
+            label: None,
+            tags: Vec::new(),
+            description: String::new(),
+        }
+    }
+
+    /// Folders of the snippet's group from the outermost in (`code/rust`
+    /// gives `["code", "rust"]`). Empty for ungrouped snippets.
+    pub fn group_segments(&self) -> Vec<&str> {
+        self.group
+            .as_deref()
+            .unwrap_or("")
+            .split('/')
+            .map(str::trim)
+            .filter(|segment| !segment.is_empty())
+            .collect()
+    }
+
+    /// Character that starts this snippet's text expansion
+    pub fn text_trigger(&self) -> char {
+        self.trigger.unwrap_or_else(trigger_char)
+    }
+
+    /// The markdown link a `link` snippet expands to, `[label](url)`
+    pub fn markdown_link(&self) -> String {
+        let label = self.label.as_deref().unwrap_or(&self.shortcut);
+        let url = self.snippet.trim();
+        // Angle brackets keep spaces and parentheses from ending the URL early
+        let url = if url.contains(|c: char| c.is_whitespace() || c == '(' || c == ')') {
+            format!("<{}>", url)
+        } else {
+            url.to_string()
+        };
+        format!(
+            "[{}]({})",
+            label.replace('[', "\\[").replace(']', "\\]"),
+            url
+        )
+    }
+
+    /// Use the update time as the creation time for entries saved without one
+    pub fn fill_created_at(&mut self) {
+        if self.created_at.is_empty() {
+            self.created_at = self.timestamp.clone();
         }
     }
 
@@ -22,22 +224,94 @@ impl SnippetEntry {
         self.timestamp = Local::now().to_rfc3339();
     }
 
+    /// When the snippet was last updated, or None if the timestamp can't be
+    /// parsed. Compare these rather than the strings, which differ in offset
+    /// and precision between machines and snipt versions.
+    pub fn updated_at(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.timestamp).ok()
+    }
+
+    /// How long ago the snippet was last updated
     pub fn formatted_time(&self) -> String {
-        let entry_time = DateTime::parse_from_rfc3339(&self.timestamp)
-            .map(|dt| dt.with_timezone(&Local))
-            .unwrap_or_else(|_| Local::now());
-
-        let now = Local::now();
-        let duration = now.signed_duration_since(entry_time);
-
-        if duration.num_seconds() < 60 {
-            format!("{}s ago", duration.num_seconds())
-        } else if duration.num_minutes() < 60 {
-            format!("{}m ago", duration.num_minutes())
-        } else if duration.num_hours() < 24 {
-            format!("{}h ago", duration.num_hours())
+        relative_time(&self.timestamp)
+    }
+
+    /// How long ago the snippet was created
+    pub fn formatted_created_time(&self) -> String {
+        relative_time(if self.created_at.is_empty() {
+            &self.timestamp
         } else {
-            format!("{}d ago", duration.num_days())
-        }
+            &self.created_at
+        })
+    }
+}
+
+impl TrashEntry {
+    /// How long ago the snippet was deleted
+    pub fn formatted_deleted_time(&self) -> String {
+        relative_time(&self.deleted_at)
+    }
+}
+
+impl ImportRecord {
+    /// How long ago the import ran
+    pub fn formatted_imported_time(&self) -> String {
+        relative_time(&self.imported_at)
+    }
+}
+
+fn relative_time(timestamp: &str) -> String {
+    let entry_time = DateTime::parse_from_rfc3339(timestamp)
+        .map(|dt| dt.with_timezone(&Local))
+        .unwrap_or_else(|_| Local::now());
+
+    let now = Local::now();
+    let duration = now.signed_duration_since(entry_time);
+
+    if duration.num_seconds() < 60 {
+        format!("{}s ago", duration.num_seconds())
+    } else if duration.num_minutes() < 60 {
+        format!("{}m ago", duration.num_minutes())
+    } else if duration.num_hours() < 24 {
+        format!("{}h ago", duration.num_hours())
+    } else {
+        format!("{}d ago", duration.num_days())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_segments() {
+        let mut entry = SnippetEntry::new("sig".to_string(), "Jane".to_string());
+        assert!(entry.group_segments().is_empty());
+
+        entry.group = Some("a/b/c".to_string());
+        assert_eq!(entry.group_segments(), ["a", "b", "c"]);
+
+        // Empty segments and surrounding spaces are dropped
+        entry.group = Some(" code // rust/ ".to_string());
+        assert_eq!(entry.group_segments(), ["code", "rust"]);
+
+        entry.group = Some("/".to_string());
+        assert!(entry.group_segments().is_empty());
+    }
+
+    #[test]
+    fn test_updated_at() {
+        let mut earlier = SnippetEntry::new("a".to_string(), String::new());
+        let mut later = earlier.clone();
+        // 11:00 in UTC is later than 12:30 at +02:00, though it sorts first as text
+        earlier.timestamp = "2026-03-01T12:30:00+02:00".to_string();
+        later.timestamp = "2026-03-01T11:00:00Z".to_string();
+        assert!(later.timestamp < earlier.timestamp);
+        assert!(later.updated_at() > earlier.updated_at());
+
+        // Unreadable timestamps sort before every real one
+        later.timestamp = "yesterday".to_string();
+        assert_eq!(later.updated_at(), None);
+        assert!(later.updated_at() < earlier.updated_at());
     }
 }
@@ -0,0 +1,140 @@
+//! Times of day during which nothing is expanded, set with `quiet_hours`.
+//!
+//! Each window has a `start` and `end` in 24-hour `HH:MM` and, optionally,
+//! the `days` it starts on (`"mon"` or `"monday"`, any case; every day when
+//! empty). A window whose end is before its start runs past midnight into
+//! the next day, and one whose start and end are equal lasts the whole day.
+//! Times are in the configured `timezone`, or the local one.
+
+use chrono::{Datelike, Local, NaiveDateTime, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::dates::parse_timezone;
+use crate::error::{Result, SniptError};
+use crate::settings::current_config;
+
+/// A recurring time window without expansions
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+    #[serde(default)]
+    pub days: Vec<String>,
+}
+
+impl QuietHours {
+    /// Parse the start and end times and the days
+    fn parse(&self) -> Result<(NaiveTime, NaiveTime, Vec<Weekday>)> {
+        let time = |value: &str| {
+            NaiveTime::parse_from_str(value.trim(), "%H:%M").map_err(|_| {
+                SniptError::InvalidConfig(format!(
+                    "Invalid quiet hours time '{}'. Use 24-hour HH:MM such as \"09:30\"",
+                    value
+                ))
+            })
+        };
+        let days = self
+            .days
+            .iter()
+            .map(|day| {
+                day.trim().parse::<Weekday>().map_err(|_| {
+                    SniptError::InvalidConfig(format!(
+                        "Invalid quiet hours day '{}'. Use a name such as \"mon\" or \"friday\"",
+                        day
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok((time(&self.start)?, time(&self.end)?, days))
+    }
+
+    /// Whether `now` falls inside this window
+    fn contains(&self, now: NaiveDateTime) -> bool {
+        let Ok((start, end, days)) = self.parse() else {
+            return false;
+        };
+        let starts_on = |day: Weekday| days.is_empty() || days.contains(&day);
+        let today = now.weekday();
+        let time = now.time();
+
+        if start < end {
+            starts_on(today) && start <= time && time < end
+        } else if start > end {
+            // Either still in yesterday's window or in today's
+            (starts_on(today.pred()) && time < end) || (starts_on(today) && start <= time)
+        } else {
+            starts_on(today)
+        }
+    }
+}
+
+/// Check that every window can be parsed
+pub fn validate_quiet_hours(windows: &[QuietHours]) -> Result<()> {
+    windows
+        .iter()
+        .try_for_each(|window| window.parse().map(|_| ()))
+}
+
+/// The window `now` falls inside, if any
+pub fn quiet_window_at(windows: &[QuietHours], now: NaiveDateTime) -> Option<&QuietHours> {
+    windows.iter().find(|window| window.contains(now))
+}
+
+/// The configured window the current time falls inside, if any
+pub fn current_quiet_window() -> Option<QuietHours> {
+    let config = current_config();
+    if config.quiet_hours.is_empty() {
+        return None;
+    }
+    let now = match config.timezone.as_deref().map(parse_timezone) {
+        Some(Ok(tz)) => Utc::now().with_timezone(&tz).naive_local(),
+        _ => Local::now().naive_local(),
+    };
+    quiet_window_at(&config.quiet_hours, now).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn window(start: &str, end: &str, days: &[&str]) -> QuietHours {
+        QuietHours {
+            start: start.to_string(),
+            end: end.to_string(),
+            days: days.iter().map(|day| day.to_string()).collect(),
+        }
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // June 2024: the 3rd is a Monday
+        NaiveDate::from_ymd_opt(2024, 6, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let meetings = [window("09:30", "10:00", &["mon", "Wednesday"])];
+        assert!(quiet_window_at(&meetings, at(3, 9, 30)).is_some());
+        assert!(quiet_window_at(&meetings, at(3, 10, 0)).is_none());
+        assert!(quiet_window_at(&meetings, at(4, 9, 45)).is_none());
+        assert!(quiet_window_at(&meetings, at(5, 9, 45)).is_some());
+
+        // Crossing midnight: Friday night into Saturday morning
+        let nights = [window("22:00", "06:00", &["fri"])];
+        assert!(quiet_window_at(&nights, at(7, 23, 0)).is_some());
+        assert!(quiet_window_at(&nights, at(8, 5, 59)).is_some());
+        assert!(quiet_window_at(&nights, at(8, 6, 0)).is_none());
+        assert!(quiet_window_at(&nights, at(7, 5, 0)).is_none());
+
+        // Equal start and end is the whole day; no days is every day
+        assert!(quiet_window_at(&[window("00:00", "00:00", &["sun"])], at(9, 15, 0)).is_some());
+        assert!(quiet_window_at(&[window("12:00", "13:00", &[])], at(4, 12, 30)).is_some());
+
+        assert!(validate_quiet_hours(&meetings).is_ok());
+        assert!(validate_quiet_hours(&[window("9am", "10:00", &[])]).is_err());
+        assert!(validate_quiet_hours(&[window("09:00", "10:00", &["someday"])]).is_err());
+    }
+}
@@ -0,0 +1,63 @@
+//! Best-effort detection of password entry, so snippets aren't expanded into
+//! a password field. Used when `block_secure_input` is on.
+//!
+//! - macOS: asks the system whether secure event input is on, which password
+//!   fields and "Secure Keyboard Entry" in terminals turn on.
+//! - Linux and Windows: there is no such API, so only a frontmost window of a
+//!   well-known password manager is detected. Password fields in browsers and
+//!   other apps are not; add those apps to `app_blocklist` instead.
+
+/// Password managers, matched as case-insensitive substrings of the app name
+const PASSWORD_MANAGERS: &[&str] = &[
+    "1password",
+    "bitwarden",
+    "dashlane",
+    "enpass",
+    "keepass",
+    "keeper",
+    "lastpass",
+    "nordpass",
+    "proton pass",
+    "roboform",
+];
+
+/// Whether `app_name` looks like a password manager
+pub fn is_password_manager(app_name: &str) -> bool {
+    let app_name = app_name.to_lowercase();
+    PASSWORD_MANAGERS
+        .iter()
+        .any(|manager| app_name.contains(manager))
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+    fn IsSecureEventInputEnabled() -> u8;
+}
+
+/// Whether a password is probably being typed right now
+#[cfg(target_os = "macos")]
+pub fn secure_input_active() -> bool {
+    // SAFETY: takes no arguments and only reads system state
+    unsafe { IsSecureEventInputEnabled() != 0 }
+}
+
+/// Whether a password is probably being typed right now
+#[cfg(not(target_os = "macos"))]
+pub fn secure_input_active() -> bool {
+    is_password_manager(&crate::expansion::get_frontmost_app())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_password_manager() {
+        assert!(is_password_manager("KeePassXC"));
+        assert!(is_password_manager("Bitwarden - Vault"));
+        assert!(is_password_manager("1Password 8"));
+        assert!(!is_password_manager("Firefox"));
+        assert!(!is_password_manager(""));
+    }
+}
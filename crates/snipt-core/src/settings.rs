@@ -0,0 +1,477 @@
+//! User configuration stored in `config.json` (or `config.toml`) inside the
+//! snipt config directory.
+
+use crate::config::{
+    get_config_dir, set_trigger_chars, storage_error, CONFIG_FILENAME, CONFIG_TOML_FILENAME,
+    EXECUTE_CHAR, SPECIAL_CHAR,
+};
+use crate::dates::parse_timezone;
+use crate::error::{Result, SniptError};
+use crate::expansion::set_app_rules;
+use crate::keyboard::{terminator_key, Hotkey};
+use crate::layout::{KeyboardLayout, LayoutKind};
+use crate::quiet_hours::{validate_quiet_hours, QuietHours};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// How expanded text is inserted into the focused application
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExpansionMethod {
+    /// Simulate typing each character
+    #[default]
+    Typing,
+    /// Put the text on the clipboard and send the paste shortcut
+    Clipboard,
+}
+
+/// How the typed shortcut is removed before an expansion is inserted
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeletionMethod {
+    /// Press Backspace once per character
+    #[default]
+    Backspace,
+    /// Select the shortcut with Shift+Left, then press Backspace once
+    Select,
+}
+
+/// User-tunable settings. Missing fields fall back to their defaults so older
+/// config files keep working as new options are added.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SniptConfig {
+    /// Character that starts a text expansion
+    pub trigger_char: char,
+    /// Character that starts an execution
+    pub execute_char: char,
+    /// Keyboard layout used to turn physical keys into characters
+    pub keyboard_layout: LayoutKind,
+    /// Custom key mappings such as `"KeyY": "z"` or `"Shift+Num7": "/"`
+    pub layout_overrides: HashMap<String, String>,
+    /// Record performed expansions (shortcut names only) in the history file
+    pub history_enabled: bool,
+    /// Maximum number of history entries to keep
+    pub history_limit: usize,
+    /// Apps where expansion is never performed (case-insensitive substring match)
+    pub app_blocklist: Vec<String>,
+    /// If non-empty, expansion only happens in these apps (the blocklist still wins)
+    pub app_allowlist: Vec<String>,
+    /// Don't expand while a password seems to be being entered (see
+    /// `secure_input` for what can be detected on each platform)
+    pub block_secure_input: bool,
+    /// Let `:Brb` and `:BRB` expand the `brb` snippet, capitalizing the first
+    /// letter or uppercasing the whole expansion to match
+    pub case_preserving: bool,
+    /// Treat `$1`, `${2:text}` and `$0` in text snippets as tab stops: the
+    /// markers are removed and, with the clipboard method, the cursor is left
+    /// at the first stop
+    pub tab_stops: bool,
+    /// Only expand shortcuts typed at the start of a word, i.e. after
+    /// whitespace or at the start of a line
+    pub whole_word: bool,
+    /// Treat a doubled trigger such as `::` as an escape: it types a single
+    /// literal trigger character and the word after it never expands
+    pub escaped_triggers: bool,
+    /// Let key combinations such as Ctrl+C or Cmd+V clear the shortcut being
+    /// typed instead of adding their key to it
+    pub ignore_chords: bool,
+    /// Keys that complete a typed shortcut: any of `space`, `enter` and `tab`
+    pub terminator_keys: Vec<String>,
+    /// Also expand when a shortcut is followed by any other symbol such as
+    /// `.` or `,`, which is typed after the expansion
+    pub expand_on_non_alphanumeric: bool,
+    /// Ignore a repeat of the same shortcut within this many milliseconds of
+    /// its last expansion (0 disables debouncing)
+    pub debounce_ms: u64,
+    /// How expanded text is inserted
+    pub expansion_method: ExpansionMethod,
+    /// Expansion method for apps whose name contains the key
+    /// (case-insensitive), overriding `expansion_method`
+    pub app_expansion_methods: HashMap<String, ExpansionMethod>,
+    /// Pause in milliseconds between typed characters (0 types whole lines at once)
+    pub typing_delay_ms: u64,
+    /// How the typed shortcut is removed
+    pub deletion_method: DeletionMethod,
+    /// Deletion method for apps whose name contains the key (case-insensitive),
+    /// overriding `deletion_method`
+    pub app_deletion_methods: HashMap<String, DeletionMethod>,
+    /// Pause in milliseconds before each key press that removes the shortcut
+    pub deletion_delay_ms: u64,
+    /// Put back the previous clipboard contents after a clipboard expansion
+    pub restore_clipboard: bool,
+    /// Milliseconds to wait after pasting before restoring the clipboard, so
+    /// the target app has read the expansion
+    pub restore_clipboard_delay_ms: u64,
+    /// Show a desktop notification when the daemon stops working
+    pub notifications_enabled: bool,
+    /// Convert CRLF to LF and strip trailing whitespace when adding or
+    /// updating a snippet
+    pub normalize_whitespace: bool,
+    /// Most lines a snippet written in the editor can have, up to
+    /// `EDITOR_MAX_LINES_CEILING`
+    pub editor_max_lines: usize,
+    /// Most characters a line written in the editor can have, up to
+    /// `EDITOR_MAX_LINE_LENGTH_CEILING`
+    pub editor_max_line_length: usize,
+    /// Key combination that stops an expansion while it is being typed, in
+    /// addition to Escape
+    pub abort_hotkey: Option<String>,
+    /// When set, shortcuts expand only when this key combination is pressed
+    /// after them, never on Space, Tab, Enter or symbols
+    pub expand_hotkey: Option<String>,
+    /// Hotkey that expands the last expanded snippet again at the cursor
+    pub repeat_hotkey: Option<String>,
+    /// Global hotkey that opens the snippet picker, e.g. `"Ctrl+Alt+Space"`
+    pub picker_hotkey: Option<String>,
+    /// Command that opens a terminal and runs the arguments after it. Empty
+    /// uses the platform default.
+    pub picker_terminal: Vec<String>,
+    /// Number of database backups to keep in the `backups` directory
+    pub backup_limit: usize,
+    /// Hours between automatic backups while the daemon runs (0 disables them)
+    pub backup_interval_hours: u64,
+    /// Seconds an execute snippet's command or script may run before it is
+    /// stopped (0 disables the limit)
+    pub execute_timeout_secs: u64,
+    /// Output of an execute snippet beyond this many bytes is dropped (0
+    /// disables the limit)
+    pub execute_max_output_bytes: usize,
+    /// IANA timezone such as `"Europe/Berlin"` for `{{date}}` and `{{time}}`
+    /// placeholders and quiet hours. Unset uses the local timezone.
+    pub timezone: Option<String>,
+    /// Time windows during which nothing is expanded
+    pub quiet_hours: Vec<QuietHours>,
+    /// Encrypt the snippet database with a passphrase. Change it with
+    /// `snipt encrypt` / `snipt decrypt` so the file is rewritten.
+    pub encrypt_database: bool,
+}
+
+impl Default for SniptConfig {
+    fn default() -> Self {
+        Self {
+            trigger_char: SPECIAL_CHAR,
+            execute_char: EXECUTE_CHAR,
+            keyboard_layout: LayoutKind::default(),
+            layout_overrides: HashMap::new(),
+            history_enabled: true,
+            history_limit: 1000,
+            app_blocklist: Vec::new(),
+            app_allowlist: Vec::new(),
+            block_secure_input: true,
+            case_preserving: false,
+            tab_stops: false,
+            whole_word: false,
+            escaped_triggers: false,
+            ignore_chords: true,
+            terminator_keys: vec!["space".to_string(), "enter".to_string(), "tab".to_string()],
+            expand_on_non_alphanumeric: false,
+            debounce_ms: 0,
+            expansion_method: ExpansionMethod::default(),
+            app_expansion_methods: HashMap::new(),
+            typing_delay_ms: 0,
+            deletion_method: DeletionMethod::default(),
+            app_deletion_methods: HashMap::new(),
+            deletion_delay_ms: 2,
+            restore_clipboard: true,
+            restore_clipboard_delay_ms: 150,
+            notifications_enabled: true,
+            normalize_whitespace: false,
+            editor_max_lines: 10_000,
+            editor_max_line_length: 5_000,
+            abort_hotkey: None,
+            expand_hotkey: None,
+            repeat_hotkey: None,
+            picker_hotkey: None,
+            picker_terminal: Vec::new(),
+            backup_limit: 10,
+            backup_interval_hours: 0,
+            execute_timeout_secs: 10,
+            execute_max_output_bytes: 64 * 1024,
+            timezone: None,
+            quiet_hours: Vec::new(),
+            encrypt_database: false,
+        }
+    }
+}
+
+/// Check that a pair of trigger characters can be typed and told apart
+pub fn validate_trigger_chars(trigger: char, execute: char) -> Result<()> {
+    for c in [trigger, execute] {
+        if !c.is_ascii_punctuation() || matches!(c, '(' | ')' | '$') {
+            return Err(SniptError::InvalidConfig(format!(
+                "'{}' can't be used as a trigger character. Use an ASCII symbol such as ':', ';' or '!'",
+                c
+            )));
+        }
+    }
+
+    if trigger == execute {
+        return Err(SniptError::InvalidConfig(
+            "Trigger and execute characters must be different".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Highest `editor_max_lines` that can be configured
+pub const EDITOR_MAX_LINES_CEILING: usize = 100_000;
+
+/// Highest `editor_max_line_length` that can be configured
+pub const EDITOR_MAX_LINE_LENGTH_CEILING: usize = 100_000;
+
+/// Configuration most recently applied in this process
+static CURRENT_CONFIG: RwLock<Option<SniptConfig>> = RwLock::new(None);
+
+/// Check settings that parse but can't be used, such as a hotkey or
+/// timezone snipt doesn't understand
+pub fn validate_config(config: &SniptConfig) -> Result<()> {
+    validate_trigger_chars(config.trigger_char, config.execute_char)?;
+    for hotkey in [
+        &config.picker_hotkey,
+        &config.abort_hotkey,
+        &config.expand_hotkey,
+        &config.repeat_hotkey,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        Hotkey::parse(hotkey)?;
+    }
+    if let Some(timezone) = &config.timezone {
+        parse_timezone(timezone)?;
+    }
+    validate_quiet_hours(&config.quiet_hours)?;
+    for name in &config.terminator_keys {
+        if terminator_key(name).is_none() {
+            return Err(SniptError::InvalidConfig(format!(
+                "Unknown terminator key '{}'. Use \"space\", \"enter\" or \"tab\"",
+                name
+            )));
+        }
+    }
+    for (name, value, ceiling) in [
+        (
+            "editor_max_lines",
+            config.editor_max_lines,
+            EDITOR_MAX_LINES_CEILING,
+        ),
+        (
+            "editor_max_line_length",
+            config.editor_max_line_length,
+            EDITOR_MAX_LINE_LENGTH_CEILING,
+        ),
+    ] {
+        if !(1..=ceiling).contains(&value) {
+            return Err(SniptError::InvalidConfig(format!(
+                "{} must be between 1 and {}",
+                name, ceiling
+            )));
+        }
+    }
+    KeyboardLayout::from_config(config)?;
+    Ok(())
+}
+
+/// Apply settings that are read through process-wide state
+pub fn apply_config(config: &SniptConfig) -> Result<()> {
+    validate_config(config)?;
+    set_trigger_chars(config.trigger_char, config.execute_char);
+    set_app_rules(&config.app_blocklist, &config.app_allowlist);
+    if let Ok(mut current) = CURRENT_CONFIG.write() {
+        *current = Some(config.clone());
+    }
+    Ok(())
+}
+
+/// The configuration last passed to `apply_config`, or the defaults
+pub fn current_config() -> SniptConfig {
+    CURRENT_CONFIG
+        .read()
+        .ok()
+        .and_then(|current| current.clone())
+        .unwrap_or_default()
+}
+
+/// Format of a config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// Format of the config file at `path`, by its extension
+    pub fn of(path: &Path) -> Self {
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            ConfigFormat::Toml
+        } else {
+            ConfigFormat::Json
+        }
+    }
+}
+
+/// Get the path to the user configuration file: `config.json`, or
+/// `config.toml` when only that one exists
+pub fn get_config_file_path() -> PathBuf {
+    let config_dir = get_config_dir();
+    let json = config_dir.join(CONFIG_FILENAME);
+    let toml = config_dir.join(CONFIG_TOML_FILENAME);
+    if !json.exists() && toml.exists() {
+        toml
+    } else {
+        json
+    }
+}
+
+/// Parse a config file. Errors name the setting that is wrong and the line
+/// it is on.
+pub fn parse_config(
+    content: &str,
+    format: ConfigFormat,
+) -> std::result::Result<SniptConfig, String> {
+    fn describe<E: std::fmt::Display>(e: serde_path_to_error::Error<E>) -> String {
+        match e.path().to_string().as_str() {
+            "." => e.inner().to_string(),
+            setting => format!("'{}': {}", setting, e.inner()),
+        }
+    }
+
+    match format {
+        ConfigFormat::Json => {
+            let mut deserializer = serde_json::Deserializer::from_str(content);
+            let config = serde_path_to_error::deserialize(&mut deserializer).map_err(describe)?;
+            deserializer.end().map_err(|e| e.to_string())?;
+            Ok(config)
+        }
+        ConfigFormat::Toml => {
+            serde_path_to_error::deserialize(toml::Deserializer::new(content)).map_err(describe)
+        }
+    }
+}
+
+/// Top-level settings in a config file that snipt doesn't know, and ignores
+pub fn unknown_config_keys(content: &str, format: ConfigFormat) -> Vec<String> {
+    let Ok(serde_json::Value::Object(known)) = serde_json::to_value(SniptConfig::default()) else {
+        return Vec::new();
+    };
+    let keys: Vec<String> = match format {
+        ConfigFormat::Json => {
+            serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(content)
+                .map(|settings| settings.keys().cloned().collect())
+                .unwrap_or_default()
+        }
+        ConfigFormat::Toml => toml::from_str::<toml::Table>(content)
+            .map(|settings| settings.keys().cloned().collect())
+            .unwrap_or_default(),
+    };
+    keys.into_iter()
+        .filter(|key| !known.contains_key(key))
+        .collect()
+}
+
+/// Load the user configuration, using defaults when no config file exists
+pub fn load_config() -> Result<SniptConfig> {
+    let path = get_config_file_path();
+    if !path.exists() {
+        return Ok(SniptConfig::default());
+    }
+
+    let content = fs::read_to_string(&path)?;
+    if content.trim().is_empty() {
+        return Ok(SniptConfig::default());
+    }
+
+    parse_config(&content, ConfigFormat::of(&path))
+        .map_err(|e| SniptError::InvalidConfig(format!("{}: {}", path.display(), e)))
+}
+
+/// Check the config file without applying it, returning the settings in it
+/// that snipt ignores
+pub fn check_config_file() -> Result<Vec<String>> {
+    validate_config(&load_config()?)?;
+    let path = get_config_file_path();
+    match fs::read_to_string(&path) {
+        Ok(content) => Ok(unknown_config_keys(&content, ConfigFormat::of(&path))),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+/// Write the user configuration file
+pub fn save_config(config: &SniptConfig) -> Result<()> {
+    let config_dir = get_config_dir();
+    if !config_dir.exists() {
+        fs::create_dir_all(&config_dir)
+            .map_err(|e| storage_error("create the config directory", &config_dir, e))?;
+    }
+
+    let path = get_config_file_path();
+    let serialized = match ConfigFormat::of(&path) {
+        ConfigFormat::Json => serde_json::to_string_pretty(config)?,
+        ConfigFormat::Toml => toml::to_string_pretty(config)
+            .map_err(|e| SniptError::Other(format!("Failed to write the config: {}", e)))?,
+    };
+    fs::write(&path, serialized).map_err(|e| storage_error("write the config file", &path, e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = parse_config(
+            r#"{"trigger_char": ";", "whole_word": true}"#,
+            ConfigFormat::Json,
+        )
+        .unwrap();
+        assert_eq!(config.trigger_char, ';');
+        assert!(config.whole_word);
+
+        let config = parse_config(
+            "trigger_char = \";\"\ndebounce_ms = 150\n",
+            ConfigFormat::Toml,
+        )
+        .unwrap();
+        assert_eq!(config.trigger_char, ';');
+        assert_eq!(config.debounce_ms, 150);
+
+        // Errors name the setting that is wrong
+        let error = parse_config(r#"{"whole_word": "yes"}"#, ConfigFormat::Json).unwrap_err();
+        assert!(error.starts_with("'whole_word': "), "{}", error);
+        let error = parse_config("debounce_ms = \"fast\"", ConfigFormat::Toml).unwrap_err();
+        assert!(error.starts_with("'debounce_ms': "), "{}", error);
+        assert!(parse_config("{", ConfigFormat::Json).is_err());
+
+        assert_eq!(
+            ConfigFormat::of(Path::new("config.toml")),
+            ConfigFormat::Toml
+        );
+        assert_eq!(
+            ConfigFormat::of(Path::new("config.json")),
+            ConfigFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_unknown_config_keys() {
+        assert_eq!(
+            unknown_config_keys(
+                r#"{"trigger_char": ";", "trigger_chr": ";"}"#,
+                ConfigFormat::Json
+            ),
+            vec!["trigger_chr".to_string()]
+        );
+        assert_eq!(
+            unknown_config_keys("whole_word = true\nwholeword = true\n", ConfigFormat::Toml),
+            vec!["wholeword".to_string()]
+        );
+        assert!(unknown_config_keys("not json", ConfigFormat::Json).is_empty());
+    }
+}
@@ -1,25 +1,160 @@
-use crate::config::{ensure_config_dir, get_db_file_path};
+use crate::config::{
+    ensure_config_dir, execute_char, get_backups_dir, get_db_file_path, get_import_journal_path,
+    get_seed_file_path, get_seeded_marker_path, get_trash_file_path, storage_error, trigger_char,
+};
+use crate::crypto::{decrypt, encrypt, is_encrypted, passphrase};
 use crate::error::{Result, SniptError};
-use crate::models::SnippetEntry;
+use crate::execution::is_url;
+use crate::models::{ImportRecord, PackInfo, SnippetEntry, SnippetKind, SnippetPack, TrashEntry};
+use crate::settings::{current_config, validate_trigger_chars};
+use chrono::Local;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Schema version written to the database file. Version 1 was a bare JSON
+/// array of snippets; version 2 wraps it in `{ "version": 2, "snippets": [...] }`.
+pub const DB_VERSION: u32 = 2;
+
+#[derive(Serialize)]
+struct DatabaseOut<'a> {
+    version: u32,
+    snippets: &'a [SnippetEntry],
+}
+
+#[derive(Deserialize)]
+struct DatabaseIn {
+    version: u32,
+    snippets: Vec<SnippetEntry>,
+}
+
+/// Parse the contents of a database file in any known format, upgrading
+/// older entries in memory
+pub fn parse_database(content: &str) -> Result<Vec<SnippetEntry>> {
+    let content = content.trim();
+    if content.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut snippets: Vec<SnippetEntry> = if content.starts_with('[') {
+        serde_json::from_str(content)?
+    } else {
+        let database: DatabaseIn = serde_json::from_str(content)?;
+        if database.version > DB_VERSION {
+            return Err(SniptError::Other(format!(
+                "The snippet database uses format version {}, but this snipt only understands up to version {}. Please upgrade snipt.",
+                database.version, DB_VERSION
+            )));
+        }
+        database.snippets
+    };
+
+    for entry in &mut snippets {
+        entry.fill_created_at();
+    }
+    Ok(snippets)
+}
+
+/// Serialize snippets in the current database format
+pub fn serialize_database(snippets: &[SnippetEntry]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&DatabaseOut {
+        version: DB_VERSION,
+        snippets,
+    })?)
+}
+
+/// Read a snipt data file, decrypting it if it was written encrypted
+fn read_data_file(path: &Path) -> Result<String> {
+    let content = fs::read_to_string(path)?;
+    if is_encrypted(&content) {
+        return decrypt(&content, &passphrase()?);
+    }
+    Ok(content)
+}
+
+/// Write a snipt data file, encrypting it when `encrypt_database` is on
+fn write_data_file(path: &Path, content: String, action: &str) -> Result<()> {
+    let content = if current_config().encrypt_database {
+        encrypt(&content, &passphrase()?)?
+    } else {
+        content
+    };
+    fs::write(path, content).map_err(|e| storage_error(action, path, e))
+}
+
+/// Replace a file in one step by writing a temporary file next to it and
+/// renaming it into place, so readers never see it half written
+pub fn write_atomically(path: &Path, content: &[u8]) -> io::Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    fs::write(&temp_path, content)?;
+    fs::rename(&temp_path, path)
+}
 
 /// Load all snippets from the database
 pub fn load_snippets() -> Result<Vec<SnippetEntry>> {
     let path = get_db_file_path();
+    // `ensure_config_dir` leaves an empty database behind, which the seed
+    // file may still fill
+    if !fs::metadata(&path).is_ok_and(|metadata| metadata.len() > 0) {
+        seed_database(&path)?;
+    }
     if !path.exists() {
         return Err(SniptError::DatabaseNotFound(
             path.to_string_lossy().to_string(),
         ));
     }
 
-    let content = fs::read_to_string(&path)?;
+    parse_database(&read_data_file(&path)?)
+}
 
-    // Handle empty database file
-    if content.trim().is_empty() {
-        return Ok(vec![]);
+/// On first run, write the snippets of the seed file (`seed.json` in the
+/// config directory, or `$SNIPT_SEED`) as the database. A marker file keeps
+/// the seed from being imported again, even after the database is deleted.
+fn seed_database(db_path: &Path) -> Result<()> {
+    let seeded = seed_database_from(&get_seed_file_path(), db_path, &get_seeded_marker_path())?;
+    if let Some(count) = seeded {
+        println!("Added {} starter snippets from the seed file", count);
+    }
+    Ok(())
+}
+
+/// Write the snippets in `seed` to `db_path` unless `marker` exists, and
+/// create the marker. Returns the number of snippets written.
+fn seed_database_from(seed: &Path, db_path: &Path, marker: &Path) -> Result<Option<usize>> {
+    if marker.exists() || !seed.exists() {
+        return Ok(None);
     }
 
-    serde_json::from_str(&content).map_err(|e| e.into())
+    let content =
+        fs::read_to_string(seed).map_err(|e| storage_error("read the seed file", seed, e))?;
+    let entries = parse_database(&content)
+        .map_err(|e| SniptError::Other(format!("Invalid seed file {}: {}", seed.display(), e)))?;
+    // Checked like any import, so a repeated shortcut is only added once
+    let mut snippets = Vec::new();
+    let summary = merge_import(&mut snippets, entries, false, &mut ImportRecord::default());
+    if !summary.skipped.is_empty() {
+        warn!(
+            "Skipped repeated shortcuts in the seed file: {}",
+            summary.skipped.join(", ")
+        );
+    }
+    if let Some(dir) = db_path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| storage_error("create the config directory", dir, e))?;
+    }
+    write_data_file(
+        db_path,
+        serialize_database(&snippets)?,
+        "write the snippet database",
+    )?;
+    fs::write(marker, "")
+        .map_err(|e| storage_error("record that the seed file was imported", marker, e))?;
+    Ok(Some(snippets.len()))
 }
 
 /// Save snippets to the database file
@@ -27,21 +162,145 @@ pub fn save_snippets(snippets: &[SnippetEntry]) -> Result<()> {
     let config_dir = ensure_config_dir()?;
     let db_path = config_dir.join("snipt.json");
 
-    let serialized = serde_json::to_string_pretty(&snippets)?;
-    fs::write(&db_path, serialized)?;
+    write_data_file(
+        &db_path,
+        serialize_database(snippets)?,
+        "write the snippet database",
+    )
+}
+
+/// Load deleted snippets, most recently deleted last
+pub fn load_trash() -> Result<Vec<TrashEntry>> {
+    let path = get_trash_file_path();
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let content = read_data_file(&path)?;
+    if content.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    let mut trash: Vec<TrashEntry> = serde_json::from_str(&content)?;
+    for trashed in &mut trash {
+        trashed.entry.fill_created_at();
+    }
+    Ok(trash)
+}
+
+/// Save deleted snippets to the trash file
+pub fn save_trash(trash: &[TrashEntry]) -> Result<()> {
+    ensure_config_dir()?;
+    write_data_file(
+        &get_trash_file_path(),
+        serde_json::to_string_pretty(trash)?,
+        "write the trash",
+    )
+}
+
+/// Copy the database to a timestamped file in the `backups` directory and
+/// prune the oldest backups beyond `backup_limit`. Returns the new backup's path.
+pub fn backup_database() -> Result<PathBuf> {
+    let db_path = get_db_file_path();
+    if !db_path.exists() {
+        return Err(SniptError::DatabaseNotFound(
+            db_path.to_string_lossy().to_string(),
+        ));
+    }
+
+    let backups_dir = get_backups_dir();
+    fs::create_dir_all(&backups_dir)
+        .map_err(|e| storage_error("create the backups directory", &backups_dir, e))?;
+
+    let backup_path = backups_dir.join(format!(
+        "snipt-{}.json",
+        Local::now().format("%Y%m%d-%H%M%S-%3f")
+    ));
+    fs::copy(&db_path, &backup_path)
+        .map_err(|e| storage_error("back up the snippet database", &backup_path, e))?;
 
+    prune_backups(&backups_dir, current_config().backup_limit)?;
+    Ok(backup_path)
+}
+
+/// Remove the oldest backups in `backups_dir` beyond `limit`. The newest
+/// backup is always kept.
+fn prune_backups(backups_dir: &Path, limit: usize) -> Result<()> {
+    let backups = list_backups_in(backups_dir)?;
+    let limit = limit.max(1);
+    if backups.len() > limit {
+        for old in &backups[..backups.len() - limit] {
+            fs::remove_file(old).map_err(|e| storage_error("remove an old backup", old, e))?;
+        }
+    }
     Ok(())
 }
 
-/// Add a new snippet
-pub fn add_snippet(shortcut: String, snippet: String) -> Result<()> {
-    // Check for excessively large snippets
+/// Database backups in the `backups` directory, oldest first
+fn list_backups() -> Result<Vec<PathBuf>> {
+    list_backups_in(&get_backups_dir())
+}
+
+fn list_backups_in(backups_dir: &Path) -> Result<Vec<PathBuf>> {
+    if !backups_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    // Timestamped names sort oldest first
+    let mut backups: Vec<PathBuf> = fs::read_dir(backups_dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("snipt-") && name.ends_with(".json"))
+        })
+        .collect();
+    backups.sort();
+    Ok(backups)
+}
+
+/// Write every backup again with the current `encrypt_database` setting, so
+/// turning encryption on doesn't leave plain-text copies of the database
+/// behind. Returns how many backups were rewritten.
+pub fn rewrite_backups() -> Result<usize> {
+    let backups = list_backups()?;
+    for backup in &backups {
+        let content = read_data_file(backup)?;
+        write_data_file(backup, content, "rewrite a backup")?;
+    }
+    Ok(backups.len())
+}
+
+/// Reject snippets over the 1MB size limit
+fn check_snippet_size(snippet: &str) -> Result<()> {
     if snippet.len() > 1_000_000 {
-        // 1MB limit
         return Err(SniptError::Other(
             "Snippet is too large. Maximum size is 1MB.".to_string(),
         ));
     }
+    Ok(())
+}
+
+/// Add a new snippet
+pub fn add_snippet(shortcut: String, snippet: String) -> Result<()> {
+    add_entry(SnippetEntry::new(shortcut, snippet), false).map(|_| ())
+}
+
+/// Add a snippet, or replace the text of an existing snippet with the same
+/// shortcut. Returns true if an existing snippet was replaced; its group,
+/// language and other settings are kept.
+pub fn upsert_snippet(shortcut: String, snippet: String) -> Result<bool> {
+    add_entry(SnippetEntry::new(shortcut, snippet), true)
+}
+
+/// Add a fully built snippet with a single save. With `replace`, an existing
+/// snippet with the same shortcut gets the new text, and the group, trigger,
+/// kind, label and description `entry` sets; its other settings are kept.
+/// Returns true if an existing snippet was replaced.
+pub fn add_entry(entry: SnippetEntry, replace: bool) -> Result<bool> {
+    check_snippet_size(&entry.snippet)?;
+    if let Some(trigger) = entry.trigger {
+        validate_trigger_chars(trigger, execute_char())?;
+    }
 
     let mut snippets = match load_snippets() {
         Ok(s) => s,
@@ -49,28 +308,453 @@ pub fn add_snippet(shortcut: String, snippet: String) -> Result<()> {
         Err(e) => return Err(e),
     };
 
-    // Check for duplicate shortcut
-    if snippets.iter().any(|entry| entry.shortcut == shortcut) {
+    let entry = SnippetEntry {
+        snippet: prepare_snippet_text(entry.snippet),
+        ..entry
+    };
+    let replaced = merge_entry(&mut snippets, entry, replace)?;
+    save_snippets(&snippets)?;
+    Ok(replaced)
+}
+
+/// Add `entry` to `snippets` as `add_entry` does, without saving
+fn merge_entry(
+    snippets: &mut Vec<SnippetEntry>,
+    entry: SnippetEntry,
+    replace: bool,
+) -> Result<bool> {
+    let group = normalize_group(entry.group);
+    let trigger = entry.trigger.filter(|&trigger| trigger != trigger_char());
+    let label = entry.label.filter(|label| !label.trim().is_empty());
+    let description = entry.description.trim().to_string();
+    validate_snippet_kind(entry.kind, label.as_deref(), &entry.snippet)?;
+
+    let Some(existing) = snippets
+        .iter_mut()
+        .find(|existing| existing.shortcut == entry.shortcut)
+    else {
+        snippets.push(SnippetEntry {
+            group,
+            trigger,
+            label,
+            description,
+            ..entry
+        });
+        return Ok(false);
+    };
+    if !replace {
         return Err(SniptError::Other(format!(
             "Shortcut '{}' already exists",
-            shortcut
+            entry.shortcut
         )));
     }
 
-    let entry = SnippetEntry::new(shortcut, snippet);
-    snippets.push(entry);
-    save_snippets(&snippets)
+    let mut updated = existing.clone();
+    updated.update_snippet(entry.snippet);
+    if group.is_some() {
+        updated.group = group;
+    }
+    if trigger.is_some() {
+        updated.trigger = trigger;
+    }
+    if entry.kind != SnippetKind::Text {
+        updated.kind = entry.kind;
+        updated.label = label;
+    }
+    if !description.is_empty() {
+        updated.description = description;
+    }
+    validate_snippet_kind(updated.kind, updated.label.as_deref(), &updated.snippet)?;
+    *existing = updated;
+    Ok(true)
+}
+
+/// Convert CRLF line endings to LF and strip trailing whitespace from every
+/// line. Leading indentation is kept.
+pub fn normalize_snippet(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .split('\n')
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Apply the `normalize_whitespace` setting to snippet text about to be saved
+fn prepare_snippet_text(text: String) -> String {
+    if current_config().normalize_whitespace {
+        normalize_snippet(&text)
+    } else {
+        text
+    }
+}
+
+/// Outcome of merging imported snippets into the database
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: Vec<String>,
+    /// ID to pass to `undo_import`, when the import changed anything
+    pub id: Option<String>,
+}
+
+/// Outcome of undoing an import
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct UndoSummary {
+    pub removed: usize,
+    pub restored: usize,
+}
+
+/// Number of imports kept in the journal; older ones can't be undone
+pub const IMPORT_JOURNAL_LIMIT: usize = 20;
+
+/// Load the journal of recent imports, oldest first
+pub fn load_import_journal() -> Result<Vec<ImportRecord>> {
+    let path = get_import_journal_path();
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let content = read_data_file(&path)?;
+    if content.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Save the journal of recent imports
+pub fn save_import_journal(journal: &[ImportRecord]) -> Result<()> {
+    ensure_config_dir()?;
+    write_data_file(
+        &get_import_journal_path(),
+        serde_json::to_string_pretty(journal)?,
+        "write the import journal",
+    )
+}
+
+/// Merge `entries` into `snippets`, skipping existing shortcuts unless
+/// `overwrite` is set. `record` collects what changed so it can be undone.
+pub fn merge_import(
+    snippets: &mut Vec<SnippetEntry>,
+    entries: Vec<SnippetEntry>,
+    overwrite: bool,
+    record: &mut ImportRecord,
+) -> ImportSummary {
+    let mut summary = ImportSummary::default();
+    for entry in entries {
+        match snippets.iter_mut().find(|e| e.shortcut == entry.shortcut) {
+            Some(existing) if overwrite => {
+                // Only the first value is worth restoring when a source
+                // repeats a shortcut
+                if !record.added.contains(&existing.shortcut)
+                    && !record
+                        .replaced
+                        .iter()
+                        .any(|old| old.shortcut == existing.shortcut)
+                {
+                    record.replaced.push(existing.clone());
+                }
+                existing.update_snippet(entry.snippet);
+                if entry.group.is_some() {
+                    existing.group = entry.group;
+                }
+                for tag in entry.tags {
+                    if !existing.tags.contains(&tag) {
+                        existing.tags.push(tag);
+                    }
+                }
+                summary.updated += 1;
+            }
+            Some(_) => summary.skipped.push(entry.shortcut),
+            None => {
+                record.added.push(entry.shortcut.clone());
+                snippets.push(entry);
+                summary.added += 1;
+            }
+        }
+    }
+    summary
+}
+
+/// Reverse an import in `snippets`: remove the snippets it added and put back
+/// the ones it overwrote
+pub fn revert_import(snippets: &mut Vec<SnippetEntry>, record: &ImportRecord) -> UndoSummary {
+    let before = snippets.len();
+    snippets.retain(|entry| !record.added.contains(&entry.shortcut));
+    let mut summary = UndoSummary {
+        removed: before - snippets.len(),
+        restored: 0,
+    };
+
+    for old in &record.replaced {
+        match snippets.iter_mut().find(|e| e.shortcut == old.shortcut) {
+            Some(existing) => *existing = old.clone(),
+            None => snippets.push(old.clone()),
+        }
+        summary.restored += 1;
+    }
+    summary
+}
+
+/// Pick an ID for an import made now that isn't in the journal yet
+fn new_import_id(journal: &[ImportRecord]) -> String {
+    let base = Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let taken = |id: &str| journal.iter().any(|record| record.id == id);
+    if !taken(&base) {
+        return base;
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|id| !taken(id))
+        .unwrap()
+}
+
+/// Merge imported snippets into the database, skipping existing shortcuts
+/// unless `overwrite` is set. The database is backed up before overwriting,
+/// and the changes are recorded in the import journal so they can be undone.
+pub fn import_snippets(entries: Vec<SnippetEntry>, overwrite: bool) -> Result<ImportSummary> {
+    let mut snippets = match load_snippets() {
+        Ok(s) => s,
+        Err(SniptError::DatabaseNotFound(_)) => vec![],
+        Err(e) => return Err(e),
+    };
+
+    if overwrite
+        && entries
+            .iter()
+            .any(|entry| find_snippet(&snippets, &entry.shortcut).is_some())
+    {
+        backup_database()?;
+    }
+
+    let mut journal = load_import_journal()?;
+    let mut record = ImportRecord {
+        id: new_import_id(&journal),
+        imported_at: Local::now().to_rfc3339(),
+        ..ImportRecord::default()
+    };
+    let mut summary = merge_import(&mut snippets, entries, overwrite, &mut record);
+
+    save_snippets(&snippets)?;
+    if !record.added.is_empty() || !record.replaced.is_empty() {
+        summary.id = Some(record.id.clone());
+        journal.push(record);
+        let excess = journal.len().saturating_sub(IMPORT_JOURNAL_LIMIT);
+        journal.drain(..excess);
+        save_import_journal(&journal)?;
+    }
+    Ok(summary)
+}
+
+/// Undo the import with `id`, removing it from the journal. The database is
+/// backed up first.
+pub fn undo_import(id: &str) -> Result<UndoSummary> {
+    let mut journal = load_import_journal()?;
+    let index = journal
+        .iter()
+        .position(|record| record.id == id)
+        .ok_or_else(|| {
+            let recent: Vec<&str> = journal
+                .iter()
+                .rev()
+                .map(|record| record.id.as_str())
+                .collect();
+            SniptError::Other(if recent.is_empty() {
+                format!("No import with ID '{}'; there are no imports to undo", id)
+            } else {
+                format!(
+                    "No import with ID '{}'. Recent imports: {}",
+                    id,
+                    recent.join(", ")
+                )
+            })
+        })?;
+
+    let mut snippets = load_snippets()?;
+    backup_database()?;
+    let summary = revert_import(&mut snippets, &journal[index]);
+    save_snippets(&snippets)?;
+
+    journal.remove(index);
+    save_import_journal(&journal)?;
+    Ok(summary)
+}
+
+/// Version written to `.sniptpack` files
+pub const PACK_VERSION: u32 = 1;
+
+/// File extension of snippet packs
+pub const PACK_EXTENSION: &str = "sniptpack";
+
+/// Check whether a snippet is in `group` or one of its subgroups
+fn in_group(entry: &SnippetEntry, group: &str) -> bool {
+    let wanted: Vec<&str> = group
+        .split('/')
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    entry.group_segments().starts_with(&wanted)
+}
+
+/// Build a pack from the given shortcuts, or the snippets in `group`, or all
+/// snippets when neither is given
+pub fn build_pack(
+    snippets: &[SnippetEntry],
+    info: PackInfo,
+    shortcuts: &[String],
+    group: Option<&str>,
+) -> Result<SnippetPack> {
+    if info.name.trim().is_empty() {
+        return Err(SniptError::Other("A snippet pack needs a name".to_string()));
+    }
+    for shortcut in shortcuts {
+        if find_snippet(snippets, shortcut).is_none() {
+            return Err(shortcut_not_found(snippets, shortcut));
+        }
+    }
+
+    let snippets: Vec<SnippetEntry> = snippets
+        .iter()
+        .filter(|entry| shortcuts.is_empty() || shortcuts.contains(&entry.shortcut))
+        .filter(|entry| group.map_or(true, |group| in_group(entry, group)))
+        .cloned()
+        .collect();
+    if snippets.is_empty() {
+        return Err(SniptError::Other(
+            "No snippets match, so there is nothing to put in the pack".to_string(),
+        ));
+    }
+
+    Ok(SnippetPack {
+        version: PACK_VERSION,
+        info,
+        snippets,
+    })
+}
+
+/// Write a snippet pack to `path`. Packs are always plain JSON so they can be
+/// shared. Returns the number of snippets written.
+pub fn export_pack(
+    path: &Path,
+    info: PackInfo,
+    shortcuts: &[String],
+    group: Option<&str>,
+) -> Result<usize> {
+    let pack = build_pack(&load_snippets()?, info, shortcuts, group)?;
+    fs::write(path, serde_json::to_string_pretty(&pack)?)
+        .map_err(|e| storage_error("write the snippet pack", path, e))?;
+    Ok(pack.snippets.len())
+}
+
+/// The pack file to write for `path`, adding `.sniptpack` when it has no
+/// extension
+pub fn pack_path(path: &Path) -> PathBuf {
+    if path.extension().is_some() {
+        path.to_path_buf()
+    } else {
+        path.with_extension(PACK_EXTENSION)
+    }
+}
+
+/// Parse the contents of a `.sniptpack` file
+pub fn parse_pack(content: &str) -> Result<SnippetPack> {
+    let mut pack: SnippetPack = serde_json::from_str(content)?;
+    if pack.version > PACK_VERSION {
+        return Err(SniptError::Other(format!(
+            "The snippet pack uses format version {}, but this snipt only understands up to version {}. Please upgrade snipt.",
+            pack.version, PACK_VERSION
+        )));
+    }
+    for entry in &mut pack.snippets {
+        entry.fill_created_at();
+    }
+    Ok(pack)
+}
+
+/// Read a snippet pack from `path`
+pub fn read_pack(path: &Path) -> Result<SnippetPack> {
+    parse_pack(&fs::read_to_string(path)?)
+}
+
+/// Merge a pack's snippets into the database, like `import_snippets`
+pub fn import_pack(pack: SnippetPack, overwrite: bool) -> Result<ImportSummary> {
+    import_snippets(pack.snippets, overwrite)
 }
 
-/// Delete a snippet by shortcut
+/// Delete a snippet by shortcut, moving it to the trash
 pub fn delete_snippet(shortcut: &str) -> Result<()> {
     let mut snippets = load_snippets()?;
-    snippets.retain(|entry| entry.shortcut != shortcut);
+    let mut trash = load_trash()?;
+    if !move_to_trash(&mut snippets, &mut trash, shortcut) {
+        return Err(shortcut_not_found(&snippets, shortcut));
+    }
+    // Save the trash first so a failure can't lose the snippet
+    save_trash(&trash)?;
     save_snippets(&snippets)
 }
 
+/// Move the snippet with `shortcut` into `trash`, replacing an older trashed
+/// snippet with the same shortcut. Returns false if there is no such snippet.
+pub fn move_to_trash(
+    snippets: &mut Vec<SnippetEntry>,
+    trash: &mut Vec<TrashEntry>,
+    shortcut: &str,
+) -> bool {
+    let Some(index) = snippets.iter().position(|entry| entry.shortcut == shortcut) else {
+        return false;
+    };
+    let entry = snippets.remove(index);
+    trash.retain(|trashed| trashed.entry.shortcut != shortcut);
+    trash.push(TrashEntry {
+        deleted_at: Local::now().to_rfc3339(),
+        entry,
+    });
+    true
+}
+
+/// Move the trashed snippet with `shortcut` back into `snippets`
+pub fn restore_from_trash(
+    snippets: &mut Vec<SnippetEntry>,
+    trash: &mut Vec<TrashEntry>,
+    shortcut: &str,
+) -> Result<()> {
+    let index = trash
+        .iter()
+        .position(|trashed| trashed.entry.shortcut == shortcut)
+        .ok_or_else(|| SniptError::Other(format!("'{}' is not in the trash", shortcut)))?;
+    if find_snippet(snippets, shortcut).is_some() {
+        return Err(SniptError::Other(format!(
+            "A snippet with shortcut '{}' already exists. Rename or delete it before restoring",
+            shortcut
+        )));
+    }
+
+    snippets.push(trash.remove(index).entry);
+    Ok(())
+}
+
+/// Restore a deleted snippet from the trash
+pub fn restore_snippet(shortcut: &str) -> Result<()> {
+    let mut snippets = load_snippets()?;
+    let mut trash = load_trash()?;
+    restore_from_trash(&mut snippets, &mut trash, shortcut)?;
+    // Save the snippets first so a failure leaves the snippet in the trash
+    save_snippets(&snippets)?;
+    save_trash(&trash)
+}
+
+/// Permanently remove every snippet in the trash. Returns how many were removed.
+pub fn empty_trash() -> Result<usize> {
+    let trash = load_trash()?;
+    if !trash.is_empty() {
+        save_trash(&[])?;
+    }
+    Ok(trash.len())
+}
+
 /// Update an existing snippet
 pub fn update_snippet(shortcut: &str, new_snippet: String) -> Result<()> {
+    let new_snippet = prepare_snippet_text(new_snippet);
     let mut snippets = load_snippets()?;
     let mut updated = false;
 
@@ -82,16 +766,755 @@ pub fn update_snippet(shortcut: &str, new_snippet: String) -> Result<()> {
     }
 
     if !updated {
+        return Err(shortcut_not_found(&snippets, shortcut));
+    }
+
+    save_snippets(&snippets)
+}
+
+/// Move a snippet into a group such as `code/rust`, or out of any group with `None`
+pub fn set_snippet_group(shortcut: &str, group: Option<String>) -> Result<()> {
+    let mut snippets = load_snippets()?;
+    let index = snippets
+        .iter()
+        .position(|entry| entry.shortcut == shortcut)
+        .ok_or_else(|| shortcut_not_found(&snippets, shortcut))?;
+    snippets[index].group = normalize_group(group);
+    save_snippets(&snippets)
+}
+
+/// A group without surrounding whitespace and slashes, `None` if that leaves
+/// nothing
+fn normalize_group(group: Option<String>) -> Option<String> {
+    group
+        .map(|group| group.trim().trim_matches('/').to_string())
+        .filter(|group| !group.is_empty())
+}
+
+/// Set or, when empty, clear a snippet's description
+pub fn set_snippet_description(shortcut: &str, description: &str) -> Result<()> {
+    let mut snippets = load_snippets()?;
+    let index = snippets
+        .iter()
+        .position(|entry| entry.shortcut == shortcut)
+        .ok_or_else(|| shortcut_not_found(&snippets, shortcut))?;
+    snippets[index].description = description.trim().to_string();
+    save_snippets(&snippets)
+}
+
+/// Check a tag name, returning it trimmed. Tags are single words so they can
+/// be listed and typed easily.
+pub fn validate_tag(tag: &str) -> Result<&str> {
+    let tag = tag.trim();
+    if tag.is_empty() || tag.contains(|c: char| c.is_whitespace() || c == ',') {
         return Err(SniptError::Other(format!(
-            "Shortcut '{}' not found",
-            shortcut
+            "Invalid tag '{}': tags can't be empty or contain spaces or commas",
+            tag
         )));
     }
+    Ok(tag)
+}
+
+/// Whether a snippet's shortcut or body contains `pattern`, ignoring case
+fn contains_ignore_case(entry: &SnippetEntry, pattern: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    entry.shortcut.to_lowercase().contains(&pattern)
+        || entry.snippet.to_lowercase().contains(&pattern)
+}
+
+/// Tag the snippets whose shortcut or body contains `pattern`, returning how
+/// many of them didn't have the tag yet
+pub fn tag_matching(snippets: &mut [SnippetEntry], tag: &str, pattern: &str) -> usize {
+    let mut changed = 0;
+    for entry in snippets
+        .iter_mut()
+        .filter(|entry| contains_ignore_case(entry, pattern))
+    {
+        if !entry.tags.iter().any(|existing| existing == tag) {
+            entry.tags.push(tag.to_string());
+            changed += 1;
+        }
+    }
+    changed
+}
+
+/// Remove a tag from the snippets whose shortcut or body contains `pattern`,
+/// returning how many of them had it
+pub fn untag_matching(snippets: &mut [SnippetEntry], tag: &str, pattern: &str) -> usize {
+    let mut changed = 0;
+    for entry in snippets
+        .iter_mut()
+        .filter(|entry| contains_ignore_case(entry, pattern))
+    {
+        let before = entry.tags.len();
+        entry.tags.retain(|existing| existing != tag);
+        if entry.tags.len() != before {
+            changed += 1;
+        }
+    }
+    changed
+}
+
+/// Every tag in use with the number of snippets that have it, by name
+pub fn count_tags(snippets: &[SnippetEntry]) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for tag in snippets.iter().flat_map(|entry| &entry.tags) {
+        *counts.entry(tag.as_str()).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .map(|(tag, count)| (tag.to_string(), count))
+        .collect()
+}
+
+/// Add `tag` to every saved snippet matching `pattern`, returning how many changed
+pub fn add_tag(tag: &str, pattern: &str) -> Result<usize> {
+    let tag = validate_tag(tag)?;
+    let mut snippets = load_snippets()?;
+    let changed = tag_matching(&mut snippets, tag, pattern);
+    if changed > 0 {
+        save_snippets(&snippets)?;
+    }
+    Ok(changed)
+}
+
+/// Remove `tag` from every saved snippet matching `pattern`, returning how many changed
+pub fn remove_tag(tag: &str, pattern: &str) -> Result<usize> {
+    let mut snippets = load_snippets()?;
+    let changed = untag_matching(&mut snippets, tag.trim(), pattern);
+    if changed > 0 {
+        save_snippets(&snippets)?;
+    }
+    Ok(changed)
+}
+
+/// Every tag in use with the number of saved snippets that have it
+pub fn tag_counts() -> Result<Vec<(String, usize)>> {
+    Ok(count_tags(&load_snippets()?))
+}
+
+/// Give a snippet its own trigger character, or make it use the global one
+/// again when `trigger` is `None`
+pub fn set_snippet_trigger(shortcut: &str, trigger: Option<char>) -> Result<()> {
+    if let Some(trigger) = trigger {
+        validate_trigger_chars(trigger, execute_char())?;
+    }
 
+    let mut snippets = load_snippets()?;
+    let index = snippets
+        .iter()
+        .position(|entry| entry.shortcut == shortcut)
+        .ok_or_else(|| shortcut_not_found(&snippets, shortcut))?;
+    snippets[index].trigger = trigger.filter(|&trigger| trigger != trigger_char());
     save_snippets(&snippets)
 }
 
+/// Check that a snippet with `text` can be of `kind`: only links have a
+/// label, and a link's text must be a URL
+pub fn validate_snippet_kind(kind: SnippetKind, label: Option<&str>, text: &str) -> Result<()> {
+    if label.is_some() && kind != SnippetKind::Link {
+        return Err(SniptError::Other(
+            "Only link snippets have a label".to_string(),
+        ));
+    }
+    if kind == SnippetKind::Link && !is_url(text) {
+        return Err(SniptError::Other(
+            "A link snippet's text must be a URL".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Set what a snippet holds, and the label of a link
+pub fn set_snippet_kind(shortcut: &str, kind: SnippetKind, label: Option<String>) -> Result<()> {
+    let mut snippets = load_snippets()?;
+    let index = snippets
+        .iter()
+        .position(|entry| entry.shortcut == shortcut)
+        .ok_or_else(|| shortcut_not_found(&snippets, shortcut))?;
+    let entry = &mut snippets[index];
+    validate_snippet_kind(kind, label.as_deref(), &entry.snippet)?;
+    entry.kind = kind;
+    entry.label = label.filter(|label| !label.trim().is_empty());
+    save_snippets(&snippets)
+}
+
+/// Enable a disabled snippet or disable an enabled one, returning whether it
+/// is now enabled
+pub fn toggle_snippet(shortcut: &str) -> Result<bool> {
+    let mut snippets = load_snippets()?;
+    let index = snippets
+        .iter()
+        .position(|entry| entry.shortcut == shortcut)
+        .ok_or_else(|| shortcut_not_found(&snippets, shortcut))?;
+    let entry = &mut snippets[index];
+    entry.enabled = !entry.enabled;
+    let enabled = entry.enabled;
+
+    save_snippets(&snippets)?;
+    Ok(enabled)
+}
+
+/// Pin an unpinned snippet or unpin a pinned one, returning whether it is
+/// now pinned
+pub fn toggle_pin(shortcut: &str) -> Result<bool> {
+    let mut snippets = load_snippets()?;
+    let pinned = toggle_pin_in(&mut snippets, shortcut)?;
+    save_snippets(&snippets)?;
+    Ok(pinned)
+}
+
+/// `toggle_pin` on loaded snippets, without saving
+fn toggle_pin_in(snippets: &mut [SnippetEntry], shortcut: &str) -> Result<bool> {
+    let index = snippets
+        .iter()
+        .position(|entry| entry.shortcut == shortcut)
+        .ok_or_else(|| shortcut_not_found(snippets, shortcut))?;
+    let entry = &mut snippets[index];
+    entry.pinned = !entry.pinned;
+    Ok(entry.pinned)
+}
+
+/// Copy a snippet under a new shortcut (`<shortcut>-copy`, numbered if that
+/// is taken), returning the new shortcut
+pub fn duplicate_snippet(shortcut: &str) -> Result<String> {
+    let mut snippets = load_snippets()?;
+    let new_shortcut = duplicate_in(&mut snippets, shortcut)?;
+    save_snippets(&snippets)?;
+    Ok(new_shortcut)
+}
+
+/// `duplicate_snippet` on loaded snippets, without saving
+fn duplicate_in(snippets: &mut Vec<SnippetEntry>, shortcut: &str) -> Result<String> {
+    let original =
+        find_snippet(snippets, shortcut).ok_or_else(|| shortcut_not_found(snippets, shortcut))?;
+
+    let base = format!("{}-copy", shortcut);
+    let new_shortcut = (1..)
+        .map(|n| {
+            if n == 1 {
+                base.clone()
+            } else {
+                format!("{}{}", base, n)
+            }
+        })
+        .find(|candidate| find_snippet(snippets, candidate).is_none())
+        .unwrap_or(base);
+
+    let mut copy = SnippetEntry::new(new_shortcut.clone(), original.snippet.clone());
+    copy.language = original.language.clone();
+    copy.html = original.html.clone();
+    copy.enabled = original.enabled;
+    copy.group = original.group.clone();
+    snippets.push(copy);
+    Ok(new_shortcut)
+}
+
 /// Find a snippet by shortcut
 pub fn find_snippet<'a>(snippets: &'a [SnippetEntry], shortcut: &str) -> Option<&'a SnippetEntry> {
     snippets.iter().find(|entry| entry.shortcut == shortcut)
 }
+
+/// Up to `n` enabled snippets for quick access: pinned snippets in their
+/// saved order, then the most used ones according to `usage` (as returned by
+/// `history::usage_counts`)
+pub fn favorite_snippets<'a>(
+    snippets: &'a [SnippetEntry],
+    usage: &[(String, usize)],
+    n: usize,
+) -> Vec<&'a SnippetEntry> {
+    let pinned = snippets
+        .iter()
+        .filter(|entry| entry.enabled && entry.pinned);
+    let most_used = usage
+        .iter()
+        .filter_map(|(shortcut, _)| find_snippet(snippets, shortcut))
+        .filter(|entry| entry.enabled && !entry.pinned);
+    pinned.chain(most_used).take(n).collect()
+}
+
+/// Number of single-character insertions, deletions and substitutions
+/// needed to turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Up to `n` shortcuts that are close to `input`, nearest first. Shortcuts
+/// needing more than a third of the input's length in edits (at least 2) are
+/// not considered close.
+pub fn nearest_shortcuts(snippets: &[SnippetEntry], input: &str, n: usize) -> Vec<String> {
+    let input = input.to_lowercase();
+    let max_distance = (input.chars().count() / 3).max(2);
+    let mut candidates: Vec<(usize, &str)> = snippets
+        .iter()
+        .map(|entry| {
+            let distance = edit_distance(&input, &entry.shortcut.to_lowercase());
+            (distance, entry.shortcut.as_str())
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    candidates.sort();
+    candidates
+        .into_iter()
+        .take(n)
+        .map(|(_, shortcut)| shortcut.to_string())
+        .collect()
+}
+
+/// Up to `n` saved shortcuts that look like typos of `input`, nearest first
+pub fn suggest_shortcuts(input: &str, n: usize) -> Result<Vec<String>> {
+    Ok(nearest_shortcuts(&load_snippets()?, input, n))
+}
+
+/// Message for a shortcut that doesn't exist, suggesting close matches
+pub fn shortcut_not_found_message(snippets: &[SnippetEntry], shortcut: &str) -> String {
+    let suggestions = nearest_shortcuts(snippets, shortcut, 3);
+    if suggestions.is_empty() {
+        format!("Shortcut '{}' not found", shortcut)
+    } else {
+        format!(
+            "Shortcut '{}' not found. Did you mean: {}?",
+            shortcut,
+            suggestions.join(", ")
+        )
+    }
+}
+
+/// Error for a shortcut that doesn't exist, suggesting close matches
+pub fn shortcut_not_found(snippets: &[SnippetEntry], shortcut: &str) -> SniptError {
+    SniptError::Other(shortcut_not_found_message(snippets, shortcut))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_shortcuts() {
+        assert_eq!(edit_distance("teh-thing", "the-thing"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+
+        let snippets: Vec<SnippetEntry> = ["the-thing", "thing", "sig", "signature"]
+            .iter()
+            .map(|shortcut| SnippetEntry::new(shortcut.to_string(), String::new()))
+            .collect();
+        assert_eq!(
+            nearest_shortcuts(&snippets, "teh-thing", 3),
+            vec!["the-thing"]
+        );
+        assert_eq!(nearest_shortcuts(&snippets, "SIGG", 3), vec!["sig"]);
+        assert!(nearest_shortcuts(&snippets, "xyz", 3).is_empty());
+    }
+
+    #[test]
+    fn test_merge_entry() {
+        let mut snippets = vec![SnippetEntry {
+            group: Some("mail".to_string()),
+            pinned: true,
+            ..SnippetEntry::new("sig".to_string(), "Jane".to_string())
+        }];
+
+        // A new entry is added with its settings tidied up
+        let entry = SnippetEntry {
+            group: Some("/code/rust/".to_string()),
+            description: " Say hi ".to_string(),
+            ..SnippetEntry::new("hi".to_string(), "Hello".to_string())
+        };
+        assert!(!merge_entry(&mut snippets, entry, false).unwrap());
+        assert_eq!(snippets[1].group.as_deref(), Some("code/rust"));
+        assert_eq!(snippets[1].description, "Say hi");
+
+        // An existing shortcut is only replaced when asked to
+        let entry = SnippetEntry::new("sig".to_string(), "Bob".to_string());
+        assert!(merge_entry(&mut snippets, entry, false).is_err());
+        assert_eq!(snippets[0].snippet, "Jane");
+
+        // Replacing changes the text and what the entry sets, and keeps the rest
+        let entry = SnippetEntry {
+            description: "Signature".to_string(),
+            ..SnippetEntry::new("sig".to_string(), "Bob".to_string())
+        };
+        assert!(merge_entry(&mut snippets, entry, true).unwrap());
+        assert_eq!(snippets.len(), 2);
+        assert_eq!(snippets[0].snippet, "Bob");
+        assert_eq!(snippets[0].description, "Signature");
+        assert_eq!(snippets[0].group.as_deref(), Some("mail"));
+        assert!(snippets[0].pinned);
+
+        // A link snippet's text must still be a URL
+        let entry = SnippetEntry {
+            kind: SnippetKind::Link,
+            ..SnippetEntry::new("docs".to_string(), "not a url".to_string())
+        };
+        assert!(merge_entry(&mut snippets, entry, false).is_err());
+        assert_eq!(snippets.len(), 2);
+    }
+
+    #[test]
+    fn test_toggle_pin() {
+        let mut snippets = vec![SnippetEntry::new("sig".to_string(), "Jane".to_string())];
+        assert!(toggle_pin_in(&mut snippets, "sig").unwrap());
+        assert!(snippets[0].pinned);
+        assert!(!toggle_pin_in(&mut snippets, "sig").unwrap());
+        assert!(!snippets[0].pinned);
+        assert!(toggle_pin_in(&mut snippets, "nope").is_err());
+    }
+
+    #[test]
+    fn test_duplicate_snippet() {
+        let mut original = SnippetEntry::new("sig".to_string(), "Jane".to_string());
+        original.group = Some("mail".to_string());
+        original.enabled = false;
+        original.pinned = true;
+        let mut snippets = vec![original];
+
+        // The copy keeps the text and settings, but isn't pinned
+        assert_eq!(duplicate_in(&mut snippets, "sig").unwrap(), "sig-copy");
+        let copy = &snippets[1];
+        assert_eq!(copy.snippet, "Jane");
+        assert_eq!(copy.group.as_deref(), Some("mail"));
+        assert!(!copy.enabled);
+        assert!(!copy.pinned);
+
+        // Taken names are skipped
+        assert_eq!(duplicate_in(&mut snippets, "sig").unwrap(), "sig-copy2");
+        assert_eq!(duplicate_in(&mut snippets, "sig").unwrap(), "sig-copy3");
+        assert_eq!(snippets.len(), 4);
+
+        assert!(duplicate_in(&mut snippets, "nope").is_err());
+        assert_eq!(snippets.len(), 4);
+    }
+
+    #[test]
+    fn test_favorite_snippets() {
+        let mut snippets: Vec<SnippetEntry> = ["sig", "addr", "todo", "off", "pin"]
+            .iter()
+            .map(|shortcut| SnippetEntry::new(shortcut.to_string(), String::new()))
+            .collect();
+        snippets[3].enabled = false;
+        snippets[4].pinned = true;
+
+        let usage = vec![
+            ("off".to_string(), 9),
+            ("todo".to_string(), 5),
+            ("gone".to_string(), 4),
+            ("pin".to_string(), 3),
+            ("sig".to_string(), 1),
+        ];
+        let shortcuts = |n| {
+            favorite_snippets(&snippets, &usage, n)
+                .iter()
+                .map(|entry| entry.shortcut.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(shortcuts(5), vec!["pin", "todo", "sig"]);
+        assert_eq!(shortcuts(2), vec!["pin", "todo"]);
+    }
+
+    #[test]
+    fn test_undo_import() {
+        let mut snippets = vec![
+            SnippetEntry::new("sig".to_string(), "Regards".to_string()),
+            SnippetEntry::new("addr".to_string(), "1 Main St".to_string()),
+        ];
+        snippets[0].tags = vec!["email".to_string()];
+        let original = snippets.clone();
+        let mut best = SnippetEntry::new("sig".to_string(), "Best".to_string());
+        best.tags = vec!["work".to_string(), "email".to_string()];
+        let imported = vec![
+            SnippetEntry::new("sig".to_string(), "Cheers".to_string()),
+            SnippetEntry::new("tel".to_string(), "555-0100".to_string()),
+            best,
+        ];
+
+        let mut record = ImportRecord::default();
+        let summary = merge_import(&mut snippets, imported.clone(), true, &mut record);
+        assert_eq!((summary.added, summary.updated), (1, 2));
+        assert_eq!(record.added, vec!["tel"]);
+        // Only the value from before the import is kept
+        assert_eq!(record.replaced.len(), 1);
+        assert_eq!(record.replaced[0].snippet, "Regards");
+        assert_eq!(find_snippet(&snippets, "sig").unwrap().snippet, "Best");
+        // Imported tags are added to the ones the snippet already had
+        assert_eq!(
+            find_snippet(&snippets, "sig").unwrap().tags,
+            vec!["email", "work"]
+        );
+
+        let undone = revert_import(&mut snippets, &record);
+        assert_eq!(
+            undone,
+            UndoSummary {
+                removed: 1,
+                restored: 1
+            }
+        );
+        assert_eq!(snippets.len(), 2);
+        for (entry, before) in snippets.iter().zip(&original) {
+            assert_eq!(entry.shortcut, before.shortcut);
+            assert_eq!(entry.snippet, before.snippet);
+            assert_eq!(entry.tags, before.tags);
+        }
+
+        // Without overwrite nothing existing is recorded or changed
+        let mut record = ImportRecord::default();
+        let summary = merge_import(&mut snippets, imported, false, &mut record);
+        assert_eq!(summary.skipped, vec!["sig", "sig"]);
+        assert!(record.replaced.is_empty());
+        assert_eq!(revert_import(&mut snippets, &record).removed, 1);
+        assert_eq!(snippets.len(), 2);
+    }
+
+    #[test]
+    fn test_trash() {
+        let mut snippets = vec![
+            SnippetEntry::new("hi".to_string(), "hello".to_string()),
+            SnippetEntry::new("bye".to_string(), "goodbye".to_string()),
+        ];
+        let mut trash = vec![];
+
+        assert!(!move_to_trash(&mut snippets, &mut trash, "missing"));
+        assert!(move_to_trash(&mut snippets, &mut trash, "hi"));
+        assert_eq!(snippets.len(), 1);
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].entry.snippet, "hello");
+
+        // Deleting a new snippet with the same shortcut replaces the old one
+        snippets.push(SnippetEntry::new("hi".to_string(), "hey".to_string()));
+        assert!(move_to_trash(&mut snippets, &mut trash, "hi"));
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].entry.snippet, "hey");
+
+        // Restoring fails while another snippet uses the shortcut
+        snippets.push(SnippetEntry::new("hi".to_string(), "again".to_string()));
+        assert!(restore_from_trash(&mut snippets, &mut trash, "hi").is_err());
+        assert_eq!(trash.len(), 1);
+
+        snippets.retain(|entry| entry.shortcut != "hi");
+        restore_from_trash(&mut snippets, &mut trash, "hi").unwrap();
+        assert!(trash.is_empty());
+        assert_eq!(find_snippet(&snippets, "hi").unwrap().snippet, "hey");
+        assert!(restore_from_trash(&mut snippets, &mut trash, "hi").is_err());
+
+        // The trash file keeps the snippet's fields next to the deletion time
+        move_to_trash(&mut snippets, &mut trash, "bye");
+        let json = serde_json::to_string(&trash).unwrap();
+        assert!(json.contains("\"deleted_at\"") && json.contains("\"shortcut\":\"bye\""));
+        let parsed: Vec<TrashEntry> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed[0].entry.snippet, "goodbye");
+    }
+
+    #[test]
+    fn test_tags() {
+        let mut snippets = vec![
+            SnippetEntry::new("jira-bug".to_string(), "Steps to reproduce".to_string()),
+            SnippetEntry::new("sig".to_string(), "Sent from JIRA".to_string()),
+            SnippetEntry::new("addr".to_string(), "1 Main St".to_string()),
+        ];
+
+        // Shortcuts and bodies both match, ignoring case
+        assert_eq!(tag_matching(&mut snippets, "work", "jira"), 2);
+        assert_eq!(tag_matching(&mut snippets, "work", "jira"), 0);
+        assert_eq!(tag_matching(&mut snippets, "home", "main"), 1);
+        assert_eq!(tag_matching(&mut snippets, "old", ""), 3);
+        assert_eq!(snippets[0].tags, vec!["work", "old"]);
+        assert_eq!(
+            count_tags(&snippets),
+            vec![
+                ("home".to_string(), 1),
+                ("old".to_string(), 3),
+                ("work".to_string(), 2)
+            ]
+        );
+
+        assert_eq!(untag_matching(&mut snippets, "old", "sig"), 1);
+        assert_eq!(untag_matching(&mut snippets, "missing", ""), 0);
+        assert_eq!(snippets[1].tags, vec!["work"]);
+
+        assert_eq!(validate_tag(" work ").unwrap(), "work");
+        assert!(validate_tag("two words").is_err());
+        assert!(validate_tag(" ").is_err());
+    }
+
+    #[test]
+    fn test_prune_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let names = [
+            "snipt-20260101-090000-000.json",
+            "snipt-20260102-090000-000.json",
+            "snipt-20260103-090000-000.json",
+            "snipt-20260103-090000-500.json",
+            "snipt-20260104-090000-000.json",
+        ];
+        for name in names.iter().chain(&["notes.txt"]) {
+            fs::write(dir.path().join(name), "[]").unwrap();
+        }
+        let backups = |dir: &Path| -> Vec<String> {
+            list_backups_in(dir)
+                .unwrap()
+                .iter()
+                .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+                .collect()
+        };
+
+        // The oldest backups go; other files are left alone
+        prune_backups(dir.path(), 3).unwrap();
+        assert_eq!(backups(dir.path()), names[2..]);
+        assert!(dir.path().join("notes.txt").exists());
+
+        // Under the limit nothing is removed, and a limit of 0 keeps the newest
+        prune_backups(dir.path(), 10).unwrap();
+        assert_eq!(backups(dir.path()).len(), 3);
+        prune_backups(dir.path(), 0).unwrap();
+        assert_eq!(backups(dir.path()), names[4..]);
+
+        // A missing directory has no backups
+        assert!(list_backups_in(&dir.path().join("missing"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_pack_path() {
+        assert_eq!(pack_path(Path::new("team")), Path::new("team.sniptpack"));
+        assert_eq!(
+            pack_path(Path::new("out/team")),
+            Path::new("out/team.sniptpack")
+        );
+        assert_eq!(pack_path(Path::new("team.json")), Path::new("team.json"));
+    }
+
+    #[test]
+    fn test_snippet_packs() {
+        let mut snippets = vec![
+            SnippetEntry::new("sig".to_string(), "Best regards".to_string()),
+            SnippetEntry::new("fn".to_string(), "fn main() {}".to_string()),
+            SnippetEntry::new("hi".to_string(), "hello".to_string()),
+        ];
+        snippets[0].group = Some("email".to_string());
+        snippets[1].group = Some("code/rust".to_string());
+        let info = PackInfo {
+            name: "Team".to_string(),
+            description: "Shared replies".to_string(),
+            author: String::new(),
+        };
+
+        let pack = build_pack(&snippets, info.clone(), &[], Some("code")).unwrap();
+        assert_eq!(pack.snippets.len(), 1);
+        assert_eq!(pack.snippets[0].shortcut, "fn");
+        let pack = build_pack(&snippets, info.clone(), &["hi".to_string()], None).unwrap();
+        assert_eq!(pack.snippets[0].shortcut, "hi");
+        assert!(build_pack(&snippets, info.clone(), &["nope".to_string()], None).is_err());
+        assert!(build_pack(&snippets, info.clone(), &[], Some("cod")).is_err());
+        assert!(build_pack(&snippets, PackInfo::default(), &[], None).is_err());
+
+        // Metadata sits next to the snippets, and empty fields are left out
+        let pack = build_pack(&snippets, info, &[], None).unwrap();
+        let json = serde_json::to_string(&pack).unwrap();
+        assert!(json.starts_with("{\"version\":1,\"name\":\"Team\",\"description\""));
+        assert!(!json.contains("author"));
+        let parsed = parse_pack(&json).unwrap();
+        assert_eq!(parsed.info.description, "Shared replies");
+        assert_eq!(parsed.snippets.len(), 3);
+
+        let newer = json.replacen("\"version\":1", "\"version\":99", 1);
+        assert!(parse_pack(&newer).is_err());
+    }
+
+    #[test]
+    fn test_write_atomically() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("usage.json");
+        write_atomically(&path, b"{}").unwrap();
+        write_atomically(&path, b"{\"sig\":2}").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"sig\":2}");
+        // Nothing is left next to it
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_seed_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = dir.path().join("seed.json");
+        let db = dir.path().join("db").join("snipt.json");
+        let marker = dir.path().join("snipt.seeded");
+
+        // Nothing to import without a seed file
+        assert_eq!(seed_database_from(&seed, &db, &marker).unwrap(), None);
+        assert!(!db.exists());
+
+        fs::write(
+            &seed,
+            r#"[{"shortcut": "sig", "snippet": "Jane", "timestamp": ""}]"#,
+        )
+        .unwrap();
+        assert_eq!(seed_database_from(&seed, &db, &marker).unwrap(), Some(1));
+        let snippets = parse_database(&fs::read_to_string(&db).unwrap()).unwrap();
+        assert_eq!(snippets[0].shortcut, "sig");
+        assert!(marker.exists());
+
+        // The seed is imported only once, even if the database goes away
+        fs::remove_file(&db).unwrap();
+        assert_eq!(seed_database_from(&seed, &db, &marker).unwrap(), None);
+        assert!(!db.exists());
+
+        // A shortcut the seed repeats is only added once
+        fs::remove_file(&marker).unwrap();
+        fs::write(
+            &seed,
+            r#"[{"shortcut": "sig", "snippet": "Jane", "timestamp": ""},
+                {"shortcut": "sig", "snippet": "Bob", "timestamp": ""}]"#,
+        )
+        .unwrap();
+        assert_eq!(seed_database_from(&seed, &db, &marker).unwrap(), Some(1));
+        let snippets = parse_database(&fs::read_to_string(&db).unwrap()).unwrap();
+        assert_eq!(snippets.len(), 1);
+        assert_eq!(snippets[0].snippet, "Jane");
+
+        fs::remove_file(&marker).unwrap();
+        fs::write(&seed, "not json").unwrap();
+        assert!(seed_database_from(&seed, &db, &marker).is_err());
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn test_database_versions() {
+        // Version 1: a bare array without the newer fields
+        let legacy =
+            r#"[{"shortcut": "sig", "snippet": "Jane", "timestamp": "2023-01-01T00:00:00+00:00"}]"#;
+        let snippets = parse_database(legacy).unwrap();
+        assert_eq!(snippets[0].shortcut, "sig");
+        assert_eq!(snippets[0].created_at, "2023-01-01T00:00:00+00:00");
+        assert!(snippets[0].enabled);
+        assert!(snippets[0].description.is_empty());
+
+        // Saving upgrades to the versioned envelope, which reads back the same
+        let saved = serialize_database(&snippets).unwrap();
+        assert!(saved.contains(&format!("\"version\": {}", DB_VERSION)));
+        let reloaded = parse_database(&saved).unwrap();
+        assert_eq!(reloaded[0].snippet, "Jane");
+
+        assert!(parse_database("").unwrap().is_empty());
+        assert!(parse_database(r#"{"version": 99, "snippets": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_normalize_snippet() {
+        assert_eq!(normalize_snippet("Hi,\r\nthere\r\n"), "Hi,\nthere\n");
+        assert_eq!(
+            normalize_snippet("fn main() {  \r\n    run();\t\r\n}"),
+            "fn main() {\n    run();\n}"
+        );
+        assert_eq!(normalize_snippet("already clean"), "already clean");
+    }
+}
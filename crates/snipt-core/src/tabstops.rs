@@ -0,0 +1,159 @@
+//! TextMate-style tab stops in text snippets, used when `tab_stops` is on.
+//!
+//! Recognized markers, which are removed from the expanded text:
+//!
+//! - `$1`, `$2`, ... and `${1}`: a numbered stop
+//! - `${1:text}`: a stop whose placeholder `text` is kept (up to the first `}`)
+//! - `$0`: where the cursor ends up; it comes after all numbered stops
+//!
+//! `\$` gives a literal `$`. Anything else starting with `$`, such as `$name`,
+//! `${x}` or an unclosed `${1:`, is kept as written.
+//!
+//! Offsets are counted in characters of the expanded text.
+
+use serde::{Deserialize, Serialize};
+
+/// A place in the expanded text the cursor can jump to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TabStop {
+    /// The stop's number, 0 for the final position
+    pub index: u32,
+    /// Character offset into the expanded text
+    pub offset: usize,
+}
+
+/// Snippet text with its tab stop markers removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TabStops {
+    pub text: String,
+    /// Stops in the order they are visited: by number, then by position,
+    /// with `$0` last
+    pub stops: Vec<TabStop>,
+}
+
+impl TabStops {
+    /// Where the cursor goes right after expanding: the first stop
+    pub fn cursor(&self) -> Option<usize> {
+        self.stops.first().map(|stop| stop.offset)
+    }
+
+    /// Number of characters between the cursor and the end of the text
+    pub fn chars_after_cursor(&self) -> usize {
+        self.cursor()
+            .map(|offset| self.text.chars().count() - offset)
+            .unwrap_or(0)
+    }
+}
+
+/// Split a snippet body into its text and tab stops
+pub fn parse_tab_stops(body: &str) -> TabStops {
+    let chars: Vec<char> = body.chars().collect();
+    let mut text = String::new();
+    let mut length = 0;
+    let mut stops = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if chars.get(i + 1) == Some(&'$') => {
+                text.push('$');
+                length += 1;
+                i += 2;
+                continue;
+            }
+            '$' => {
+                if let Some((index, placeholder, end)) = parse_marker(&chars, i + 1) {
+                    stops.push(TabStop {
+                        index,
+                        offset: length,
+                    });
+                    text.push_str(&placeholder);
+                    length += placeholder.chars().count();
+                    i = end;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        text.push(chars[i]);
+        length += 1;
+        i += 1;
+    }
+
+    // `$0` sorts last; the sort is stable so repeats keep their text order
+    stops.sort_by_key(|stop| (stop.index == 0, stop.index));
+    TabStops { text, stops }
+}
+
+/// Parse the marker following a `$` at `start`, returning its number, its
+/// placeholder text and the position right after it
+fn parse_marker(chars: &[char], start: usize) -> Option<(u32, String, usize)> {
+    let digits_end = |from: usize| {
+        (from..chars.len())
+            .find(|&i| !chars[i].is_ascii_digit())
+            .unwrap_or(chars.len())
+    };
+    let number = |from: usize, to: usize| {
+        chars[from..to]
+            .iter()
+            .collect::<String>()
+            .parse::<u32>()
+            .ok()
+    };
+
+    if chars.get(start) == Some(&'{') {
+        let end = digits_end(start + 1);
+        let index = number(start + 1, end)?;
+        return match chars.get(end) {
+            Some('}') => Some((index, String::new(), end + 1)),
+            Some(':') => {
+                let close = (end + 1..chars.len()).find(|&i| chars[i] == '}')?;
+                let placeholder = chars[end + 1..close].iter().collect();
+                Some((index, placeholder, close + 1))
+            }
+            _ => None,
+        };
+    }
+
+    let end = digits_end(start);
+    let index = number(start, end)?;
+    Some((index, String::new(), end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stop(index: u32, offset: usize) -> TabStop {
+        TabStop { index, offset }
+    }
+
+    #[test]
+    fn test_parse_tab_stops() {
+        let parsed = parse_tab_stops("Dear $1,\n\n$0\n\nRegards, ${2:Ada} ($1)");
+        assert_eq!(parsed.text, "Dear ,\n\n\n\nRegards, Ada ()");
+        assert_eq!(
+            parsed.stops,
+            vec![stop(1, 5), stop(1, 24), stop(2, 19), stop(0, 8)]
+        );
+        assert_eq!(parsed.cursor(), Some(5));
+        assert_eq!(parsed.chars_after_cursor(), 20);
+
+        // Only `$0`: the cursor goes there
+        let parsed = parse_tab_stops("fn main() {\n    $0\n}");
+        assert_eq!(parsed.text, "fn main() {\n    \n}");
+        assert_eq!(parsed.cursor(), Some(16));
+
+        // Escapes and things that aren't stops stay as written
+        let parsed = parse_tab_stops(r"costs \$5, $name ${x} ${1:open $ é");
+        assert_eq!(parsed.text, "costs $5, $name ${x} ${1:open $ é");
+        assert!(parsed.stops.is_empty());
+        assert_eq!(parsed.cursor(), None);
+        assert_eq!(parsed.chars_after_cursor(), 0);
+
+        // Offsets count characters, not bytes
+        let parsed = parse_tab_stops("héllo ${1}wörld");
+        assert_eq!(parsed.stops, vec![stop(1, 6)]);
+        assert_eq!(parsed.chars_after_cursor(), 5);
+    }
+}
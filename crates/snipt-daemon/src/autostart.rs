@@ -0,0 +1,389 @@
+//! Starting the daemon at login with `snipt autostart`.
+//!
+//! Each platform gets the registration its own session manager reads, running
+//! `snipt start` (with `--profile` when a profile is active, and with
+//! `SNIPT_CONFIG_DIR` when it is set):
+//!
+//! - macOS: a launchd agent in `~/Library/LaunchAgents`
+//! - Linux: an XDG autostart entry in `~/.config/autostart`. Unlike a systemd
+//!   user service it only runs once the graphical session is up, which the
+//!   keyboard listener needs.
+//! - Windows: a value under the current user's `Run` registry key
+//!
+//! Every profile is registered under its own name, so several can start at
+//! login side by side.
+
+use snipt_core::config::{active_profile, config_dir_override, CONFIG_DIR_ENV};
+use snipt_core::crypto::encryption_in_use;
+use snipt_core::{Result, SniptError};
+use std::env;
+
+/// The command that starts the daemon, as the snipt binary and its arguments
+fn start_command() -> Result<Vec<String>> {
+    let mut command = vec![env::current_exe()?.to_string_lossy().to_string()];
+    if let Some(profile) = active_profile() {
+        command.push("--profile".to_string());
+        command.push(profile);
+    }
+    command.push("start".to_string());
+    Ok(command)
+}
+
+/// Environment variables the command needs, which the login session won't
+/// have: the config directory when `SNIPT_CONFIG_DIR` moved it
+fn start_environment() -> Vec<(String, String)> {
+    config_dir_override()
+        .map(|dir| vec![(CONFIG_DIR_ENV.to_string(), dir.display().to_string())])
+        .unwrap_or_default()
+}
+
+/// Name the registration is kept under for the active profile
+#[cfg_attr(not(any(target_os = "linux", target_os = "windows")), allow(dead_code))]
+fn entry_name() -> String {
+    match active_profile() {
+        Some(profile) => format!("snipt-{}", profile),
+        None => "snipt".to_string(),
+    }
+}
+
+/// Register `snipt start` to run at login
+pub fn enable_autostart() -> Result<()> {
+    // The daemon started at login has no terminal to ask for the passphrase,
+    // and the passphrase doesn't belong in the registration
+    if encryption_in_use() {
+        return Err(SniptError::Other(
+            "Starting snipt at login isn't supported with an encrypted snippet database"
+                .to_string(),
+        ));
+    }
+    let command = start_command()?;
+    let environment = start_environment();
+    let location = platform::register(&command, &environment)?;
+    println!("snipt will start at login ({})", location);
+    let variables: String = environment
+        .iter()
+        .map(|(name, value)| format!("{}={} ", name, value))
+        .collect();
+    println!("Command: {}{}", variables, command.join(" "));
+    Ok(())
+}
+
+/// Stop starting snipt at login
+pub fn disable_autostart() -> Result<()> {
+    match platform::unregister()? {
+        Some(location) => println!("snipt will no longer start at login (removed {})", location),
+        None => println!("snipt wasn't set to start at login"),
+    }
+    Ok(())
+}
+
+/// Print whether snipt is set to start at login
+pub fn autostart_status() -> Result<()> {
+    match platform::registered()? {
+        Some(location) => println!("snipt starts at login ({})", location),
+        None => {
+            println!("snipt doesn't start at login");
+            println!("Run 'snipt autostart enable' to start it at login");
+        }
+    }
+    Ok(())
+}
+
+/// Launch agents and autostart entries are files in the home directory
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+mod platform {
+    use super::*;
+    use snipt_core::config::storage_error;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn home_dir() -> Result<PathBuf> {
+        env::var("HOME")
+            .map(PathBuf::from)
+            .map_err(|_| SniptError::Other("HOME is not set".to_string()))
+    }
+
+    /// Label of the launch agent
+    #[cfg(target_os = "macos")]
+    fn label() -> String {
+        match active_profile() {
+            Some(profile) => format!("com.snipt.daemon.{}", profile),
+            None => "com.snipt.daemon".to_string(),
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn entry_path() -> Result<PathBuf> {
+        Ok(home_dir()?
+            .join("Library/LaunchAgents")
+            .join(format!("{}.plist", label())))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn entry_path() -> Result<PathBuf> {
+        let config_home = match env::var("XDG_CONFIG_HOME") {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => home_dir()?.join(".config"),
+        };
+        Ok(config_home
+            .join("autostart")
+            .join(format!("{}.desktop", entry_name())))
+    }
+
+    #[cfg(target_os = "macos")]
+    fn xml_escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+
+    /// A launch agent that runs `command` once at login. `snipt start`
+    /// detaches the daemon and exits, so launchd must leave its process
+    /// group alone.
+    #[cfg(target_os = "macos")]
+    fn entry_contents(command: &[String], environment: &[(String, String)]) -> String {
+        let arguments: String = command
+            .iter()
+            .map(|arg| format!("        <string>{}</string>\n", xml_escape(arg)))
+            .collect();
+        let variables = if environment.is_empty() {
+            String::new()
+        } else {
+            let entries: String = environment
+                .iter()
+                .map(|(name, value)| {
+                    format!(
+                        "        <key>{}</key>\n        <string>{}</string>\n",
+                        xml_escape(name),
+                        xml_escape(value)
+                    )
+                })
+                .collect();
+            format!(
+                "    <key>EnvironmentVariables</key>\n    <dict>\n{}    </dict>\n",
+                entries
+            )
+        };
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{}    </array>
+{}    <key>RunAtLoad</key>
+    <true/>
+    <key>AbandonProcessGroup</key>
+    <true/>
+</dict>
+</plist>
+"#,
+            xml_escape(&label()),
+            arguments,
+            variables
+        )
+    }
+
+    /// Quote an argument for the `Exec` key of a desktop entry. The key is
+    /// also a string value, whose own escapes are read first, so every
+    /// backslash of the Exec quoting is escaped again.
+    #[cfg(target_os = "linux")]
+    fn desktop_quote(arg: &str) -> String {
+        let mut quoted = String::from("\"");
+        for c in arg.chars() {
+            match c {
+                '"' | '`' | '$' => {
+                    quoted.push_str("\\\\");
+                    quoted.push(c);
+                }
+                '\\' => quoted.push_str("\\\\\\\\"),
+                '%' => quoted.push_str("%%"),
+                '\n' => quoted.push_str("\\n"),
+                '\t' => quoted.push_str("\\t"),
+                '\r' => quoted.push_str("\\r"),
+                _ => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        quoted
+    }
+
+    /// An autostart entry running `command`. Desktop entries can't set
+    /// environment variables, so `env` sets them.
+    #[cfg(target_os = "linux")]
+    fn entry_contents(command: &[String], environment: &[(String, String)]) -> String {
+        let mut exec: Vec<String> = Vec::new();
+        if !environment.is_empty() {
+            exec.push("env".to_string());
+            exec.extend(
+                environment
+                    .iter()
+                    .map(|(name, value)| desktop_quote(&format!("{}={}", name, value))),
+            );
+        }
+        exec.extend(command.iter().map(|arg| desktop_quote(arg)));
+        format!(
+            "[Desktop Entry]\nType=Application\nName=snipt\nComment=Text snippet expansion\nExec={}\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
+            exec.join(" ")
+        )
+    }
+
+    pub(super) fn register(command: &[String], environment: &[(String, String)]) -> Result<String> {
+        let path = entry_path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| storage_error("create the autostart directory", dir, e))?;
+        }
+        fs::write(&path, entry_contents(command, environment))
+            .map_err(|e| storage_error("write the autostart entry", &path, e))?;
+        Ok(path.display().to_string())
+    }
+
+    pub(super) fn unregister() -> Result<Option<String>> {
+        let path = entry_path()?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        fs::remove_file(&path)
+            .map_err(|e| storage_error("remove the autostart entry", &path, e))?;
+        Ok(Some(path.display().to_string()))
+    }
+
+    pub(super) fn registered() -> Result<Option<String>> {
+        let path = entry_path()?;
+        Ok(path.exists().then(|| path.display().to_string()))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[cfg(target_os = "linux")]
+        #[test]
+        fn test_desktop_quote() {
+            assert_eq!(desktop_quote("start"), "\"start\"");
+            assert_eq!(
+                desktop_quote("/opt/my apps/snipt"),
+                "\"/opt/my apps/snipt\""
+            );
+            // Shell characters are escaped twice, once for the Exec quoting
+            // and once for the string value, and field codes are doubled
+            assert_eq!(desktop_quote(r#"a"b`c$d\e"#), r#""a\\"b\\`c\\$d\\\\e""#);
+            assert_eq!(desktop_quote("a\nb"), r#""a\nb""#);
+            assert_eq!(desktop_quote("100%"), "\"100%%\"");
+        }
+
+        #[cfg(target_os = "linux")]
+        #[test]
+        fn test_entry_sets_config_dir() {
+            let command = vec!["/usr/bin/snipt".to_string(), "start".to_string()];
+            let environment = vec![(
+                CONFIG_DIR_ENV.to_string(),
+                "/home/a/.snipt-work".to_string(),
+            )];
+            let contents = entry_contents(&command, &environment);
+            assert!(contents.contains(&format!(
+                "Exec=env \"{}=/home/a/.snipt-work\" \"/usr/bin/snipt\" \"start\"\n",
+                CONFIG_DIR_ENV
+            )));
+            assert!(entry_contents(&command, &[]).contains("Exec=\"/usr/bin/snipt\" \"start\"\n"));
+        }
+    }
+}
+
+/// On Windows the command goes into the current user's `Run` key
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+    use std::process::Command;
+
+    const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+    fn location() -> String {
+        format!(r"{}\{}", RUN_KEY, entry_name())
+    }
+
+    fn reg(args: &[&str]) -> Result<bool> {
+        let output = Command::new("reg").args(args).output()?;
+        Ok(output.status.success())
+    }
+
+    pub(super) fn register(command: &[String], environment: &[(String, String)]) -> Result<String> {
+        // A Run value is only a command line, with nowhere to set variables,
+        // and without them the daemon would use a different directory
+        if let Some((name, _)) = environment.first() {
+            return Err(SniptError::Other(format!(
+                "Starting snipt at login with {} set isn't supported on Windows",
+                name
+            )));
+        }
+        let quoted: Vec<String> = command
+            .iter()
+            .map(|arg| {
+                if arg.contains(' ') {
+                    format!("\"{}\"", arg)
+                } else {
+                    arg.clone()
+                }
+            })
+            .collect();
+        let name = entry_name();
+        let value = quoted.join(" ");
+        let args = [
+            "add", RUN_KEY, "/v", &name, "/t", "REG_SZ", "/d", &value, "/f",
+        ];
+        if !reg(&args)? {
+            return Err(SniptError::Other(format!(
+                "Failed to add {} to the registry",
+                location()
+            )));
+        }
+        Ok(location())
+    }
+
+    pub(super) fn unregister() -> Result<Option<String>> {
+        if registered()?.is_none() {
+            return Ok(None);
+        }
+        if !reg(&["delete", RUN_KEY, "/v", &entry_name(), "/f"])? {
+            return Err(SniptError::Other(format!(
+                "Failed to remove {} from the registry",
+                location()
+            )));
+        }
+        Ok(Some(location()))
+    }
+
+    pub(super) fn registered() -> Result<Option<String>> {
+        let found = reg(&["query", RUN_KEY, "/v", &entry_name()])?;
+        Ok(found.then(location))
+    }
+}
+
+/// Platforms without a known way to start programs at login
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+mod platform {
+    use super::*;
+
+    fn unsupported() -> SniptError {
+        SniptError::Other("Starting snipt at login isn't supported on this platform".to_string())
+    }
+
+    pub(super) fn register(
+        _command: &[String],
+        _environment: &[(String, String)],
+    ) -> Result<String> {
+        Err(unsupported())
+    }
+
+    pub(super) fn unregister() -> Result<Option<String>> {
+        Err(unsupported())
+    }
+
+    pub(super) fn registered() -> Result<Option<String>> {
+        Err(unsupported())
+    }
+}
@@ -0,0 +1,337 @@
+//! Control socket the running daemon listens on, so CLI commands and other
+//! integrations can talk to it without starting a new process each time.
+//!
+//! The protocol is line-based JSON over a Unix domain socket in the config
+//! directory: each request is one object on its own line and gets one
+//! response object back.
+//!
+//! ```text
+//! {"command":"status"}                 -> {"ok":true,"pid":4242,"paused":false,"snippets":37}
+//! {"command":"pause"}                  -> {"ok":true,"paused":true}
+//! {"command":"resume"}                 -> {"ok":true,"paused":false}
+//! {"command":"reload"}                 -> {"ok":true}
+//! {"command":"expand","shortcut":"sig"} -> {"ok":true}
+//! ```
+//!
+//! `expand` pastes the snippet into the focused application. Failures come
+//! back as `{"ok":false,"error":"..."}`. Only the user running the daemon can
+//! connect. The socket is only available on Unix; elsewhere the CLI falls back
+//! to its usual behavior.
+
+use serde::{Deserialize, Serialize};
+use snipt_core::storage::{find_snippet, shortcut_not_found};
+use snipt_core::{Result, SnippetEntry, SniptError};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set by a `reload` request and picked up by the daemon's monitor loop
+static RELOAD_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// A command sent to the daemon
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum ControlRequest {
+    Status,
+    Pause,
+    Resume,
+    Reload,
+    Expand { shortcut: String },
+}
+
+/// The daemon's answer to a request
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ControlResponse {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paused: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippets: Option<usize>,
+}
+
+impl ControlResponse {
+    #[cfg_attr(not(unix), allow(dead_code))]
+    fn success() -> Self {
+        Self {
+            ok: true,
+            ..Self::default()
+        }
+    }
+
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            error: Some(message.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Turn a failed response into an error
+    pub fn into_result(self) -> Result<Self> {
+        if self.ok {
+            Ok(self)
+        } else {
+            Err(SniptError::Other(self.error.unwrap_or_else(|| {
+                "The daemon rejected the request".to_string()
+            })))
+        }
+    }
+}
+
+/// Find the snippet to paste for an `expand` request
+pub(crate) fn enabled_snippet(snippets: &[SnippetEntry], shortcut: &str) -> Result<SnippetEntry> {
+    match find_snippet(snippets, shortcut) {
+        Some(entry) if entry.enabled => Ok(entry.clone()),
+        Some(_) => Err(SniptError::Other(format!(
+            "Snippet '{}' is disabled",
+            shortcut
+        ))),
+        None => Err(shortcut_not_found(snippets, shortcut)),
+    }
+}
+
+/// Check, and clear, whether a `reload` was requested
+pub(crate) fn take_reload_request() -> bool {
+    RELOAD_REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+/// Parse one request line and answer it with `handle`
+#[cfg_attr(not(unix), allow(dead_code))]
+fn respond(line: &str, handle: impl Fn(ControlRequest) -> ControlResponse) -> ControlResponse {
+    match serde_json::from_str::<ControlRequest>(line) {
+        Ok(request) => handle(request),
+        Err(e) => ControlResponse::error(format!("Invalid request: {}", e)),
+    }
+}
+
+#[cfg(unix)]
+pub(crate) use unix::{remove_control_socket, send_request, start_control_socket, ControlState};
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+    use log::{debug, info, warn};
+    use snipt_core::config::{get_control_socket_path, set_paused};
+    use snipt_core::expansion::paste_snippet;
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::process;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    /// Daemon state the control socket reads and changes
+    #[derive(Clone)]
+    pub(crate) struct ControlState {
+        pub snippets: Arc<Mutex<Vec<SnippetEntry>>>,
+        pub paused: Arc<Mutex<bool>>,
+    }
+
+    /// Listen for control requests in the background. A socket left behind by
+    /// a daemon that didn't shut down cleanly is replaced.
+    pub(crate) fn start_control_socket(state: ControlState) {
+        let path = get_control_socket_path();
+        let _ = fs::remove_file(&path);
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!("Control socket unavailable at {}: {}", path.display(), e);
+                return;
+            }
+        };
+        // The socket is created with the umask's permissions; other users
+        // mustn't be able to paste snippets or pause expansion
+        if let Err(e) = fs::set_permissions(&path, fs::Permissions::from_mode(0o600)) {
+            warn!(
+                "Control socket disabled, its permissions can't be restricted: {}",
+                e
+            );
+            let _ = fs::remove_file(&path);
+            return;
+        }
+        info!("Listening for control commands on {}", path.display());
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = state.clone();
+                thread::spawn(move || {
+                    if let Err(e) = serve(stream, &state) {
+                        debug!("Control connection ended: {}", e);
+                    }
+                });
+            }
+        });
+    }
+
+    /// Remove the socket when the daemon stops
+    pub(crate) fn remove_control_socket() {
+        let _ = fs::remove_file(get_control_socket_path());
+    }
+
+    pub(super) fn serve(stream: UnixStream, state: &ControlState) -> std::io::Result<()> {
+        let mut writer = stream.try_clone()?;
+        for line in BufReader::new(stream).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = respond(&line, |request| handle(request, state));
+            let mut out = serde_json::to_string(&response)?;
+            out.push('\n');
+            writer.write_all(out.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn handle(request: ControlRequest, state: &ControlState) -> ControlResponse {
+        match request {
+            ControlRequest::Status => ControlResponse {
+                ok: true,
+                pid: Some(process::id()),
+                paused: Some(*state.paused.lock().unwrap()),
+                snippets: Some(state.snippets.lock().unwrap().len()),
+                ..ControlResponse::default()
+            },
+            ControlRequest::Pause | ControlRequest::Resume => {
+                let paused = request == ControlRequest::Pause;
+                // Keep the pause file in step so the state survives a restart
+                if let Err(e) = set_paused(paused) {
+                    return ControlResponse::error(e.to_string());
+                }
+                *state.paused.lock().unwrap() = paused;
+                info!(
+                    "Expansion {} over the control socket",
+                    if paused { "paused" } else { "resumed" }
+                );
+                ControlResponse {
+                    paused: Some(paused),
+                    ..ControlResponse::success()
+                }
+            }
+            ControlRequest::Reload => {
+                RELOAD_REQUESTED.store(true, Ordering::SeqCst);
+                ControlResponse::success()
+            }
+            ControlRequest::Expand { shortcut } => {
+                let entry = enabled_snippet(&state.snippets.lock().unwrap(), &shortcut);
+                match entry.and_then(|entry| paste_snippet(&entry)) {
+                    Ok(()) => ControlResponse::success(),
+                    Err(e) => ControlResponse::error(e.to_string()),
+                }
+            }
+        }
+    }
+
+    /// Send a request to the running daemon. Returns `None` when no daemon is
+    /// listening, so the caller can fall back to working without it.
+    pub(crate) fn send_request(request: &ControlRequest) -> Option<Result<ControlResponse>> {
+        let stream = UnixStream::connect(get_control_socket_path()).ok()?;
+        Some(exchange(stream, request))
+    }
+
+    pub(super) fn exchange(
+        stream: UnixStream,
+        request: &ControlRequest,
+    ) -> Result<ControlResponse> {
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+        let mut writer = stream.try_clone()?;
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        writer.write_all(line.as_bytes())?;
+
+        let mut answer = String::new();
+        BufReader::new(stream).read_line(&mut answer)?;
+        Ok(serde_json::from_str(&answer)?)
+    }
+}
+
+/// Without Unix sockets no daemon is ever reachable
+#[cfg(not(unix))]
+pub(crate) fn send_request(_request: &ControlRequest) -> Option<Result<ControlResponse>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol() {
+        let expand = ControlRequest::Expand {
+            shortcut: "sig".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_string(&expand).unwrap(),
+            r#"{"command":"expand","shortcut":"sig"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&ControlRequest::Status).unwrap(),
+            r#"{"command":"status"}"#
+        );
+
+        // Fields without a value are left out of responses
+        let response = ControlResponse {
+            paused: Some(true),
+            ..ControlResponse::success()
+        };
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"ok":true,"paused":true}"#
+        );
+        assert!(response.into_result().is_ok());
+
+        let failed: ControlResponse =
+            serde_json::from_str(r#"{"ok":false,"error":"Snippet 'x' is disabled"}"#).unwrap();
+        assert!(matches!(
+            failed.into_result(),
+            Err(SniptError::Other(message)) if message == "Snippet 'x' is disabled"
+        ));
+    }
+
+    #[test]
+    fn test_respond() {
+        let handle = |request: ControlRequest| match request {
+            ControlRequest::Expand { shortcut } => ControlResponse::error(shortcut),
+            _ => ControlResponse::success(),
+        };
+        assert!(respond(r#"{"command":"reload"}"#, handle).ok);
+        let response = respond(r#"{"command":"expand","shortcut":"sig"}"#, handle);
+        assert_eq!(response.error.as_deref(), Some("sig"));
+
+        // Unknown commands and broken JSON are answered, not dropped
+        for line in [r#"{"command":"explode"}"#, "not json", "{}"] {
+            let response = respond(line, handle);
+            assert!(!response.ok);
+            assert!(response.error.unwrap().starts_with("Invalid request"));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_socket_round_trip() {
+        use std::os::unix::net::UnixStream;
+        use std::sync::{Arc, Mutex};
+
+        let state = ControlState {
+            snippets: Arc::new(Mutex::new(vec![SnippetEntry::new(
+                "sig".to_string(),
+                "Regards".to_string(),
+            )])),
+            paused: Arc::new(Mutex::new(true)),
+        };
+        let (client, server) = UnixStream::pair().unwrap();
+        let daemon = std::thread::spawn(move || unix::serve(server, &state));
+
+        let response = unix::exchange(client, &ControlRequest::Status).unwrap();
+        assert_eq!(response.pid, Some(std::process::id()));
+        assert_eq!(response.paused, Some(true));
+        assert_eq!(response.snippets, Some(1));
+
+        // The daemon side ends when the client hangs up
+        daemon.join().unwrap().unwrap();
+    }
+}
@@ -1,21 +1,58 @@
-use crate::keyboard_listener::start_keyboard_listener;
+use crate::control::{self, enabled_snippet, take_reload_request, ControlRequest};
+use crate::heartbeat::{clear_heartbeat, write_heartbeat};
+use crate::keyboard_listener::{
+    buffer_limit_for, completed_expansions, expansion_in_progress, set_watch_only,
+    start_keyboard_listener,
+};
+use crate::notifications::notify_error;
 use crate::permissions::check_and_request_permissions;
 use crate::process::verify_process_running;
-use snipt_core::config::{db_file_exists, ensure_config_dir, get_db_file_path, get_pid_file_path};
-use snipt_core::{get_config_dir, is_daemon_running, load_snippets, Result, SniptError};
+use log::{debug, error, info, warn};
+use snipt_core::config::{
+    active_profile, db_file_exists, ensure_config_dir, get_daemon_log_path, get_db_file_path,
+    get_pid_file_path, is_paused, set_paused, LOG_ENV, PASSPHRASE_ENV,
+};
+use snipt_core::crypto::{cached_passphrase, encryption_in_use};
+use snipt_core::expansion::paste_snippet;
+use snipt_core::keyboard::Hotkeys;
+use snipt_core::layout::KeyboardLayout;
+use snipt_core::metrics::{clear_daemon_metrics, write_daemon_metrics, DaemonMetrics};
+use snipt_core::quiet_hours::current_quiet_window;
+use snipt_core::settings::{get_config_file_path, validate_config};
+use snipt_core::{
+    apply_config, backup_database, get_config_dir, is_daemon_running, load_config, load_snippets,
+    Result, SniptConfig, SniptError,
+};
 use snipt_server::server::http_server::stop_api_server;
-use snipt_server::server::utils::{get_api_server_port, port_is_available, save_api_port};
+use snipt_server::server::utils::{
+    get_api_server_port, port_is_available, remove_api_port, save_api_port,
+};
+use std::env;
 use std::fs::{self, File};
 use std::io::Write;
-use std::process;
+use std::path::Path;
+use std::process::{self, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// Start the daemon process
 pub fn start_daemon(api_port: u16) -> Result<()> {
     // Check for permissions first -
     check_and_request_permissions()?;
+
+    // The daemon won't run on an invalid config, so say why here
+    let config = load_config()?;
+    validate_config(&config)?;
+    KeyboardLayout::from_config(&config)?;
+
+    // The detached daemon and API server can't ask for the passphrase, so
+    // check it now; `spawn_detached` hands it to them over stdin
+    if encryption_in_use() {
+        load_snippets()?;
+    }
+
     // Check if daemon is already running
     if let Some(pid) = is_daemon_running()? {
         if verify_process_running(pid) {
@@ -43,22 +80,9 @@ pub fn start_daemon(api_port: u16) -> Result<()> {
 
         #[cfg(unix)]
         {
-            use std::process::Command;
-
-            // Create a new detached process for the daemon
-            // Get the path to the current executable
-            let current_exe = std::env::current_exe()?;
-
-            // Start the daemon process detached with nohup
-            let daemon_log_file = format!("{}/daemon_log.txt", get_config_dir().to_string_lossy());
-
-            let cmd = format!(
-                "nohup \"{}\" daemon-worker > \"{}\" 2>&1 &",
-                current_exe.to_string_lossy(),
-                daemon_log_file
-            );
-
-            Command::new("sh").arg("-c").arg(&cmd).status()?;
+            // Start the daemon process detached
+            let daemon_log_file = get_daemon_log_path().to_string_lossy().to_string();
+            spawn_detached(&["daemon-worker"], &get_daemon_log_path())?;
 
             // Wait for the daemon to start and create its PID file
             for _ in 0..20 {
@@ -88,21 +112,9 @@ pub fn start_daemon(api_port: u16) -> Result<()> {
 
         #[cfg(windows)]
         {
-            use std::process::Command;
-
-            // Get the path to the current executable
-            let current_exe = std::env::current_exe()?;
-
             // Start the daemon process detached
-            let daemon_log_file = format!("{}\\daemon_log.txt", get_config_dir().to_string_lossy());
-
-            let cmd = format!(
-                "START /B \"snipt Daemon\" \"{}\" daemon-worker > \"{}\" 2>&1",
-                current_exe.to_string_lossy(),
-                daemon_log_file
-            );
-
-            Command::new("cmd").arg("/C").arg(&cmd).status()?;
+            let daemon_log_file = get_daemon_log_path().to_string_lossy().to_string();
+            spawn_detached(&["daemon-worker"], &get_daemon_log_path())?;
 
             // Wait for the daemon to start and create its PID file
             for _ in 0..20 {
@@ -148,31 +160,18 @@ pub fn start_daemon(api_port: u16) -> Result<()> {
         current_port += 1;
     }
 
-    // Save the API port info
-    if let Err(e) = save_api_port(current_port) {
-        println!("Warning: Failed to save API port information: {}", e);
-    }
-
     // Start the API server in a separate process
-    let current_exe = std::env::current_exe()?;
+    let port_arg = current_port.to_string();
 
     #[cfg(unix)]
     {
-        use std::process::Command;
         let log_file = format!("{}/api_server_log.txt", get_config_dir().to_string_lossy());
-
-        let cmd = format!(
-            "nohup \"{}\" serve --port {} > \"{}\" 2>&1 &",
-            current_exe.to_string_lossy(),
-            current_port,
-            log_file
-        );
-
-        Command::new("sh").arg("-c").arg(&cmd).status()?;
+        spawn_detached(&["serve", "--port", &port_arg], Path::new(&log_file))?;
 
         // Verify the server started by checking if the port is no longer available
         thread::sleep(Duration::from_secs(2));
         if !port_is_available(current_port) {
+            record_api_port(current_port);
             println!("API server started on port {}.", current_port);
             println!(
                 "You can access the server at: http://localhost:{}",
@@ -189,21 +188,13 @@ pub fn start_daemon(api_port: u16) -> Result<()> {
 
     #[cfg(windows)]
     {
-        use std::process::Command;
         let log_file = format!("{}\\api_server_log.txt", get_config_dir().to_string_lossy());
-
-        let cmd = format!(
-            "START /B \"snipt API Server\" \"{}\" serve --port {} > \"{}\" 2>&1",
-            current_exe.to_string_lossy(),
-            current_port,
-            log_file
-        );
-
-        Command::new("cmd").arg("/C").arg(&cmd).status()?;
+        spawn_detached(&["serve", "--port", &port_arg], Path::new(&log_file))?;
 
         // Verify the server started
         thread::sleep(Duration::from_secs(2));
         if !port_is_available(current_port) {
+            record_api_port(current_port);
             println!("API server started on port {}.", current_port);
             println!(
                 "You can access the server at: http://localhost:{}",
@@ -226,6 +217,66 @@ pub fn start_daemon(api_port: u16) -> Result<()> {
     }
 }
 
+/// Start the snipt binary with `args` in the background, writing its output
+/// to `log_path`. The passphrase of an encrypted database goes to the child's
+/// stdin: in its environment any process of the same user could read it for
+/// as long as the daemon runs.
+#[cfg(any(unix, windows))]
+fn spawn_detached(args: &[&str], log_path: &Path) -> Result<()> {
+    let log = File::create(log_path)?;
+    let passphrase = encryption_in_use().then(cached_passphrase).flatten();
+
+    // nohup keeps the daemon alive when the terminal that started it closes,
+    // and its own process group keeps Ctrl+C in that terminal away from it
+    #[cfg(unix)]
+    let mut command = {
+        use std::os::unix::process::CommandExt;
+        let mut command = Command::new("nohup");
+        command.arg(env::current_exe()?).process_group(0);
+        command
+    };
+    #[cfg(windows)]
+    let mut command = {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        let mut command = Command::new(env::current_exe()?);
+        command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+        command
+    };
+
+    command.args(args);
+    if passphrase.is_some() {
+        command.arg("--passphrase-stdin");
+    }
+    let mut child = command
+        .env_remove(PASSPHRASE_ENV)
+        .stdin(Stdio::piped())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .spawn()?;
+
+    // Dropping stdin closes the pipe, so the child never waits on it
+    if let (Some(mut stdin), Some(passphrase)) = (child.stdin.take(), passphrase) {
+        writeln!(stdin, "{}", passphrase)?;
+    }
+    Ok(())
+}
+
+/// Persist the port the API server bound to so later commands can find it
+fn record_api_port(port: u16) {
+    if let Err(e) = save_api_port(port) {
+        println!("Warning: Failed to save API port information: {}", e);
+    }
+}
+
+/// How long `stop_daemon` lets the daemon shut down before killing it
+#[cfg(unix)]
+const GRACEFUL_STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a stopping daemon waits for the expansion being typed to finish
+const EXPANSION_FINISH_TIMEOUT: Duration = Duration::from_secs(3);
+
 /// Stop the daemon if it's running
 pub fn stop_daemon() -> Result<()> {
     let pid_file = get_pid_file_path();
@@ -256,8 +307,9 @@ pub fn stop_daemon() -> Result<()> {
 
     println!("Attempting to stop daemon with PID {}...", pid);
 
-    // First try to stop the API server
+    // First try to stop the API server, then make sure no stale port is left behind
     let _ = stop_api_server();
+    let _ = remove_api_port();
 
     // Check if the process is actually running before attempting to kill it
     if !verify_process_running(pid) {
@@ -283,9 +335,12 @@ pub fn stop_daemon() -> Result<()> {
             }
         }
 
-        // If SIGTERM didn't work, try SIGKILL after a short delay
+        // The daemon finishes a running expansion and cleans up on SIGTERM;
+        // only kill it outright when that takes too long
         if !success || verify_process_running(pid) {
-            thread::sleep(Duration::from_millis(500));
+            if success {
+                wait_for_exit(pid, GRACEFUL_STOP_TIMEOUT);
+            }
 
             if verify_process_running(pid) {
                 println!("Daemon didn't terminate gracefully, using force kill...");
@@ -318,8 +373,6 @@ pub fn stop_daemon() -> Result<()> {
 
     #[cfg(windows)]
     {
-        use std::process::Command;
-
         let mut success = false;
 
         // On Windows, first try normal termination
@@ -365,25 +418,178 @@ pub fn stop_daemon() -> Result<()> {
     // If we get here, all attempts failed
     println!("WARNING: Failed to stop daemon process. PID file will be removed anyway.");
     let _ = fs::remove_file(&pid_file);
+    clear_heartbeat();
+
+    Ok(())
+}
+
+/// Wait up to `timeout` for process `pid` to exit
+#[cfg(unix)]
+fn wait_for_exit(pid: u32, timeout: Duration) {
+    let start = Instant::now();
+    while verify_process_running(pid) && start.elapsed() < timeout {
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// How long `restart_daemon` waits for the daemon to go down and come back up
+const RESTART_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Stop the daemon (if running) and start it again on the given API port
+pub fn restart_daemon(api_port: u16) -> Result<()> {
+    if is_daemon_running()?.is_some() {
+        stop_daemon()?;
+    } else {
+        println!("Daemon is not running, starting it...");
+    }
+
+    // Wait for the old daemon to release its PID file before starting a new one
+    if !wait_for_daemon_state(false, RESTART_TIMEOUT)? {
+        return Err(SniptError::Other(format!(
+            "Daemon did not stop within {} seconds. Run 'snipt stop' and try again",
+            RESTART_TIMEOUT.as_secs()
+        )));
+    }
 
+    start_daemon(api_port).map_err(|e| {
+        SniptError::Other(format!(
+            "Daemon was stopped but failed to start again: {}. Run 'snipt start' to retry",
+            e
+        ))
+    })?;
+
+    if !wait_for_daemon_state(true, RESTART_TIMEOUT)? {
+        return Err(SniptError::Other(format!(
+            "Daemon was stopped but did not come back up within {} seconds. Check logs at {}",
+            RESTART_TIMEOUT.as_secs(),
+            get_daemon_log_path().display()
+        )));
+    }
+
+    println!("Daemon restarted successfully.");
     Ok(())
 }
 
+/// Poll until the daemon is (or is not) running, returning false on timeout
+fn wait_for_daemon_state(running: bool, timeout: Duration) -> Result<bool> {
+    let start = Instant::now();
+    loop {
+        let is_running = match is_daemon_running()? {
+            Some(pid) => verify_process_running(pid),
+            None => false,
+        };
+
+        if is_running == running {
+            return Ok(true);
+        }
+
+        if start.elapsed() >= timeout {
+            return Ok(false);
+        }
+
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Temporarily stop expanding snippets without stopping the daemon
+pub fn pause_daemon() -> Result<()> {
+    // The daemon applies it right away; otherwise the pause file is enough
+    match control::send_request(&ControlRequest::Pause) {
+        Some(response) => {
+            response?.into_result()?;
+        }
+        None => set_paused(true)?,
+    }
+    println!("Snippet expansion paused. Run 'snipt resume' to turn it back on.");
+    if is_daemon_running()?.is_none() {
+        println!("Note: the daemon is not running; it will start paused.");
+    }
+    Ok(())
+}
+
+/// Turn snippet expansion back on after `pause_daemon`
+pub fn resume_daemon() -> Result<()> {
+    match control::send_request(&ControlRequest::Resume) {
+        Some(response) => {
+            response?.into_result()?;
+        }
+        None => set_paused(false)?,
+    }
+    println!("Snippet expansion resumed.");
+    if is_daemon_running()?.is_none() {
+        println!("Note: the daemon is not running. Start it with 'snipt start'.");
+    }
+    Ok(())
+}
+
+/// Make the running daemon reload its config and snippets now
+pub fn reload_daemon() -> Result<()> {
+    match control::send_request(&ControlRequest::Reload) {
+        Some(response) => {
+            response?.into_result()?;
+            println!("The daemon is reloading its config and snippets.");
+            Ok(())
+        }
+        None if is_daemon_running()?.is_some() => Err(SniptError::Other(
+            "The daemon isn't listening for commands. It picks up changes on its own within a second, or run 'snipt restart'".to_string(),
+        )),
+        None => Err(SniptError::DaemonNotRunning),
+    }
+}
+
+/// Paste a snippet into the focused application. The running daemon does it
+/// when it can; otherwise it's done from this process.
+pub fn paste_snippet_now(shortcut: &str) -> Result<()> {
+    let request = ControlRequest::Expand {
+        shortcut: shortcut.to_string(),
+    };
+    if let Some(response) = control::send_request(&request) {
+        response?.into_result()?;
+        return Ok(());
+    }
+
+    paste_snippet(&enabled_snippet(&load_snippets()?, shortcut)?)
+}
+
 /// Check daemon status
 pub fn daemon_status() -> Result<()> {
+    println!(
+        "Profile: {}",
+        active_profile().unwrap_or_else(|| "default".to_string())
+    );
+
+    // Ask the daemon directly when it's listening
+    if let Some(Ok(status)) = control::send_request(&ControlRequest::Status) {
+        let pid = status.pid.unwrap_or_default();
+        if status.paused == Some(true) {
+            println!("snipt daemon is running (paused) with PID {}", pid);
+            println!("Run 'snipt resume' to turn expansion back on");
+        } else {
+            println!("snipt daemon is running with PID {}", pid);
+        }
+        print_quiet_hours();
+        if let Some(count) = status.snippets {
+            println!("Snippets loaded: {}", count);
+        }
+        print_api_server_port();
+        return Ok(());
+    }
+
     match is_daemon_running()? {
         Some(pid) => {
             // Verify the process is actually running
             let process_exists = verify_process_running(pid);
 
             if process_exists {
-                println!("snipt daemon is running with PID {}", pid);
-
-                // Check if we can find the API port information
-                if let Ok(port) = get_api_server_port() {
-                    println!("API server is running on port {}", port);
-                    println!("UI available at: http://localhost:{}", port);
+                if is_paused() {
+                    println!("snipt daemon is running (paused) with PID {}", pid);
+                    println!("Run 'snipt resume' to turn expansion back on");
+                } else {
+                    println!("snipt daemon is running with PID {}", pid);
                 }
+                print_quiet_hours();
+
+                print_api_server_port();
 
                 Ok(())
             } else {
@@ -401,6 +607,24 @@ pub fn daemon_status() -> Result<()> {
     }
 }
 
+/// Mention quiet hours while they keep the daemon from expanding
+fn print_quiet_hours() {
+    if let Some(window) = current_quiet_window() {
+        println!("Quiet hours until {}: nothing is expanded", window.end);
+    }
+}
+
+/// Report the port recorded when the API server bound
+fn print_api_server_port() {
+    match get_api_server_port() {
+        Ok(port) => {
+            println!("API server is running on port {}", port);
+            println!("UI available at: http://localhost:{}", port);
+        }
+        Err(_) => println!("API server port information not found"),
+    }
+}
+
 /// The daemon worker process (run by the daemon itself)
 #[cfg(unix)]
 pub fn daemon_worker() -> Result<()> {
@@ -413,8 +637,66 @@ pub fn daemon_worker() -> Result<()> {
     run_daemon_worker()
 }
 
+/// How the keyboard listener is run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WorkerMode {
+    /// The background daemon
+    Daemon,
+    /// In the foreground until the first expansion
+    Once,
+    /// In the foreground, printing expansions instead of performing them
+    Watch,
+}
+
 /// The actual daemon worker process
 pub fn run_daemon_worker() -> Result<()> {
+    run_worker(WorkerMode::Daemon)
+}
+
+/// Listen in the foreground until one snippet has been expanded, then exit.
+/// Meant to be bound to a key instead of keeping the daemon running.
+pub fn run_once() -> Result<()> {
+    check_and_request_permissions()?;
+    if let Some(pid) = is_daemon_running()? {
+        if verify_process_running(pid) {
+            return Err(SniptError::Other(format!(
+                "The daemon is already running with PID {} and would expand snippets twice. Run 'snipt stop' first",
+                pid
+            )));
+        }
+    }
+
+    run_worker(WorkerMode::Once)
+}
+
+/// Listen in the foreground and print each shortcut that would expand,
+/// without deleting or typing anything, until Ctrl+C
+pub fn watch_expansions() -> Result<()> {
+    check_and_request_permissions()?;
+    if let Some(pid) = is_daemon_running()? {
+        if verify_process_running(pid) {
+            println!(
+                "Note: the daemon (PID {}) is running and still expands snippets.",
+                pid
+            );
+        }
+    }
+    println!("Watching for shortcuts; nothing is expanded. Press Ctrl+C to stop.\n");
+
+    set_watch_only();
+    run_worker(WorkerMode::Watch)
+}
+
+/// Listen for shortcuts and expand them, returning after the first
+/// completed expansion in `WorkerMode::Once`
+fn run_worker(mode: WorkerMode) -> Result<()> {
+    let once = mode == WorkerMode::Once;
+    let daemon = mode == WorkerMode::Daemon;
+    init_logging(if mode == WorkerMode::Watch {
+        "warn"
+    } else {
+        "info"
+    });
     // Load snippets
     let db_path = get_db_file_path();
     if !db_path.exists() {
@@ -425,6 +707,16 @@ pub fn run_daemon_worker() -> Result<()> {
 
     // Load the snipt database
     let snippets = Arc::new(Mutex::new(load_snippets()?));
+    info!(
+        "Daemon started with {} snippets{}",
+        snippets.lock().unwrap().len(),
+        if once { " (one-shot)" } else { "" }
+    );
+
+    // Size the keystroke buffer so the longest shortcut always fits
+    let buffer_limit = Arc::new(AtomicUsize::new(buffer_limit_for(
+        &snippets.lock().unwrap(),
+    )));
 
     // Track the last modified time of the database file
     let last_modified = Arc::new(Mutex::new(fs::metadata(&db_path)?.modified().ok()));
@@ -433,8 +725,58 @@ pub fn run_daemon_worker() -> Result<()> {
     let running = Arc::new(Mutex::new(true));
     let running_clone = Arc::clone(&running);
 
+    // Expansion can be paused without stopping the daemon
+    let paused = Arc::new(Mutex::new(is_paused()));
+
+    // Set by SIGTERM (`snipt stop`) or SIGINT to shut down cleanly
+    let shutdown = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    if !once {
+        for signal in [signal_hook::consts::SIGTERM, signal_hook::consts::SIGINT] {
+            if let Err(e) = signal_hook::flag::register(signal, Arc::clone(&shutdown)) {
+                warn!("Failed to install the shutdown handler: {}", e);
+            }
+        }
+    }
+
+    // Apply the user configuration (trigger characters and keyboard layout).
+    // An invalid one stops the worker rather than running on the defaults.
+    let config = Arc::new(Mutex::new(SniptConfig::default()));
+    let layout = Arc::new(Mutex::new(KeyboardLayout::default()));
+    let hotkeys = Arc::new(Mutex::new(Hotkeys::default()));
+    reload_config(&config, &layout, &hotkeys)?;
+    let config_path = get_config_file_path();
+    let mut config_modified = fs::metadata(&config_path).and_then(|m| m.modified()).ok();
+    let mut last_backup = Instant::now();
+
+    // Counters served on the API server's `/metrics`
+    let mut metrics = DaemonMetrics {
+        started_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or(0),
+        ..DaemonMetrics::default()
+    };
+
+    // Take commands from the CLI and other tools while running
+    #[cfg(unix)]
+    if daemon {
+        control::start_control_socket(control::ControlState {
+            snippets: Arc::clone(&snippets),
+            paused: Arc::clone(&paused),
+        });
+    }
+
     // Start keyboard event listener in a separate thread
-    let keyboard_thread = start_keyboard_listener(Arc::clone(&snippets), running_clone);
+    let keyboard_thread = start_keyboard_listener(
+        Arc::clone(&snippets),
+        running_clone,
+        Arc::clone(&paused),
+        Arc::clone(&layout),
+        Arc::clone(&buffer_limit),
+        Arc::clone(&config),
+        Arc::clone(&hotkeys),
+    );
 
     // Clone references for the monitoring thread
     let db_path_clone = db_path.clone();
@@ -443,10 +785,32 @@ pub fn run_daemon_worker() -> Result<()> {
 
     // Monitor for database changes and termination signals
     let check_interval = Duration::from_secs(1);
+    let mut listener_stopped = false;
     while *running.lock().unwrap() {
         // Add a small sleep to reduce CPU usage
         thread::sleep(Duration::from_millis(100));
 
+        if shutdown.load(Ordering::SeqCst) {
+            info!("Received a stop signal, shutting down");
+            *running.lock().unwrap() = false;
+            break;
+        }
+
+        // In one-shot mode the job is done after the first expansion. The
+        // keyboard thread can't be stopped, so it ends with the process.
+        if once && completed_expansions() > 0 {
+            return Ok(());
+        }
+
+        // Without the keyboard listener there is nothing left to do
+        if keyboard_thread.is_finished() {
+            listener_stopped = true;
+            break;
+        }
+
+        // Follow `snipt pause` / `snipt resume`
+        *paused.lock().unwrap() = is_paused();
+
         // Check if it's time to check for file changes
         static mut LAST_CHECK: Option<std::time::Instant> = None;
         let should_check = unsafe {
@@ -462,6 +826,44 @@ pub fn run_daemon_worker() -> Result<()> {
         };
 
         if should_check {
+            // Let `snipt daemon-health` know the listener is still alive
+            if daemon {
+                write_heartbeat();
+                metrics.expansions = completed_expansions() as u64;
+                if let Err(e) = write_daemon_metrics(&metrics) {
+                    debug!("Failed to write metrics: {}", e);
+                }
+            }
+
+            // `snipt reload` asks for both the config and the snippets
+            let reload_requested = take_reload_request();
+            if reload_requested {
+                info!("Reload requested");
+            }
+
+            // Pick up config changes (e.g. from `snipt config set`) without a restart
+            let current_config_modified =
+                fs::metadata(&config_path).and_then(|m| m.modified()).ok();
+            if reload_requested || current_config_modified != config_modified {
+                config_modified = current_config_modified;
+                if let Err(e) = reload_config(&config, &layout, &hotkeys) {
+                    warn!("{}. Keeping the previous configuration.", e);
+                }
+                metrics.reloads += 1;
+            }
+
+            // Take a scheduled backup
+            let backup_interval = config.lock().unwrap().backup_interval_hours;
+            if backup_interval > 0
+                && last_backup.elapsed() >= Duration::from_secs(backup_interval * 3600)
+            {
+                last_backup = Instant::now();
+                match backup_database() {
+                    Ok(path) => info!("Backed up snippets to {}", path.display()),
+                    Err(e) => error!("Scheduled backup failed: {}", e),
+                }
+            }
+
             // Check if the database file has been modified
             if let Ok(metadata) = fs::metadata(&db_path_clone) {
                 if let Ok(current_modified) = metadata.modified() {
@@ -480,9 +882,12 @@ pub fn run_daemon_worker() -> Result<()> {
                         }
                     };
 
-                    if reload_needed {
+                    if reload_needed || reload_requested {
                         // Reload snippets
                         if let Ok(new_snippets) = load_snippets() {
+                            info!("Reloaded {} snippets", new_snippets.len());
+                            metrics.reloads += 1;
+                            buffer_limit.store(buffer_limit_for(&new_snippets), Ordering::Relaxed);
                             let mut snippets_guard = snippets_clone.lock().unwrap();
                             *snippets_guard = new_snippets;
                         }
@@ -492,14 +897,75 @@ pub fn run_daemon_worker() -> Result<()> {
         }
     }
 
+    if daemon {
+        clear_heartbeat();
+        clear_daemon_metrics();
+        #[cfg(unix)]
+        control::remove_control_socket();
+    }
+
+    // The listener thread only ends with the process, so don't wait for it.
+    // An expansion being typed still gets to finish and record its history.
+    if shutdown.load(Ordering::SeqCst) {
+        let start = Instant::now();
+        while expansion_in_progress() && start.elapsed() < EXPANSION_FINISH_TIMEOUT {
+            thread::sleep(Duration::from_millis(50));
+        }
+        if expansion_in_progress() {
+            warn!("Stopped while an expansion was still being typed");
+        }
+        info!("Daemon stopped");
+        return Ok(());
+    }
+
     // Wait for keyboard thread to finish
     if let Err(e) = keyboard_thread.join() {
-        eprintln!("Error joining keyboard thread: {:?}", e);
+        error!("Error joining keyboard thread: {:?}", e);
+        notify_error(
+            "snipt daemon stopped",
+            "The keyboard listener crashed. Run 'snipt start' to start it again.",
+        );
+    }
+
+    if listener_stopped {
+        return Err(SniptError::Other(
+            "Keyboard listener stopped unexpectedly".to_string(),
+        ));
     }
 
     Ok(())
 }
 
+/// Send log output to stderr, which the detached daemon redirects to its log
+/// file. The level comes from `SNIPT_LOG` and defaults to `default_level`.
+fn init_logging(default_level: &str) {
+    let _ = env_logger::Builder::from_env(env_logger::Env::new().filter_or(LOG_ENV, default_level))
+        .try_init();
+}
+
+/// Load the user configuration and apply it to the running daemon. If the
+/// file is invalid, the previous settings stay in place and the error is
+/// returned.
+fn reload_config(
+    config: &Arc<Mutex<SniptConfig>>,
+    layout: &Arc<Mutex<KeyboardLayout>>,
+    hotkeys: &Arc<Mutex<Hotkeys>>,
+) -> Result<()> {
+    let result = load_config().and_then(|new_config| {
+        let new_layout = KeyboardLayout::from_config(&new_config)?;
+        let new_hotkeys = Hotkeys::from_config(&new_config)?;
+        apply_config(&new_config)?;
+        Ok((new_config, new_layout, new_hotkeys))
+    });
+
+    let (new_config, new_layout, new_hotkeys) = result?;
+    *config.lock().unwrap() = new_config;
+    *layout.lock().unwrap() = new_layout;
+    *hotkeys.lock().unwrap() = new_hotkeys;
+    info!("Configuration loaded");
+    Ok(())
+}
+
 /// This function runs as a separate daemon process
 pub fn daemon_worker_entry() -> Result<()> {
     // Create PID file with the current process ID
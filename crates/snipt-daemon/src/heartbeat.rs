@@ -0,0 +1,150 @@
+//! Heartbeat the daemon writes while its keyboard listener works, so that
+//! `snipt daemon-health` can tell a live listener from one that has died or
+//! hung inside a still-running process.
+//!
+//! The heartbeat file holds two lines of Unix timestamps in seconds: when the
+//! heartbeat was written and when the last key event arrived (0 if none yet).
+
+use crate::keyboard_listener::{last_key_event, listener_busy_for};
+use crate::process::verify_process_running;
+use log::debug;
+use snipt_core::config::get_heartbeat_file_path;
+use snipt_core::{is_daemon_running, Result, SniptError};
+use std::fs;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A heartbeat older than this means the listener has stalled
+const STALE_AFTER: Duration = Duration::from_secs(5);
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0)
+}
+
+/// Record that the listener is alive. Nothing is written while the listener
+/// has been stuck on a key event for too long, so the heartbeat goes stale.
+pub(crate) fn write_heartbeat() {
+    if listener_busy_for().is_some_and(|busy| busy >= STALE_AFTER) {
+        return;
+    }
+
+    let last_event = last_key_event().map(unix_secs).unwrap_or(0);
+    let content = format!("{}\n{}\n", unix_secs(SystemTime::now()), last_event);
+    if let Err(e) = fs::write(get_heartbeat_file_path(), content) {
+        debug!("Failed to write heartbeat: {}", e);
+    }
+}
+
+/// Remove the heartbeat when the daemon stops
+pub(crate) fn clear_heartbeat() {
+    let _ = fs::remove_file(get_heartbeat_file_path());
+}
+
+/// Read the heartbeat time and last key event time
+fn read_heartbeat() -> Option<(u64, u64)> {
+    parse_heartbeat(&fs::read_to_string(get_heartbeat_file_path()).ok()?)
+}
+
+/// Parse the heartbeat file. A missing or unreadable heartbeat time gives
+/// None; a missing or unreadable key event time counts as no event yet.
+fn parse_heartbeat(content: &str) -> Option<(u64, u64)> {
+    let mut lines = content.lines().map(|line| line.trim().parse::<u64>());
+    let written = lines.next()?.ok()?;
+    let last_event = lines.next().and_then(|line| line.ok()).unwrap_or(0);
+    Some((written, last_event))
+}
+
+/// Check the heartbeat of the daemon process `pid` at `now`, returning its age
+/// and the time of the last key event
+fn check_heartbeat(heartbeat: Option<(u64, u64)>, now: u64, pid: u32) -> Result<(u64, u64)> {
+    let Some((written, last_event)) = heartbeat else {
+        return Err(SniptError::Other(format!(
+            "Listener stalled: daemon process {} is running but has never reported a heartbeat. Run 'snipt restart'",
+            pid
+        )));
+    };
+
+    let age = now.saturating_sub(written);
+    if age > STALE_AFTER.as_secs() {
+        return Err(SniptError::Other(format!(
+            "Listener stalled: daemon process {} is running but its keyboard listener last reported {}s ago. Run 'snipt restart'",
+            pid, age
+        )));
+    }
+    Ok((age, last_event))
+}
+
+/// Check that the daemon's keyboard listener is alive, not just its process
+pub fn daemon_health() -> Result<()> {
+    let pid = match is_daemon_running()? {
+        Some(pid) if verify_process_running(pid) => pid,
+        _ => return Err(SniptError::DaemonNotRunning),
+    };
+
+    let now = unix_secs(SystemTime::now());
+    let (age, last_event) = check_heartbeat(read_heartbeat(), now, pid)?;
+
+    println!("snipt daemon is healthy (PID {})", pid);
+    println!("Heartbeat:      {}s ago", age);
+    if last_event == 0 {
+        println!("Last key event: none since the daemon started");
+    } else {
+        println!("Last key event: {}s ago", now.saturating_sub(last_event));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_heartbeat() {
+        assert_eq!(
+            parse_heartbeat("1700000000\n1699999990\n"),
+            Some((1700000000, 1699999990))
+        );
+        assert_eq!(
+            parse_heartbeat(" 1700000000 \r\n0\r\n"),
+            Some((1700000000, 0))
+        );
+
+        // Without a readable key event time there was no event yet
+        assert_eq!(parse_heartbeat("1700000000\n"), Some((1700000000, 0)));
+        assert_eq!(parse_heartbeat("1700000000\nsoon\n"), Some((1700000000, 0)));
+
+        // Without a readable heartbeat time there is no heartbeat
+        assert_eq!(parse_heartbeat(""), None);
+        assert_eq!(parse_heartbeat("\n1699999990\n"), None);
+        assert_eq!(parse_heartbeat("-5\n0\n"), None);
+        assert_eq!(parse_heartbeat("garbage"), None);
+    }
+
+    #[test]
+    fn test_check_heartbeat() {
+        let now = 1700000000;
+        let stale = STALE_AFTER.as_secs();
+
+        assert_eq!(
+            check_heartbeat(Some((now - 2, now - 60)), now, 42).unwrap(),
+            (2, now - 60)
+        );
+        assert_eq!(
+            check_heartbeat(Some((now - stale, 0)), now, 42).unwrap(),
+            (stale, 0)
+        );
+        // A heartbeat from the future, after a clock change, is fresh
+        assert_eq!(
+            check_heartbeat(Some((now + 3, 0)), now, 42).unwrap(),
+            (0, 0)
+        );
+
+        // A stale or missing heartbeat means the listener stalled
+        let err = check_heartbeat(Some((now - stale - 1, 0)), now, 42).unwrap_err();
+        assert!(err.to_string().contains(&format!("{}s ago", stale + 1)));
+        let err = check_heartbeat(None, now, 42).unwrap_err();
+        assert!(err.to_string().contains("never reported a heartbeat"));
+        assert!(err.to_string().contains("42"));
+    }
+}
@@ -0,0 +1,109 @@
+//! Diagnostic mode that prints how each key press is turned into a character.
+//!
+//! Useful on non-US layouts when a shortcut never matches: it shows the key
+//! rdev reports, the name the OS gives it and the character snipt resolves.
+
+use crate::permissions::check_and_request_permissions;
+use rdev::{self, EventType, Key as RdevKey};
+use snipt_core::keyboard::{rdev_key_to_char, ModifierState, Modifiers};
+use snipt_core::layout::KeyboardLayout;
+use snipt_core::{load_config, Result, SniptError};
+use std::sync::mpsc;
+use std::thread;
+
+/// Print every key press with its modifiers and resolved character until
+/// Escape is pressed
+pub fn debug_keys() -> Result<()> {
+    check_and_request_permissions()?;
+    let config = load_config()?;
+    let layout = KeyboardLayout::from_config(&config)?;
+
+    println!(
+        "Keyboard layout: {:?}{}",
+        config.keyboard_layout,
+        if config.layout_overrides.is_empty() {
+            String::new()
+        } else {
+            format!(" with {} override(s)", config.layout_overrides.len())
+        }
+    );
+    println!("Press keys to see how snipt reads them. Press Escape to quit.\n");
+    println!(
+        "{:<16} {:<20} {:<8} {:<8} snipt",
+        "key", "modifiers", "os", "default"
+    );
+
+    let (done, finished) = mpsc::channel();
+    thread::spawn(move || {
+        let mut modifier_state = ModifierState::default();
+        let escape_pressed = done.clone();
+        let result = rdev::listen(move |event| {
+            let key = match event.event_type {
+                EventType::KeyPress(key) => key,
+                EventType::KeyRelease(key) => {
+                    modifier_state.update(&key, false);
+                    return;
+                }
+                _ => return,
+            };
+            if modifier_state.update(&key, true) {
+                return;
+            }
+            let modifiers = modifier_state.modifiers();
+            if key == RdevKey::Escape {
+                let _ = escape_pressed.send(Ok(()));
+                return;
+            }
+
+            println!(
+                "{:<16} {:<20} {:<8} {:<8} {}",
+                format!("{:?}", key),
+                describe_modifiers(modifiers),
+                describe_name(event.name.as_deref()),
+                describe_char(rdev_key_to_char(&key, &event)),
+                describe_char(layout.key_to_char(&key, &event, modifiers.shift)),
+            );
+        });
+
+        if let Err(e) = result {
+            let _ = done.send(Err(SniptError::Other(format!(
+                "Unable to listen for keyboard events: {:?}",
+                e
+            ))));
+        }
+    });
+
+    // The listener thread can't be stopped; it ends with the process
+    finished.recv().unwrap_or(Ok(()))
+}
+
+fn describe_modifiers(modifiers: Modifiers) -> String {
+    let names: Vec<&str> = [
+        (modifiers.ctrl, "Ctrl"),
+        (modifiers.alt, "Alt"),
+        (modifiers.shift, "Shift"),
+        (modifiers.meta, "Meta"),
+    ]
+    .into_iter()
+    .filter_map(|(held, name)| held.then_some(name))
+    .collect();
+
+    if names.is_empty() {
+        "-".to_string()
+    } else {
+        names.join("+")
+    }
+}
+
+/// Show the OS-reported key name, making control characters visible
+fn describe_name(name: Option<&str>) -> String {
+    match name {
+        Some(name) if !name.is_empty() => format!("{:?}", name),
+        _ => "-".to_string(),
+    }
+}
+
+fn describe_char(c: Option<char>) -> String {
+    c.map(|c| format!("{:?}", c))
+        .unwrap_or_else(|| "-".to_string())
+}
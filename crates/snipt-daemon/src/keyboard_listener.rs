@@ -1,32 +1,420 @@
+use crate::notifications::notify_error;
+use crate::quick_switcher::open_picker;
+use log::{debug, error, info, log_enabled, warn, Level};
 use rdev::{self, EventType, Key as RdevKey};
 use snipt_core::clipboard::get_clipboard_text;
-use snipt_core::config::{EXECUTE_CHAR, SPECIAL_CHAR};
-use snipt_core::expansion::process_expansion;
-use snipt_core::handle_expansion;
-use snipt_core::keyboard::rdev_key_to_char;
+use snipt_core::config::execute_char;
+use snipt_core::expansion::{abort_expansion, check_app_rules, perform_expansion, ExpansionType};
+use snipt_core::expansion::{
+    count_prefix_matches, escapable_trigger_chars, process_expansion, trigger_chars,
+};
+use snipt_core::history::{append_history, record_usage, HistoryEntry};
+use snipt_core::keyboard::{
+    create_keyboard_controller, deletion_count, is_modifier_key, is_self_typing, terminator_key,
+    type_text, Hotkeys, ModifierState,
+};
+use snipt_core::layout::KeyboardLayout;
 use snipt_core::models::SnippetEntry;
+use snipt_core::{SniptConfig, SniptError};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Buffer length that is always kept, even with only short shortcuts
+const MIN_BUFFER_LEN: usize = 100;
+
+/// Room beyond the longest shortcut for the trigger char and typed parameters
+const BUFFER_MARGIN: usize = 64;
+
+/// Number of typed characters the daemon needs to remember to match every shortcut
+pub fn buffer_limit_for(snippets: &[SnippetEntry]) -> usize {
+    let longest = snippets
+        .iter()
+        .map(|entry| entry.shortcut.chars().count())
+        .max()
+        .unwrap_or(0);
+    (longest + BUFFER_MARGIN).max(MIN_BUFFER_LEN)
+}
+
+/// The last expansion: its shortcut, the text typed to trigger it and when
+/// it started
+struct LastExpanded {
+    shortcut: String,
+    typed: String,
+    at: Instant,
+}
+
+type LastExpansion = Mutex<Option<LastExpanded>>;
+
+/// Set while an expansion is being typed. Key events are then passed through
+/// without buffering, except for the keys that abort the expansion.
+static EXPANDING: AtomicBool = AtomicBool::new(false);
+
+/// Set by `snipt watch`: shortcuts are printed instead of expanded
+static WATCH_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Print shortcuts instead of expanding them from now on
+pub(crate) fn set_watch_only() {
+    WATCH_ONLY.store(true, Ordering::SeqCst);
+}
+
+/// Number of expansions completed since the daemon started
+static COMPLETED_EXPANSIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of expansions completed since the daemon started
+pub fn completed_expansions() -> usize {
+    COMPLETED_EXPANSIONS.load(Ordering::SeqCst)
+}
+
+/// Whether an expansion is being typed and hasn't been recorded yet
+pub(crate) fn expansion_in_progress() -> bool {
+    EXPANDING.load(Ordering::SeqCst)
+}
+
+/// When the listener last received a key event, in milliseconds since the
+/// Unix epoch (0 before the first one)
+static LAST_KEY_EVENT_MS: AtomicU64 = AtomicU64::new(0);
+
+/// When the listener started handling the current event (0 while idle)
+static CALLBACK_STARTED_MS: AtomicU64 = AtomicU64::new(0);
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// When the listener last received a key event
+pub fn last_key_event() -> Option<SystemTime> {
+    match LAST_KEY_EVENT_MS.load(Ordering::SeqCst) {
+        0 => None,
+        ms => Some(UNIX_EPOCH + Duration::from_millis(ms)),
+    }
+}
+
+/// How long the listener has been handling the current event, if it is busy.
+/// A long time means the callback is stuck and no more keys are read.
+pub fn listener_busy_for() -> Option<Duration> {
+    match CALLBACK_STARTED_MS.load(Ordering::SeqCst) {
+        0 => None,
+        started => Some(Duration::from_millis(now_ms().saturating_sub(started))),
+    }
+}
+
+/// Marks the listener busy from creation until dropped
+struct BusyGuard;
+
+impl BusyGuard {
+    fn enter() -> Self {
+        let now = now_ms();
+        LAST_KEY_EVENT_MS.store(now, Ordering::SeqCst);
+        CALLBACK_STARTED_MS.store(now, Ordering::SeqCst);
+        BusyGuard
+    }
+}
+
+impl Drop for BusyGuard {
+    fn drop(&mut self) {
+        CALLBACK_STARTED_MS.store(0, Ordering::SeqCst);
+    }
+}
+
+/// Start an expansion in the background and, unless disabled, record it in
+/// the history once it completes. Typing happens off the listener thread so
+/// that Escape (or the configured `abort_hotkey`) can stop a long expansion.
+/// `typed` is what triggered it, kept for the `repeat_hotkey`. A `symbol`
+/// that completed the shortcut is held back by the caller and typed once the
+/// expansion is done.
+///
+/// Returns `false` when the expansion was skipped, either because the frontmost
+/// app is excluded, another expansion is still being typed, or the same
+/// shortcut just fired within the configured debounce window. The key event
+/// must then be passed through untouched.
+fn expand_and_record(
+    config: &Mutex<SniptConfig>,
+    last_expansion: &LastExpansion,
+    typed: &str,
+    to_delete: usize,
+    expansion: ExpansionType,
+    symbol: Option<char>,
+) -> bool {
+    if WATCH_ONLY.load(Ordering::SeqCst) {
+        println!("{}  →  {}", typed, describe_expansion(&expansion));
+        return false;
+    }
+
+    let entry = HistoryEntry::for_expansion(&expansion);
+    let (history_enabled, history_limit, debounce) = {
+        let config = config.lock().unwrap();
+        (
+            config.history_enabled,
+            config.history_limit,
+            Duration::from_millis(config.debounce_ms),
+        )
+    };
+
+    if !debounce.is_zero() {
+        if let Some(last) = &*last_expansion.lock().unwrap() {
+            if last.shortcut == entry.shortcut && last.at.elapsed() < debounce {
+                debug!("Ignoring '{}': repeated within debounce_ms", last.shortcut);
+                return false;
+            }
+        }
+    }
+
+    if let Err(e) = check_app_rules() {
+        debug!("Not expanding '{}': {}", entry.shortcut, e);
+        return false;
+    }
+    if EXPANDING.swap(true, Ordering::SeqCst) {
+        debug!(
+            "Not expanding '{}': another expansion is still being typed",
+            entry.shortcut
+        );
+        return false;
+    }
+    *last_expansion.lock().unwrap() = Some(LastExpanded {
+        shortcut: entry.shortcut.clone(),
+        typed: typed.to_string(),
+        at: Instant::now(),
+    });
+
+    thread::spawn(move || {
+        info!(
+            "Expanding '{}' ({} {})",
+            entry.shortcut, entry.kind, entry.style
+        );
+        if !entry.tab_stops.is_empty() {
+            let stops: Vec<String> = entry
+                .tab_stops
+                .iter()
+                .map(|stop| format!("${}@{}", stop.index, stop.offset))
+                .collect();
+            info!("Tab stops of '{}': {}", entry.shortcut, stops.join(" "));
+        }
+        match expand_then_type_symbol(to_delete, expansion, symbol, perform_expansion, type_symbol)
+        {
+            Ok(()) => {
+                if let Err(e) = record_usage(&entry.shortcut) {
+                    warn!("Failed to count the expansion: {}", e);
+                }
+                if history_enabled {
+                    if let Err(e) = append_history(entry, history_limit) {
+                        warn!("Failed to record expansion history: {}", e);
+                    }
+                }
+                COMPLETED_EXPANSIONS.fetch_add(1, Ordering::SeqCst);
+            }
+            Err(SniptError::ExpansionAborted) => {
+                info!("Expansion of '{}' was interrupted", entry.shortcut);
+            }
+            Err(SniptError::CommandTimedOut(secs)) => warn!(
+                "Stopped '{}' after {} seconds; raise execute_timeout_secs if it needs longer",
+                entry.shortcut, secs
+            ),
+            Err(e) => error!("Failed to expand '{}': {}", entry.shortcut, e),
+        }
+        EXPANDING.store(false, Ordering::SeqCst);
+    });
+    true
+}
+
+/// Run an expansion with `perform`, then type the `symbol` that completed
+/// it with `type_symbol`. The symbol's key event was swallowed, so it lands
+/// after the expansion instead of being erased along with the shortcut. It is
+/// typed even when the expansion fails or is aborted, so it isn't lost; the
+/// expansion's error is returned after that.
+fn expand_then_type_symbol(
+    to_delete: usize,
+    expansion: ExpansionType,
+    symbol: Option<char>,
+    perform: impl FnOnce(usize, ExpansionType) -> snipt_core::Result<()>,
+    type_symbol: impl FnOnce(char) -> snipt_core::Result<()>,
+) -> snipt_core::Result<()> {
+    let result = perform(to_delete, expansion);
+    if let Some(symbol) = symbol {
+        let typed = type_symbol(symbol);
+        result?;
+        return typed;
+    }
+    result
+}
+
+fn type_symbol(symbol: char) -> snipt_core::Result<()> {
+    let mut keyboard = create_keyboard_controller()?;
+    type_text(&mut keyboard, &symbol.to_string())
+}
+
+/// What an expansion would do, for `snipt watch`
+fn describe_expansion(expansion: &ExpansionType) -> String {
+    match expansion {
+        ExpansionType::Text(text, _, _) => format!("{:?}", text),
+        ExpansionType::Execute(target, _, _) => format!("runs {:?}", target),
+        ExpansionType::ExecuteWithParams(target, params, _, _) => {
+            format!("runs {:?} with ({})", target, params.join(", "))
+        }
+        ExpansionType::ExecuteAndInsert(command, params, _, _) if params.is_empty() => {
+            format!("types the output of `{}`", command)
+        }
+        ExpansionType::ExecuteAndInsert(command, params, _, _) => format!(
+            "types the output of `{}` with ({})",
+            command,
+            params.join(", ")
+        ),
+    }
+}
+
+/// Expand the last expanded snippet again at the cursor, as it is defined now
+/// and with the same parameters. Nothing is typed, only logged, when nothing
+/// was expanded yet or the snippet has since been deleted or disabled.
+fn repeat_last_expansion(
+    config: &Mutex<SniptConfig>,
+    last_expansion: &LastExpansion,
+    snippets: &[SnippetEntry],
+) {
+    let Some((shortcut, typed)) = last_expansion
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|last| (last.shortcut.clone(), last.typed.clone()))
+    else {
+        info!("Nothing to repeat: no snippet was expanded yet");
+        return;
+    };
+
+    match process_expansion(&typed, snippets) {
+        Ok(Some(expansion)) => {
+            expand_and_record(config, last_expansion, &typed, 0, expansion, None);
+        }
+        Ok(None) => info!(
+            "Not repeating '{}': the snippet was deleted or disabled",
+            shortcut
+        ),
+        Err(e) => warn!("Could not repeat '{}': {}", shortcut, e),
+    }
+}
+
+/// Check whether the character at `index` starts a word: it follows whitespace
+/// in the buffer, or is the first buffered character and the buffer itself
+/// starts a word
+fn starts_word(
+    buffer: &VecDeque<(char, Instant)>,
+    index: usize,
+    buffer_at_word_start: bool,
+) -> bool {
+    match index.checked_sub(1) {
+        Some(previous) => buffer[previous].0.is_whitespace(),
+        None => buffer_at_word_start,
+    }
+}
+
+/// Check whether typing `c` doubles the trigger at the end of the buffer, so
+/// the pair is typed as one literal trigger instead of starting a shortcut
+fn escapes_trigger(
+    buffer: &VecDeque<(char, Instant)>,
+    c: char,
+    escapable: &[char],
+    whole_word: bool,
+    buffer_at_word_start: bool,
+) -> bool {
+    escapable.contains(&c)
+        && buffer.back().is_some_and(|(last, _)| *last == c)
+        && (!whole_word || starts_word(buffer, buffer.len() - 1, buffer_at_word_start))
+}
+
+/// Check whether typing the symbol `c` completes a shortcut when
+/// `expand_on_non_alphanumeric` is on. Characters that can be part of a
+/// shortcut or its parameters, and trigger characters, never do.
+fn symbol_completes_shortcut(c: char, triggers: &[char], in_params: bool) -> bool {
+    !c.is_alphanumeric()
+        && !matches!(c, '(' | ')' | ',' | '_' | '-')
+        && !triggers.contains(&c)
+        && !in_params
+}
+
+/// Find the earliest shortcut in the buffer that runs to its end and expands,
+/// returning the shortcut text and its expansion. In whole-word mode the
+/// shortcut has to start a word.
+fn find_buffered_expansion(
+    buffer: &VecDeque<(char, Instant)>,
+    triggers: &[char],
+    whole_word: bool,
+    buffer_at_word_start: bool,
+    snippets: &[SnippetEntry],
+) -> Option<(String, ExpansionType)> {
+    (0..buffer.len()).find_map(|i| {
+        if !triggers.contains(&buffer[i].0)
+            || (whole_word && !starts_word(buffer, i, buffer_at_word_start))
+        {
+            return None;
+        }
+        let potential_snippet: String = buffer.range(i..).map(|(c, _)| *c).collect();
+        match process_expansion(&potential_snippet, snippets) {
+            Ok(Some(expansion)) => Some((potential_snippet, expansion)),
+            _ => None,
+        }
+    })
+}
+
+/// Check whether buffered text starts with one of `triggers`, so only
+/// attempted shortcuts (never ordinary typing) end up in debug logs
+fn looks_like_trigger(text: &str, triggers: &[char]) -> bool {
+    text.starts_with(triggers)
+}
+
+/// Log the attempted shortcut at the end of the buffer and how many shortcuts
+/// it could still become, to watch matching live with `SNIPT_LOG=debug`
+fn log_typed_so_far(
+    buffer: &VecDeque<(char, Instant)>,
+    triggers: &[char],
+    snippets: &[SnippetEntry],
+) {
+    let Some(start) = buffer.iter().rposition(|(c, _)| triggers.contains(c)) else {
+        return;
+    };
+    let typed: String = buffer.range(start..).map(|(c, _)| *c).collect();
+    if typed.contains(char::is_whitespace) {
+        return;
+    }
+    let matches = count_prefix_matches(&typed, snippets);
+    debug!(
+        "buffer='{}' — prefix of {} shortcut{}",
+        typed,
+        matches,
+        if matches == 1 { "" } else { "s" }
+    );
+}
 
 /// Starts listening for keyboard events and handles text expansion
 pub fn start_keyboard_listener(
     snippets: Arc<Mutex<Vec<SnippetEntry>>>,
     running: Arc<Mutex<bool>>,
+    paused: Arc<Mutex<bool>>,
+    layout: Arc<Mutex<KeyboardLayout>>,
+    buffer_limit: Arc<AtomicUsize>,
+    config: Arc<Mutex<SniptConfig>>,
+    hotkeys: Arc<Mutex<Hotkeys>>,
 ) -> JoinHandle<()> {
     // Buffer for text accumulation with a timestamp for each character
-    let text_buffer = Arc::new(Mutex::new(Vec::<(char, Instant)>::new()));
+    let text_buffer = Arc::new(Mutex::new(VecDeque::<(char, Instant)>::new()));
     let buffer_clone = Arc::clone(&text_buffer);
 
+    // Last expansion, used to debounce repeated triggers
+    let last_expansion: LastExpansion = Mutex::new(None);
+
+    // Whether the character before the start of the buffer ends a word
+    // (whitespace, start of line or nothing typed yet), for whole-word mode
+    let word_start = Mutex::new(true);
+
     // Flag to track if we've just performed an expansion
     let just_expanded = Arc::new(Mutex::new(false));
     let expanded_flag_clone = Arc::clone(&just_expanded);
 
-    // Track modifier key states
-    let cmd_pressed = Arc::new(Mutex::new(false));
-    let ctrl_pressed = Arc::new(Mutex::new(false));
-    let cmd_clone = Arc::clone(&cmd_pressed);
-    let ctrl_clone = Arc::clone(&ctrl_pressed);
+    // Modifier keys held down, from both key presses and releases
+    let modifier_state = Mutex::new(ModifierState::default());
 
     // Clone for the thread
     let snippets_clone = Arc::clone(&snippets);
@@ -35,66 +423,155 @@ pub fn start_keyboard_listener(
     thread::spawn(move || {
         // Create a callback function closure
         let callback = move |event: rdev::Event| -> Option<rdev::Event> {
+            let _busy = BusyGuard::enter();
             if !*running_clone.lock().unwrap() {
                 return Some(event);
             }
 
-            // Handle modifier state updates first, then decide if it's a KeyPress to process further
-            match event.event_type {
+            // Releases only update the modifier state; presses go on below
+            let key = match event.event_type {
                 EventType::KeyPress(key) => {
-                    // Update modifier state for KeyPress
-                    match key {
-                        RdevKey::MetaLeft | RdevKey::MetaRight => {
-                            *cmd_clone.lock().unwrap() = true;
-                        }
-                        RdevKey::ControlLeft | RdevKey::ControlRight => {
-                            *ctrl_clone.lock().unwrap() = true;
-                        }
-                        _ => {}
-                    }
-                    // Proceed to main KeyPress logic
+                    modifier_state.lock().unwrap().update(&key, true);
+                    key
                 }
                 EventType::KeyRelease(key) => {
-                    match key {
-                        RdevKey::MetaLeft | RdevKey::MetaRight => {
-                            *cmd_clone.lock().unwrap() = false;
-                        }
-                        RdevKey::ControlLeft | RdevKey::ControlRight => {
-                            *ctrl_clone.lock().unwrap() = false;
-                        }
-                        _ => {}
-                    }
+                    modifier_state.lock().unwrap().update(&key, false);
                     return Some(event);
                 }
                 _ => return Some(event),
-            }
-
-            // This point is reached only for EventType::KeyPress
-            // We need to get the key again from event.event_type
-            let key = match event.event_type {
-                EventType::KeyPress(k) => k,
-                _ => return Some(event),
             };
+            let modifiers = modifier_state.lock().unwrap().modifiers();
+            let hotkeys = *hotkeys.lock().unwrap();
+
+            // While an expansion is typed, our own keystrokes come through here
+            // too; only watch for the keys that stop it
+            if EXPANDING.load(Ordering::SeqCst) {
+                let is_abort = key == RdevKey::Escape
+                    || hotkeys
+                        .abort
+                        .is_some_and(|hotkey| hotkey.matches(&key, modifiers));
+                if is_abort {
+                    abort_expansion();
+                    return None;
+                }
+                return Some(event);
+            }
 
             let mut buffer = buffer_clone.lock().unwrap();
+
+            // Text snipt types or pastes itself is never matched; a snippet
+            // that contains another shortcut mustn't expand again
+            if is_self_typing() {
+                buffer.clear();
+                return Some(event);
+            }
+
+            // The quick switcher hotkey opens the snippet picker instead of typing
+            if let Some(hotkey) = hotkeys.picker {
+                if hotkey.matches(&key, modifiers) {
+                    buffer.clear();
+                    let terminal = config.lock().unwrap().picker_terminal.clone();
+                    open_picker(Arc::clone(&snippets_clone), terminal);
+                    return None;
+                }
+            }
+
+            let mut at_word_start = word_start.lock().unwrap();
+
+            // While paused, pass everything through and forget what was typed
+            if *paused.lock().unwrap() {
+                buffer.clear();
+                *at_word_start = true;
+                return Some(event);
+            }
+            // The repeat hotkey expands the last snippet again
+            if hotkeys
+                .repeat
+                .is_some_and(|hotkey| hotkey.matches(&key, modifiers))
+            {
+                buffer.clear();
+                *at_word_start = false;
+                repeat_last_expansion(&config, &last_expansion, &snippets_clone.lock().unwrap());
+                return None;
+            }
+
             let mut just_expanded_val = expanded_flag_clone.lock().unwrap();
+            let (whole_word, escaped_triggers, ignore_chords, terminators, expand_on_symbols) = {
+                let config = config.lock().unwrap();
+                let terminators: Vec<RdevKey> = config
+                    .terminator_keys
+                    .iter()
+                    .filter_map(|name| terminator_key(name))
+                    .collect();
+                (
+                    config.whole_word,
+                    config.escaped_triggers,
+                    config.ignore_chords,
+                    terminators,
+                    config.expand_on_non_alphanumeric,
+                )
+            };
+            // With an expand hotkey, typing only fills the buffer
+            let hotkey_mode = hotkeys.expand.is_some();
+
+            // The expand hotkey completes the shortcut at the end of the buffer
+            if hotkeys
+                .expand
+                .is_some_and(|hotkey| hotkey.matches(&key, modifiers))
+            {
+                let snippets_guard = snippets_clone.lock().unwrap();
+                let triggers = trigger_chars(&snippets_guard);
+                if let Some((potential_snippet, expansion)) = find_buffered_expansion(
+                    &buffer,
+                    &triggers,
+                    whole_word,
+                    *at_word_start,
+                    &snippets_guard,
+                ) {
+                    let expanded = expand_and_record(
+                        &config,
+                        &last_expansion,
+                        &potential_snippet,
+                        deletion_count(&potential_snippet),
+                        expansion,
+                        None,
+                    );
+                    buffer.clear();
+                    *at_word_start = false;
+                    if expanded {
+                        *just_expanded_val = true;
+                        return None;
+                    }
+                    return Some(event);
+                }
+
+                let buffer_text: String = buffer.iter().map(|(c, _)| *c).collect();
+                if buffer_text.contains(triggers.as_slice()) {
+                    debug!("'{}' ends with no snippet", buffer_text);
+                }
+                return Some(event);
+            }
 
             // Handle paste command (Cmd+V on macOS, Ctrl+V on other platforms)
             let is_paste = match key {
                 RdevKey::KeyV => {
                     #[cfg(target_os = "macos")]
                     {
-                        *cmd_clone.lock().unwrap()
+                        modifiers.meta
                     }
                     #[cfg(not(target_os = "macos"))]
                     {
-                        *ctrl_clone.lock().unwrap()
+                        modifiers.ctrl
                     }
                 }
                 _ => false,
             };
 
             if is_paste {
+                // In whole-word mode a paste only expands when the buffer starts a word
+                if hotkey_mode || (whole_word && !*at_word_start) {
+                    return Some(event);
+                }
                 if let Ok(clipboard_text) = get_clipboard_text() {
                     let temp_buffer_for_paste_check: String =
                         buffer.iter().map(|(c, _)| *c).collect::<String>() + &clipboard_text;
@@ -111,12 +588,21 @@ pub fn start_keyboard_listener(
                         if let Ok(Some(expansion_from_paste)) =
                             process_expansion(&combined_text_for_check, &snippets_guard)
                         {
-                            let _ = handle_expansion(
-                                combined_text_for_check.len(),
+                            if !expand_and_record(
+                                &config,
+                                &last_expansion,
+                                &combined_text_for_check,
+                                deletion_count(&combined_text_for_check),
                                 expansion_from_paste,
-                            );
+                                None,
+                            ) {
+                                buffer.clear();
+                                *at_word_start = false;
+                                return Some(event);
+                            }
                             *just_expanded_val = true;
                             buffer.clear();
+                            *at_word_start = false;
                             return None;
                         }
                     }
@@ -124,24 +610,64 @@ pub fn start_keyboard_listener(
                 return Some(event);
             }
 
+            // A chord such as Ctrl+C or Cmd+A doesn't type its key, and may
+            // move the cursor, so whatever was typed before no longer leads
+            // up to it
+            if ignore_chords && modifiers.is_chord() && !is_modifier_key(&key) {
+                if !buffer.is_empty() {
+                    debug!("Chord pressed; dropping the buffered text");
+                }
+                buffer.clear();
+                *just_expanded_val = false;
+                *at_word_start = true;
+                return Some(event);
+            }
+
             // Handle special keys
             match key {
-                RdevKey::Space | RdevKey::Return | RdevKey::Tab => {
+                _ if terminators.contains(&key) && !hotkey_mode => {
                     if !buffer.is_empty() {
                         let buffer_text: String = buffer.iter().map(|(c, _)| *c).collect();
 
                         if buffer_text.contains('(') && !buffer_text.contains(')') {
-                            buffer.push((' ', Instant::now()));
+                            buffer.push_back((' ', Instant::now()));
                             return Some(event);
                         }
 
                         let snippets_guard = snippets_clone.lock().unwrap();
-                        if let Ok(Some(expansion)) =
-                            process_expansion(&buffer_text, &snippets_guard)
-                        {
-                            let _ = handle_expansion(buffer_text.len(), expansion);
+                        let triggers = trigger_chars(&snippets_guard);
+                        let expansion = match process_expansion(&buffer_text, &snippets_guard) {
+                            Ok(Some(expansion)) if !whole_word || *at_word_start => Some(expansion),
+                            Ok(Some(_)) => {
+                                debug!("'{}' is not at the start of a word", buffer_text);
+                                None
+                            }
+                            Ok(None) if looks_like_trigger(&buffer_text, &triggers) => {
+                                debug!("'{}' matches no snippet", buffer_text);
+                                None
+                            }
+                            Err(e) if looks_like_trigger(&buffer_text, &triggers) => {
+                                debug!("'{}' could not be expanded: {}", buffer_text, e);
+                                None
+                            }
+                            _ => None,
+                        };
+                        if let Some(expansion) = expansion {
+                            if !expand_and_record(
+                                &config,
+                                &last_expansion,
+                                &buffer_text,
+                                deletion_count(&buffer_text),
+                                expansion,
+                                None,
+                            ) {
+                                buffer.clear();
+                                *at_word_start = true;
+                                return Some(event);
+                            }
                             *just_expanded_val = true;
                             buffer.clear();
+                            *at_word_start = false;
                             return None;
                         }
                     }
@@ -151,56 +677,144 @@ pub fn start_keyboard_listener(
                         *just_expanded_val = false;
                     }
                     buffer.clear();
+                    *at_word_start = true;
+                    Some(event)
+                }
+                // A line break or tab that doesn't complete a shortcut still ends it;
+                // a non-terminating space is buffered like any other character
+                RdevKey::Return | RdevKey::Tab => {
+                    buffer.clear();
+                    *just_expanded_val = false;
+                    *at_word_start = true;
                     Some(event)
                 }
                 RdevKey::Backspace => {
                     if !buffer.is_empty() {
-                        buffer.pop();
+                        buffer.pop_back();
                     }
                     Some(event)
                 }
                 RdevKey::Escape => {
                     buffer.clear();
+                    *at_word_start = true;
                     Some(event)
                 }
                 _ => {
                     if *just_expanded_val {
                         buffer.clear();
                         *just_expanded_val = false;
+                        *at_word_start = false;
                     }
 
-                    if let Some(c) = rdev_key_to_char(&key, &event) {
-                        buffer.push((c, Instant::now()));
-
+                    if let Some(c) =
+                        layout
+                            .lock()
+                            .unwrap()
+                            .key_to_char(&key, &event, modifiers.shift)
+                    {
+                        let execute_char = execute_char();
                         let snippets_guard = snippets_clone.lock().unwrap();
+                        let triggers = trigger_chars(&snippets_guard);
 
-                        if c == ')' {
+                        // Any symbol can complete a shortcut. Its key event is
+                        // held back and the symbol typed after the expansion,
+                        // so the backspaces can't erase it.
+                        let in_params = buffer.iter().any(|(ch, _)| *ch == '(')
+                            && !buffer.iter().any(|(ch, _)| *ch == ')');
+
+                        // A doubled trigger types one literal trigger, and the
+                        // word after it never expands
+                        if escaped_triggers
+                            && !in_params
+                            && escapes_trigger(
+                                &buffer,
+                                c,
+                                &escapable_trigger_chars(&snippets_guard),
+                                whole_word,
+                                *at_word_start,
+                            )
+                        {
+                            debug!("'{}{}' typed as an escaped trigger", c, c);
+                            buffer.clear();
+                            *at_word_start = false;
+                            return None;
+                        }
+                        if expand_on_symbols
+                            && !hotkey_mode
+                            && symbol_completes_shortcut(c, &triggers, in_params)
+                        {
+                            if let Some((potential_snippet, expansion)) = find_buffered_expansion(
+                                &buffer,
+                                &triggers,
+                                whole_word,
+                                *at_word_start,
+                                &snippets_guard,
+                            ) {
+                                let expanded = expand_and_record(
+                                    &config,
+                                    &last_expansion,
+                                    &potential_snippet,
+                                    deletion_count(&potential_snippet),
+                                    expansion,
+                                    Some(c),
+                                );
+                                buffer.clear();
+                                *at_word_start = false;
+                                if expanded {
+                                    *just_expanded_val = true;
+                                    return None;
+                                }
+                                return Some(event);
+                            }
+                        }
+
+                        buffer.push_back((c, Instant::now()));
+                        if log_enabled!(Level::Debug) && !hotkey_mode {
+                            log_typed_so_far(&buffer, &triggers, &snippets_guard);
+                        }
+
+                        if c == ')' && !hotkey_mode {
                             let buffer_text_fn: String = buffer.iter().map(|(c, _)| *c).collect();
-                            if buffer_text_fn.starts_with(EXECUTE_CHAR)
+                            if buffer_text_fn.starts_with(execute_char)
                                 && buffer_text_fn.contains('(')
+                                && (!whole_word || *at_word_start)
                             {
                                 if let Ok(Some(expansion)) =
                                     process_expansion(&buffer_text_fn, &snippets_guard)
                                 {
-                                    let _ = handle_expansion(buffer_text_fn.len(), expansion);
+                                    if !expand_and_record(
+                                        &config,
+                                        &last_expansion,
+                                        &buffer_text_fn,
+                                        deletion_count(&buffer_text_fn),
+                                        expansion,
+                                        None,
+                                    ) {
+                                        buffer.clear();
+                                        *at_word_start = false;
+                                        return Some(event);
+                                    }
                                     *just_expanded_val = true;
                                     buffer.clear();
+                                    *at_word_start = false;
                                     return None;
                                 }
                             }
                         }
 
-                        if (c == SPECIAL_CHAR || c == EXECUTE_CHAR) && buffer.len() == 1 {
+                        if triggers.contains(&c) && buffer.len() == 1 {
                             return Some(event);
                         }
 
                         for i in 0..buffer.len() {
                             let first_char = buffer[i].0;
-                            if (first_char == SPECIAL_CHAR || first_char == EXECUTE_CHAR)
+                            if !hotkey_mode
+                                && triggers.contains(&first_char)
                                 && i < buffer.len() - 1
+                                && (!whole_word || starts_word(&buffer, i, *at_word_start))
                             {
                                 let potential_snippet: String =
-                                    buffer[i..].iter().map(|(ci, _)| *ci).collect();
+                                    buffer.range(i..).map(|(ci, _)| *ci).collect();
 
                                 if potential_snippet.contains('(')
                                     && !potential_snippet.contains(')')
@@ -211,7 +825,18 @@ pub fn start_keyboard_listener(
                                 if let Ok(Some(expansion)) =
                                     process_expansion(&potential_snippet, &snippets_guard)
                                 {
-                                    let _ = handle_expansion(potential_snippet.len(), expansion);
+                                    if !expand_and_record(
+                                        &config,
+                                        &last_expansion,
+                                        &potential_snippet,
+                                        deletion_count(&potential_snippet),
+                                        expansion,
+                                        None,
+                                    ) {
+                                        buffer.clear();
+                                        *at_word_start = false;
+                                        return Some(event);
+                                    }
                                     *just_expanded_val = true;
                                     buffer.drain(i..);
                                     return None;
@@ -219,11 +844,17 @@ pub fn start_keyboard_listener(
                             }
                         }
                         let now = Instant::now();
+                        let len_before = buffer.len();
                         buffer.retain(|(_, timestamp)| {
                             now.duration_since(*timestamp) < Duration::from_secs(10)
                         });
-                        while buffer.len() > 100 {
-                            buffer.remove(0);
+                        let limit = buffer_limit.load(Ordering::Relaxed);
+                        while buffer.len() > limit {
+                            buffer.pop_front();
+                        }
+                        // What came before the buffer is no longer known
+                        if buffer.len() < len_before {
+                            *at_word_start = false;
                         }
                         Some(event)
                     } else {
@@ -235,7 +866,165 @@ pub fn start_keyboard_listener(
 
         // Register the callback
         if let Err(error) = rdev::grab(callback) {
-            eprintln!("Error: Unable to grab keyboard events: {:?}. Ensure you have necessary permissions (e.g., member of 'input' group on Linux for Wayland/evdev).", error);
+            error!("Unable to grab keyboard events: {:?}. Ensure you have necessary permissions (e.g., member of 'input' group on Linux for Wayland/evdev).", error);
+            notify_error(
+                "snipt stopped expanding snippets",
+                "Unable to read keyboard events. Grant input access to snipt, then run 'snipt restart'.",
+            );
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snipt_core::config::trigger_char;
+    use std::cell::RefCell;
+
+    fn buffer_of(text: &str) -> VecDeque<(char, Instant)> {
+        text.chars().map(|c| (c, Instant::now())).collect()
+    }
+
+    #[test]
+    fn test_escaped_trigger() {
+        let mut custom = SnippetEntry::new("d".to_string(), "done".to_string());
+        custom.trigger = Some(';');
+        let escapable = escapable_trigger_chars(&[custom]);
+        let trigger = trigger_char();
+        let execute = execute_char();
+
+        // Doubling the trigger or a snippet's own trigger escapes it
+        let doubled = buffer_of(&format!("hi {}", trigger));
+        assert!(escapes_trigger(&doubled, trigger, &escapable, true, true));
+        assert!(escapes_trigger(
+            &buffer_of(";"),
+            ';',
+            &escapable,
+            true,
+            true
+        ));
+
+        // Doubling the execute character types both, e.g. "wow!!"
+        let bangs = buffer_of(&format!("wow{}", execute));
+        assert!(!escapes_trigger(&bangs, execute, &escapable, false, true));
+
+        // Only at the start of a word when whole_word is on
+        let mid_word = buffer_of(&format!("a{}", trigger));
+        assert!(!escapes_trigger(&mid_word, trigger, &escapable, true, true));
+        assert!(escapes_trigger(&mid_word, trigger, &escapable, false, true));
+        assert!(!escapes_trigger(
+            &buffer_of(""),
+            trigger,
+            &escapable,
+            false,
+            true
+        ));
+    }
+
+    #[test]
+    fn test_looks_like_trigger() {
+        let mut custom = SnippetEntry::new("d".to_string(), "done".to_string());
+        custom.trigger = Some(';');
+        let triggers = trigger_chars(&[custom]);
+
+        assert!(looks_like_trigger(
+            &format!("{}sig", trigger_char()),
+            &triggers
+        ));
+        assert!(looks_like_trigger(
+            &format!("{}ls", execute_char()),
+            &triggers
+        ));
+        // A snippet's own trigger counts too
+        assert!(looks_like_trigger(";d", &triggers));
+        assert!(!looks_like_trigger("hello", &triggers));
+    }
+
+    #[test]
+    fn test_symbol_terminators() {
+        let snippets = vec![SnippetEntry::new("sig".to_string(), "Regards".to_string())];
+        let triggers = trigger_chars(&snippets);
+        let trigger = trigger_char();
+
+        // Punctuation completes a shortcut; letters, digits, characters of
+        // shortcuts and parameters, and triggers don't
+        for symbol in ['.', '!', '?', ';', ' ', '"'] {
+            if !triggers.contains(&symbol) {
+                assert!(symbol_completes_shortcut(symbol, &triggers, false));
+            }
+        }
+        for symbol in ['a', '7', '(', ')', ',', '_', '-', trigger] {
+            assert!(!symbol_completes_shortcut(symbol, &triggers, false));
+        }
+        // Inside parameters nothing does
+        assert!(!symbol_completes_shortcut('.', &triggers, true));
+
+        // The shortcut at the end of the buffer is found, after other words
+        let (shortcut, expansion) = find_buffered_expansion(
+            &buffer_of(&format!("hi {}sig", trigger)),
+            &triggers,
+            true,
+            true,
+            &snippets,
+        )
+        .unwrap();
+        assert_eq!(shortcut, format!("{}sig", trigger));
+        assert_eq!(expansion.content(), "Regards");
+
+        // In whole-word mode it has to start a word
+        let glued = buffer_of(&format!("hi{}sig", trigger));
+        assert!(find_buffered_expansion(&glued, &triggers, true, true, &snippets).is_none());
+        assert!(find_buffered_expansion(&glued, &triggers, false, true, &snippets).is_some());
+        // An unfinished shortcut is not expanded
+        let partial = buffer_of(&format!("{}si", trigger));
+        assert!(find_buffered_expansion(&partial, &triggers, false, true, &snippets).is_none());
+    }
+
+    #[test]
+    fn test_symbol_typed_after_expansion() {
+        let snippets = vec![SnippetEntry::new("sig".to_string(), "Regards".to_string())];
+        let typed = format!("{}sig", trigger_char());
+        let events = RefCell::new(Vec::new());
+        let run = |symbol, fail: bool| {
+            let expansion = process_expansion(&typed, &snippets).unwrap().unwrap();
+            expand_then_type_symbol(
+                deletion_count(&typed),
+                expansion,
+                symbol,
+                |to_delete, expansion| {
+                    events.borrow_mut().push(format!(
+                        "delete {}, type {}",
+                        to_delete,
+                        expansion.content()
+                    ));
+                    if fail {
+                        Err(SniptError::ExpansionAborted)
+                    } else {
+                        Ok(())
+                    }
+                },
+                |symbol| {
+                    events.borrow_mut().push(format!("type {}", symbol));
+                    Ok(())
+                },
+            )
+        };
+
+        // Only the shortcut is deleted, and the symbol comes after the text
+        run(Some('.'), false).unwrap();
+        assert_eq!(*events.borrow(), ["delete 4, type Regards", "type ."]);
+
+        // Without a symbol nothing follows
+        events.borrow_mut().clear();
+        run(None, false).unwrap();
+        assert_eq!(*events.borrow(), ["delete 4, type Regards"]);
+
+        // A failed or aborted expansion still types the swallowed symbol
+        events.borrow_mut().clear();
+        assert!(matches!(
+            run(Some('.'), true),
+            Err(SniptError::ExpansionAborted)
+        ));
+        assert_eq!(*events.borrow(), ["delete 4, type Regards", "type ."]);
+    }
+}
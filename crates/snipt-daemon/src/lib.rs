@@ -1,9 +1,22 @@
+mod autostart;
+mod control;
 mod daemon_manager;
+mod heartbeat;
+mod key_debug;
 mod keyboard_listener;
+mod notifications;
 mod permissions;
 mod process;
+mod quick_switcher;
 
 // Re-export the main functionality
+pub use autostart::{autostart_status, disable_autostart, enable_autostart};
+pub use control::{ControlRequest, ControlResponse};
 pub use daemon_manager::{
-    daemon_status, daemon_worker, daemon_worker_entry, run_daemon_worker, start_daemon, stop_daemon,
+    daemon_status, daemon_worker, daemon_worker_entry, paste_snippet_now, pause_daemon,
+    reload_daemon, restart_daemon, resume_daemon, run_daemon_worker, run_once, start_daemon,
+    stop_daemon, watch_expansions,
 };
+pub use heartbeat::daemon_health;
+pub use key_debug::debug_keys;
+pub use permissions::{report_permissions, verify_permissions};
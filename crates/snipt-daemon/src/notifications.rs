@@ -0,0 +1,22 @@
+//! Desktop notifications for problems the user can't see in a detached daemon.
+
+use log::warn;
+use notify_rust::Notification;
+use snipt_core::settings::current_config;
+
+/// Show a desktop notification about a daemon error, unless the user turned
+/// notifications off. Failing to notify is never fatal.
+pub fn notify_error(summary: &str, body: &str) {
+    if !current_config().notifications_enabled {
+        return;
+    }
+
+    if let Err(e) = Notification::new()
+        .appname("snipt")
+        .summary(summary)
+        .body(body)
+        .show()
+    {
+        warn!("Failed to show desktop notification: {}", e);
+    }
+}
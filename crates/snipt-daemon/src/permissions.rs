@@ -543,8 +543,7 @@ fn inform_windows_permissions() -> Result<()> {
     Ok(())
 }
 
-// Add a reusable permissions verification function
-#[allow(dead_code)]
+/// Check whether the permissions needed to read and send keystrokes are granted
 pub fn verify_permissions() -> bool {
     #[cfg(target_os = "macos")]
     {
@@ -561,3 +560,62 @@ pub fn verify_permissions() -> bool {
         true // Windows doesn't have explicit permissions
     }
 }
+
+/// Report whether the permissions snipt needs are granted, without prompting
+/// or starting anything, and print how to fix whatever is missing. Returns
+/// an error when something is missing so setup scripts can check the exit code.
+pub fn report_permissions() -> Result<()> {
+    #[cfg(target_os = "macos")]
+    {
+        let granted = has_accessibility_permission();
+        println!(
+            "Accessibility: {}",
+            if granted { "granted" } else { "denied" }
+        );
+        if !granted {
+            let terminal = get_terminal_app_name();
+            println!();
+            println!("To fix this:");
+            println!("1. Open System Settings > Privacy & Security > Accessibility");
+            println!("2. Click '+' and add {} (or enable it if listed)", terminal);
+            println!("3. Do the same under Privacy & Security > Input Monitoring");
+            println!(
+                "4. Quit and reopen {}, then run 'snipt permissions' again",
+                terminal
+            );
+            return Err(SniptError::PermissionDenied(
+                "Accessibility access has not been granted".to_string(),
+            ));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let granted = has_input_permission();
+        println!(
+            "Input devices: {}",
+            if granted { "granted" } else { "denied" }
+        );
+        if !granted {
+            println!();
+            println!("To fix this:");
+            println!("1. Add your user to the 'input' group:");
+            println!("   sudo usermod -a -G input $USER");
+            println!("2. Log out and back in so the new group takes effect");
+            println!("3. Run 'snipt permissions' again");
+            return Err(SniptError::PermissionDenied(
+                "Input devices can't be read".to_string(),
+            ));
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        println!("Keyboard access: granted (Windows needs no explicit permission)");
+        println!("If expansion doesn't work, check that your antivirus or Windows");
+        println!("Security isn't blocking keyboard monitoring for snipt.");
+    }
+
+    println!("All permissions snipt needs are granted.");
+    Ok(())
+}
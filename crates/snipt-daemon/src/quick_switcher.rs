@@ -0,0 +1,252 @@
+//! Quick switcher: a global hotkey opens `snipt pick` in a terminal window and
+//! the chosen snippet is pasted into the app that had focus before.
+
+use crate::notifications::notify_error;
+use log::error;
+#[cfg(unix)]
+use snipt_core::config::get_picker_passphrase_path;
+use snipt_core::config::get_picker_selection_path;
+#[cfg(unix)]
+use snipt_core::crypto::cached_passphrase;
+use snipt_core::expansion::paste_snippet;
+use snipt_core::models::SnippetEntry;
+use snipt_core::{Result, SniptError};
+#[cfg(unix)]
+use std::ffi::CString;
+use std::fs;
+#[cfg(unix)]
+use std::fs::OpenOptions;
+#[cfg(unix)]
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Set while a picker window is open, so repeated hotkey presses don't stack windows
+static PICKER_OPEN: AtomicBool = AtomicBool::new(false);
+
+/// How long to wait for the user to choose a snippet
+const PICKER_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Time for the window manager to give focus back after the picker closes
+const FOCUS_DELAY: Duration = Duration::from_millis(300);
+
+/// Open the snippet picker in the background. `terminal` is the configured
+/// terminal command; empty uses the platform default.
+pub fn open_picker(snippets: Arc<Mutex<Vec<SnippetEntry>>>, terminal: Vec<String>) {
+    if PICKER_OPEN.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    thread::spawn(move || {
+        if let Err(e) = run_picker(&snippets, &terminal) {
+            error!("Quick switcher failed: {}", e);
+            notify_error("snipt couldn't open the snippet picker", &e.to_string());
+        }
+        PICKER_OPEN.store(false, Ordering::SeqCst);
+    });
+}
+
+fn run_picker(snippets: &Mutex<Vec<SnippetEntry>>, terminal: &[String]) -> Result<()> {
+    let previous_app = snipt_core::expansion::get_frontmost_app();
+    let selection_path = get_picker_selection_path();
+    let _ = fs::remove_file(&selection_path);
+
+    let mut pick_command = vec![
+        std::env::current_exe()?.to_string_lossy().to_string(),
+        "pick".to_string(),
+        "--output".to_string(),
+        selection_path.to_string_lossy().to_string(),
+    ];
+    // Removed when the picker is done, whether or not it read the passphrase
+    let passphrase_pipe = PassphrasePipe::open()?;
+    if let Some(pipe) = &passphrase_pipe {
+        pick_command.push("--passphrase-pipe".to_string());
+        pick_command.push(pipe.path.to_string_lossy().to_string());
+    }
+    let mut command = if terminal.is_empty() {
+        default_terminal_command(&pick_command)
+    } else {
+        let mut command = Command::new(&terminal[0]);
+        command.args(&terminal[1..]).args(&pick_command);
+        command
+    };
+
+    let program = command.get_program().to_string_lossy().to_string();
+    command.status().map_err(|e| {
+        SniptError::Other(format!(
+            "Failed to run '{}': {}. Set \"picker_terminal\" in config.json to a terminal command",
+            program, e
+        ))
+    })?;
+
+    // Some terminals return immediately and run the picker in another
+    // process, so wait for the picker to write its answer
+    let start = Instant::now();
+    let shortcut = loop {
+        if let Ok(content) = fs::read_to_string(&selection_path) {
+            break content;
+        }
+        if start.elapsed() >= PICKER_TIMEOUT {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+    let _ = fs::remove_file(&selection_path);
+
+    let shortcut = shortcut.trim();
+    if shortcut.is_empty() {
+        return Ok(());
+    }
+    let entry = snippets
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|entry| entry.shortcut == shortcut)
+        .cloned();
+    let Some(entry) = entry else {
+        return Ok(());
+    };
+
+    restore_focus(&previous_app);
+    thread::sleep(FOCUS_DELAY);
+    paste_snippet(&entry)
+}
+
+/// Named pipe that hands the daemon's passphrase to `snipt pick`. The picker
+/// runs in a new terminal window, so it can't be given the passphrase on stdin
+/// the way `snipt start` gives it to the daemon, and a pipe never stores it on
+/// disk or exposes it in an environment.
+#[cfg(unix)]
+struct PassphrasePipe {
+    path: PathBuf,
+    // Set once the picker has opened the pipe and been sent the passphrase
+    sent: Arc<Mutex<bool>>,
+}
+
+#[cfg(unix)]
+impl PassphrasePipe {
+    /// Create the pipe and start writing the passphrase to it. None when the
+    /// database isn't encrypted.
+    fn open() -> Result<Option<Self>> {
+        let Some(passphrase) = cached_passphrase() else {
+            return Ok(None);
+        };
+        let path = get_picker_passphrase_path();
+        let _ = fs::remove_file(&path);
+        let c_path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| SniptError::Other(e.to_string()))?;
+        if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        let sent = Arc::new(Mutex::new(false));
+        let writer_path = path.clone();
+        let writer_sent = Arc::clone(&sent);
+        thread::spawn(move || {
+            // Blocks until the picker opens the pipe, or drop unblocks it
+            let Ok(mut pipe) = OpenOptions::new().write(true).open(&writer_path) else {
+                return;
+            };
+            let _ = writeln!(pipe, "{}", passphrase);
+            *writer_sent.lock().unwrap() = true;
+        });
+        Ok(Some(PassphrasePipe { path, sent }))
+    }
+}
+
+#[cfg(unix)]
+impl Drop for PassphrasePipe {
+    fn drop(&mut self) {
+        let sent = self.sent.lock().unwrap();
+        if !*sent {
+            // The picker never read it, so open the reading end to release the
+            // writer thread. While it holds the lock the writer has the pipe
+            // open or is still waiting, so this doesn't block.
+            let _ = fs::File::open(&self.path);
+        }
+        drop(sent);
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Windows has no named pipes in the filesystem, so the picker asks for the
+/// passphrase itself
+#[cfg(not(unix))]
+#[allow(dead_code)]
+struct PassphrasePipe {
+    path: PathBuf,
+}
+
+#[cfg(not(unix))]
+impl PassphrasePipe {
+    fn open() -> Result<Option<Self>> {
+        Ok(None)
+    }
+}
+
+/// Command that runs `args` in a new terminal window
+#[cfg(target_os = "macos")]
+fn default_terminal_command(args: &[String]) -> Command {
+    let script = args
+        .iter()
+        .map(|arg| format!("'{}'", arg.replace('\'', "'\\''")))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let script = format!("{}; exit", script)
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+
+    let mut command = Command::new("osascript");
+    command.args([
+        "-e",
+        &format!("tell application \"Terminal\" to do script \"{}\"", script),
+        "-e",
+        "tell application \"Terminal\" to activate",
+    ]);
+    command
+}
+
+/// Command that runs `args` in a new terminal window
+#[cfg(windows)]
+fn default_terminal_command(args: &[String]) -> Command {
+    // start takes a quoted first argument as the window title, and its
+    // options go before the program
+    let mut command = Command::new("cmd");
+    command.args(["/C", "start", "\"\"", "/WAIT"]).args(args);
+    command
+}
+
+/// Command that runs `args` in a new terminal window
+#[cfg(not(any(target_os = "macos", windows)))]
+fn default_terminal_command(args: &[String]) -> Command {
+    let mut command = Command::new("x-terminal-emulator");
+    command.arg("-e").args(args);
+    command
+}
+
+/// Bring the app that was focused before the picker back to the front. Only
+/// macOS leaves the terminal app focused after its window closes.
+#[cfg(target_os = "macos")]
+fn restore_focus(app_name: &str) {
+    if app_name.is_empty() {
+        return;
+    }
+    let _ = Command::new("osascript")
+        .args([
+            "-e",
+            &format!(
+                "tell application \"{}\" to activate",
+                app_name.replace('"', "\\\"")
+            ),
+        ])
+        .status();
+}
+
+#[cfg(not(target_os = "macos"))]
+fn restore_focus(_app_name: &str) {}
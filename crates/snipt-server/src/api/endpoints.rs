@@ -1,20 +1,96 @@
 use crate::{
-    api::models::{ApiResponse, ApiServerInfo, DaemonStatus},
+    api::models::{
+        ApiResponse, ApiServerInfo, DaemonStatus, ListSnippetsRequest, PageMeta, UsageStat,
+    },
     server::utils::get_api_server_port,
 };
+use snipt_core::expansion::{expand_text, resolve_snippet, split_params};
+use snipt_core::history::usage_counts;
+use snipt_core::metrics::{read_daemon_metrics, render_metrics};
+use snipt_core::storage::shortcut_not_found_message;
 use snipt_core::{
     add_snippet, config::get_db_file_path, delete_snippet, is_daemon_running, load_snippets,
     update_snippet, SnippetEntry,
 };
+use std::time::{SystemTime, UNIX_EPOCH};
 
-/// Get all snippets
-pub fn get_snippets() -> ApiResponse<Vec<SnippetEntry>> {
+/// Page size used when a client doesn't ask for one
+pub const DEFAULT_PAGE_LIMIT: usize = 500;
+
+/// Largest page a client can request
+pub const MAX_PAGE_LIMIT: usize = 5000;
+
+/// Get a page of snippets
+pub fn get_snippets(query: &ListSnippetsRequest) -> ApiResponse<Vec<SnippetEntry>> {
     match load_snippets() {
-        Ok(snippets) => ApiResponse::success(snippets),
+        Ok(snippets) => paginate(snippets, query),
         Err(e) => ApiResponse::error(format!("Failed to load snippets: {}", e)),
     }
 }
 
+/// Cut one page out of the snippet list and describe it in the response metadata
+fn paginate(
+    snippets: Vec<SnippetEntry>,
+    query: &ListSnippetsRequest,
+) -> ApiResponse<Vec<SnippetEntry>> {
+    let total = snippets.len();
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_LIMIT)
+        .min(MAX_PAGE_LIMIT);
+    let page: Vec<SnippetEntry> = snippets
+        .into_iter()
+        .skip(query.offset)
+        .take(limit)
+        .collect();
+
+    // Clients written before paging existed expect everything in one response
+    let warning = (query.limit.is_none() && query.offset + page.len() < total).then(|| {
+        format!(
+            "Only {} of {} snippets returned. Use ?limit= and ?offset= to fetch the rest",
+            page.len(),
+            total
+        )
+    });
+
+    let mut response = ApiResponse::success(page);
+    response.meta = Some(PageMeta {
+        total,
+        offset: query.offset,
+        limit,
+        warning,
+    });
+    response
+}
+
+/// Get expansion counts per shortcut, most used first
+pub fn get_snippet_stats() -> ApiResponse<Vec<UsageStat>> {
+    match usage_counts() {
+        Ok(counts) => ApiResponse::success(
+            counts
+                .into_iter()
+                .map(|(shortcut, count)| UsageStat { shortcut, count })
+                .collect(),
+        ),
+        Err(e) => ApiResponse::error(format!("Failed to load usage stats: {}", e)),
+    }
+}
+
+/// Metrics in the Prometheus text format. Counters of a daemon that is no
+/// longer running are left out.
+pub fn get_metrics() -> String {
+    let daemon = is_daemon_running()
+        .ok()
+        .flatten()
+        .and_then(|_| read_daemon_metrics());
+    let usage = usage_counts().unwrap_or_default();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0);
+    render_metrics(daemon.as_ref(), &usage, now)
+}
+
 /// Get a specific snippet by shortcut
 pub fn get_snippet(shortcut: &str) -> ApiResponse<Option<SnippetEntry>> {
     match load_snippets() {
@@ -26,6 +102,25 @@ pub fn get_snippet(shortcut: &str) -> ApiResponse<Option<SnippetEntry>> {
     }
 }
 
+/// Preview what a snippet expands to, using the same resolution as a real expansion
+pub fn preview_snippet(shortcut: &str, args: Option<&str>) -> ApiResponse<String> {
+    let snippets = match load_snippets() {
+        Ok(snippets) => snippets,
+        Err(e) => return ApiResponse::error(format!("Failed to load snippets: {}", e)),
+    };
+
+    let entry = match resolve_snippet(&snippets, shortcut) {
+        Some(entry) => entry,
+        None => return ApiResponse::error(shortcut_not_found_message(&snippets, shortcut)),
+    };
+
+    let params = args.map(split_params).unwrap_or_default();
+    match expand_text(entry, &params) {
+        Ok(text) => ApiResponse::success(text),
+        Err(e) => ApiResponse::error(format!("Failed to preview snippet: {}", e)),
+    }
+}
+
 /// Add a new snippet
 pub fn add_snippet_handler(shortcut: String, snippet: String) -> ApiResponse<()> {
     match add_snippet(shortcut, snippet) {
@@ -79,3 +174,42 @@ pub fn get_daemon_details(port: u16) -> ApiResponse<DaemonStatus> {
         Err(e) => ApiResponse::error(format!("Failed to check daemon status: {}", e)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paginate() {
+        let snippets: Vec<SnippetEntry> = (0..DEFAULT_PAGE_LIMIT + 10)
+            .map(|i| SnippetEntry::new(format!("s{}", i), "text".to_string()))
+            .collect();
+
+        let query = ListSnippetsRequest {
+            limit: Some(5),
+            offset: 3,
+        };
+        let response = paginate(snippets.clone(), &query);
+        let page = response.data.unwrap();
+        assert_eq!(page.len(), 5);
+        assert_eq!(page[0].shortcut, "s3");
+        let meta = response.meta.unwrap();
+        assert_eq!(
+            (meta.total, meta.offset, meta.limit),
+            (snippets.len(), 3, 5)
+        );
+        assert!(meta.warning.is_none());
+
+        // Without a limit the default page size applies and the cut is reported
+        let response = paginate(snippets.clone(), &ListSnippetsRequest::default());
+        assert_eq!(response.data.unwrap().len(), DEFAULT_PAGE_LIMIT);
+        assert!(response.meta.unwrap().warning.is_some());
+
+        // Past the end is an empty page, not an error
+        let query = ListSnippetsRequest {
+            limit: None,
+            offset: 10_000,
+        };
+        assert!(paginate(snippets, &query).data.unwrap().is_empty());
+    }
+}
@@ -8,6 +8,9 @@ pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<String>,
+    /// Paging details for list endpoints
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<PageMeta>,
 }
 
 impl<T> ApiResponse<T> {
@@ -16,6 +19,7 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            meta: None,
         }
     }
 
@@ -24,10 +28,40 @@ impl<T> ApiResponse<T> {
             success: false,
             data: None,
             error: Some(message),
+            meta: None,
         }
     }
 }
 
+/// Where a page of results sits in the full collection
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct PageMeta {
+    /// Number of items in the full collection
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    /// Set when items were left out because no limit was asked for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// How often a shortcut was expanded
+#[derive(Serialize, Deserialize)]
+pub struct UsageStat {
+    pub shortcut: String,
+    pub count: usize,
+}
+
+/// Query parameters for listing snippets
+#[derive(Deserialize, Default)]
+pub struct ListSnippetsRequest {
+    /// Maximum number of snippets to return (default 500, at most 5000)
+    pub limit: Option<usize>,
+    /// Number of snippets to skip
+    #[serde(default)]
+    pub offset: usize,
+}
+
 /// Daemon status information
 #[derive(Serialize, Deserialize)]
 pub struct DaemonStatus {
@@ -57,6 +91,14 @@ pub struct GetSnippetRequest {
     pub shortcut: String,
 }
 
+/// Query parameters for previewing a snippet expansion
+#[derive(Deserialize)]
+pub struct PreviewRequest {
+    /// Comma separated parameter values, as typed between the parentheses
+    #[serde(default)]
+    pub args: Option<String>,
+}
+
 /// Request model for deleting a snippet
 #[derive(Deserialize)]
 pub struct DeleteSnippetRequest {
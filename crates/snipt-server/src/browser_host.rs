@@ -0,0 +1,290 @@
+//! Native messaging host for the snipt browser extension.
+//!
+//! The browser starts `snipt browser-host` and exchanges JSON messages with it
+//! over stdin/stdout, each preceded by its length as a 32-bit unsigned integer
+//! in native byte order. Requests have a `type` and an optional `id` that is
+//! echoed back:
+//!
+//! - `{"type": "ping"}` answers `{"ok": true}`
+//! - `{"type": "list"}` answers `{"ok": true, "shortcuts": [...]}` with the
+//!   enabled text shortcuts and the current `trigger` character
+//! - `{"type": "expand", "text": ":greet(Ada)"}` answers
+//!   `{"ok": true, "shortcut": "greet(name)", "text": "Hello Ada"}`, plus `html` when
+//!   the snippet has a rich text body
+//!
+//! Failures answer `{"ok": false, "error": "..."}`. The extension inserts the
+//! text itself; execute snippets are never run from the browser.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use snipt_core::expansion::{expand_text, match_trigger_case, resolve_snippet, split_params};
+use snipt_core::history::{append_history, record_usage, HistoryEntry};
+use snipt_core::settings::current_config;
+use snipt_core::storage::shortcut_not_found_message;
+use snipt_core::{
+    execute_char, load_snippets, trigger_char, ExpansionStyle, ExpansionType, Result, SnippetEntry,
+    SniptError,
+};
+use std::io::{self, Read, Write};
+
+/// Largest message browsers accept from a native host
+const MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+/// Requests are small; anything bigger is a broken stream
+const MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+#[derive(Deserialize)]
+struct HostMessage {
+    #[serde(default)]
+    id: Option<Value>,
+    #[serde(flatten)]
+    request: HostRequest,
+}
+
+/// A request from the browser extension
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum HostRequest {
+    Ping,
+    List,
+    /// Expand a trigger as typed, such as `:sig` or `:greet(Ada)`
+    Expand {
+        text: String,
+    },
+}
+
+/// Reply sent back to the browser extension
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct HostResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shortcut: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub html: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<char>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shortcuts: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl HostResponse {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            error: Some(message.into()),
+            ..Self::default()
+        }
+    }
+}
+
+/// Serve requests from the browser until it closes the connection
+pub fn run_browser_host() -> Result<()> {
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+
+    while let Some(message) = read_message(&mut stdin)? {
+        let response = match serde_json::from_slice::<HostMessage>(&message) {
+            Ok(message) => {
+                let mut response = match load_snippets() {
+                    Ok(snippets) => handle_request(&message.request, &snippets),
+                    Err(e) => HostResponse::error(format!("Failed to load snippets: {}", e)),
+                };
+                if response.ok && matches!(message.request, HostRequest::Expand { .. }) {
+                    record_expansion(&response);
+                }
+                response.id = message.id;
+                response
+            }
+            Err(e) => HostResponse::error(format!("Invalid request: {}", e)),
+        };
+        write_message(&mut stdout, &response)?;
+    }
+
+    Ok(())
+}
+
+/// Answer a single request
+pub fn handle_request(request: &HostRequest, snippets: &[SnippetEntry]) -> HostResponse {
+    match request {
+        HostRequest::Ping => HostResponse {
+            ok: true,
+            ..HostResponse::default()
+        },
+        HostRequest::List => HostResponse {
+            ok: true,
+            trigger: Some(trigger_char()),
+            shortcuts: Some(
+                snippets
+                    .iter()
+                    .filter(|entry| entry.enabled)
+                    .map(|entry| entry.shortcut.clone())
+                    .collect(),
+            ),
+            ..HostResponse::default()
+        },
+        HostRequest::Expand { text } => {
+            expand(text.trim(), snippets).unwrap_or_else(HostResponse::error)
+        }
+    }
+}
+
+fn expand(text: &str, snippets: &[SnippetEntry]) -> std::result::Result<HostResponse, String> {
+    let Some(typed) = text.strip_prefix(trigger_char()) else {
+        return Err(if text.starts_with(execute_char()) {
+            "Execute snippets can't be run from the browser".to_string()
+        } else {
+            format!("Expansions start with '{}'", trigger_char())
+        });
+    };
+
+    let (name, params) = match typed.find('(') {
+        Some(open) if typed.ends_with(')') => (
+            &typed[..open],
+            split_params(&typed[open + 1..typed.len() - 1]),
+        ),
+        _ => (typed, Vec::new()),
+    };
+
+    let find = |name: &str| resolve_snippet(snippets, name).filter(|entry| entry.enabled);
+    // With case preserving on, `:Brb` expands `brb` with matching casing
+    let (entry, recase) = match find(name) {
+        Some(entry) => (entry, false),
+        None => match find(&name.to_lowercase()).filter(|_| current_config().case_preserving) {
+            Some(entry) => (entry, true),
+            None => return Err(shortcut_not_found_message(snippets, name)),
+        },
+    };
+
+    let mut expanded = expand_text(entry, &params).map_err(|e| e.to_string())?;
+    if recase && entry.html.is_none() {
+        expanded = match_trigger_case(name, &expanded);
+    }
+
+    Ok(HostResponse {
+        ok: true,
+        shortcut: Some(entry.shortcut.clone()),
+        text: Some(expanded),
+        html: entry.html.clone(),
+        ..HostResponse::default()
+    })
+}
+
+/// Count a browser expansion and record it in the history like the daemon does
+fn record_expansion(response: &HostResponse) {
+    if let Some(shortcut) = &response.shortcut {
+        if let Err(e) = record_usage(shortcut) {
+            eprintln!("Failed to count the expansion: {}", e);
+        }
+    }
+
+    let config = current_config();
+    if !config.history_enabled {
+        return;
+    }
+
+    let expansion = ExpansionType::Text(
+        response.text.clone().unwrap_or_default(),
+        ExpansionStyle::Default,
+        response.shortcut.clone().unwrap_or_default(),
+    );
+    if let Err(e) = append_history(
+        HistoryEntry::for_expansion(&expansion),
+        config.history_limit,
+    ) {
+        eprintln!("Failed to record expansion history: {}", e);
+    }
+}
+
+/// Read one length-prefixed message, or `None` once the browser disconnects
+fn read_message(reader: &mut impl Read) -> Result<Option<Vec<u8>>> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+
+    let length = u32::from_ne_bytes(length) as usize;
+    if length > MAX_REQUEST_BYTES {
+        return Err(SniptError::Other(format!(
+            "Browser message of {} bytes is too large",
+            length
+        )));
+    }
+
+    let mut message = vec![0u8; length];
+    reader.read_exact(&mut message)?;
+    Ok(Some(message))
+}
+
+fn write_message(writer: &mut impl Write, response: &HostResponse) -> Result<()> {
+    let mut message = serde_json::to_vec(response)?;
+    if message.len() > MAX_RESPONSE_BYTES {
+        let mut too_large =
+            HostResponse::error("The expansion is too large to send to the browser");
+        too_large.id = response.id.clone();
+        message = serde_json::to_vec(&too_large)?;
+    }
+
+    writer.write_all(&(message.len() as u32).to_ne_bytes())?;
+    writer.write_all(&message)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_browser_host_messages() {
+        let mut disabled = SnippetEntry::new("off".to_string(), "hidden".to_string());
+        disabled.enabled = false;
+        let snippets = vec![
+            SnippetEntry::new("sig".to_string(), "Best regards".to_string()),
+            SnippetEntry::new("greet(name)".to_string(), "Hello name".to_string()),
+            disabled,
+        ];
+
+        // Requests arrive length-prefixed and may carry an id to echo back
+        let body = br#"{"id": 7, "type": "expand", "text": ":greet(Ada)"}"#;
+        let mut stream = (body.len() as u32).to_ne_bytes().to_vec();
+        stream.extend_from_slice(body);
+        let mut reader = stream.as_slice();
+        let message: HostMessage =
+            serde_json::from_slice(&read_message(&mut reader).unwrap().unwrap()).unwrap();
+        assert_eq!(message.id, Some(Value::from(7)));
+        assert!(read_message(&mut reader).unwrap().is_none());
+
+        let response = handle_request(&message.request, &snippets);
+        assert!(response.ok);
+        assert_eq!(response.shortcut.as_deref(), Some("greet(name)"));
+        assert_eq!(response.text.as_deref(), Some("Hello Ada"));
+
+        let expand = |text: &str| {
+            handle_request(
+                &HostRequest::Expand {
+                    text: text.to_string(),
+                },
+                &snippets,
+            )
+        };
+        assert_eq!(expand(":sig").text.as_deref(), Some("Best regards"));
+        assert!(!expand(":off").ok);
+        assert!(!expand("!sig").ok);
+        assert!(expand(":sgi").error.unwrap().contains("sig"));
+
+        let list = handle_request(&HostRequest::List, &snippets);
+        assert_eq!(list.shortcuts.unwrap(), vec!["sig", "greet(name)"]);
+
+        let mut written = Vec::new();
+        write_message(&mut written, &HostResponse::error("failed")).unwrap();
+        let length = u32::from_ne_bytes(written[..4].try_into().unwrap()) as usize;
+        assert_eq!(&written[4..], br#"{"ok":false,"error":"failed"}"#);
+        assert_eq!(length, written.len() - 4);
+    }
+}
@@ -1,2 +1,3 @@
 pub mod api;
+pub mod browser_host;
 pub mod server;
@@ -2,13 +2,15 @@
 
 use crate::api::{
     add_snippet_handler, delete_snippet_handler, get_daemon_details, get_daemon_status,
-    get_snippet, get_snippets, update_snippet_handler, DeleteSnippetRequest, GetSnippetRequest,
-    SnippetRequest,
+    get_metrics, get_snippet, get_snippet_stats, get_snippets, preview_snippet,
+    update_snippet_handler, DeleteSnippetRequest, GetSnippetRequest, ListSnippetsRequest,
+    PreviewRequest, SnippetRequest,
 };
-use crate::server::utils::{port_is_available, save_api_port};
+use crate::server::utils::{port_is_available, remove_api_port, save_api_port};
 
+use percent_encoding::percent_decode_str;
+use snipt_core::config::get_api_port_file_path;
 use snipt_core::{get_config_dir, is_daemon_running, Result, SniptError};
-use std::fs;
 use std::net::SocketAddr;
 use warp::Filter;
 
@@ -18,9 +20,6 @@ use super::utils::get_api_server_port;
 pub async fn start_api_server(port: u16) -> Result<()> {
     let addr = SocketAddr::from(([127, 0, 0, 1], port));
 
-    // Save the port to file so we can find it later
-    save_api_port(port)?;
-
     println!("┌─────────────────────────────────────────┐");
     println!("│          snipt API Server              │");
     println!("├─────────────────────────────────────────┤");
@@ -38,13 +37,26 @@ pub async fn start_api_server(port: u16) -> Result<()> {
     // API routes
     let get_snippets_route = warp::path!("api" / "snippets")
         .and(warp::get())
-        .map(|| warp::reply::json(&get_snippets()));
+        .and(warp::query::<ListSnippetsRequest>())
+        .map(|query: ListSnippetsRequest| warp::reply::json(&get_snippets(&query)));
+
+    let snippet_stats_route = warp::path!("api" / "snippets" / "stats")
+        .and(warp::get())
+        .map(|| warp::reply::json(&get_snippet_stats()));
 
     let get_snippet_route = warp::path!("api" / "snippet")
         .and(warp::get())
         .and(warp::query::<GetSnippetRequest>())
         .map(|query: GetSnippetRequest| warp::reply::json(&get_snippet(&query.shortcut)));
 
+    let preview_snippet_route = warp::path!("api" / "snippets" / String / "preview")
+        .and(warp::get())
+        .and(warp::query::<PreviewRequest>())
+        .map(|shortcut: String, query: PreviewRequest| {
+            let shortcut = percent_decode_str(&shortcut).decode_utf8_lossy();
+            warp::reply::json(&preview_snippet(&shortcut, query.args.as_deref()))
+        });
+
     let add_snippet_route = warp::path!("api" / "snippets")
         .and(warp::post())
         .and(warp::body::json())
@@ -74,17 +86,25 @@ pub async fn start_api_server(port: u16) -> Result<()> {
         .and(warp::get())
         .map(move || warp::reply::json(&get_daemon_details(port)));
 
+    // Prometheus metrics
+    let metrics_route = warp::path!("metrics").and(warp::get()).map(|| {
+        warp::reply::with_header(get_metrics(), "Content-Type", "text/plain; version=0.0.4")
+    });
+
     // Health check endpoint
     let health_route = warp::path!("health").map(|| "snipt API is running");
 
     // Combine routes
     let routes = get_snippets_route
+        .or(snippet_stats_route)
         .or(get_snippet_route)
+        .or(preview_snippet_route)
         .or(add_snippet_route)
         .or(update_snippet_route)
         .or(delete_snippet_route)
         .or(daemon_status_route)
         .or(daemon_details_route)
+        .or(metrics_route)
         .or(health_route)
         .with(cors);
 
@@ -99,6 +119,9 @@ pub async fn start_api_server(port: u16) -> Result<()> {
         Ok((addr, server)) => {
             println!("API server started successfully on {}", addr);
 
+            // Save the bound port so the daemon and CLI can find it later
+            save_api_port(addr.port())?;
+
             // Actually run the server - this will block until shutdown
             server.await;
             Ok(())
@@ -155,10 +178,7 @@ pub fn stop_api_server() -> Result<()> {
         let _ = std::net::TcpStream::connect(format!("127.0.0.1:{}", port));
 
         // Remove the port file
-        let port_file_path = get_config_dir().join("api_port.txt");
-        if port_file_path.exists() {
-            let _ = fs::remove_file(port_file_path);
-        }
+        let _ = remove_api_port();
 
         println!("API server port file removed.");
 
@@ -199,7 +219,7 @@ pub fn diagnose_api_server() -> Result<()> {
     }
 
     // Check if port file exists
-    let port_file = get_config_dir().join("api_port.txt");
+    let port_file = get_api_port_file_path();
     if port_file.exists() {
         println!("✅ API port file exists at {}", port_file.display());
 
@@ -1,13 +1,18 @@
 //! Utilities for managing the API server.
 
-use snipt_core::{get_config_dir, Result, SniptError};
+use snipt_core::config::get_api_port_file_path;
+use snipt_core::{Result, SniptError};
 use std::fs;
 use std::io::{Read, Write};
+use std::path::Path;
 
 /// Try to get the API server port from stored configuration
 pub fn get_api_server_port() -> Result<u16> {
-    let port_file_path = get_config_dir().join("api_port.txt");
+    read_api_port(&get_api_port_file_path())
+}
 
+/// Read a port previously written with `write_api_port`
+pub fn read_api_port(port_file_path: &Path) -> Result<u16> {
     if port_file_path.exists() {
         let mut file = fs::File::open(port_file_path)?;
         let mut contents = String::new();
@@ -32,18 +37,32 @@ pub fn port_is_available(port: u16) -> bool {
 
 /// Save the API port to a configuration file
 pub fn save_api_port(port: u16) -> Result<()> {
-    let config_dir = get_config_dir();
-    if !config_dir.exists() {
-        fs::create_dir_all(&config_dir)?;
+    write_api_port(&get_api_port_file_path(), port)
+}
+
+/// Write the port to the given file, creating its parent directory if needed
+pub fn write_api_port(port_file_path: &Path, port: u16) -> Result<()> {
+    if let Some(parent) = port_file_path.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent)?;
+        }
     }
 
-    let port_file_path = config_dir.join("api_port.txt");
     let mut file = fs::File::create(port_file_path)?;
     write!(file, "{}", port)?;
 
     Ok(())
 }
 
+/// Remove the stored API port, ignoring a file that is already gone
+pub fn remove_api_port() -> Result<()> {
+    let port_file_path = get_api_port_file_path();
+    if port_file_path.exists() {
+        fs::remove_file(port_file_path)?;
+    }
+    Ok(())
+}
+
 /// Test if a port is available asynchronously
 pub async fn test_port_availability(port: u16) -> bool {
     use std::net::TcpListener;
@@ -51,3 +70,26 @@ pub async fn test_port_availability(port: u16) -> bool {
     // Try to bind to the port to see if it's available
     TcpListener::bind(format!("127.0.0.1:{}", port)).is_ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_port_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let port_file = dir.path().join("nested").join("api_port.txt");
+
+        assert!(read_api_port(&port_file).is_err());
+
+        write_api_port(&port_file, 3042).unwrap();
+        assert_eq!(read_api_port(&port_file).unwrap(), 3042);
+
+        // Overwriting keeps only the latest port
+        write_api_port(&port_file, 3000).unwrap();
+        assert_eq!(read_api_port(&port_file).unwrap(), 3000);
+
+        fs::write(&port_file, "not a port").unwrap();
+        assert!(read_api_port(&port_file).is_err());
+    }
+}
@@ -1,12 +1,20 @@
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Style},
-    widgets::{Block, Borders, Clear, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
     Terminal,
 };
 use snipt_core::Result;
 use std::{thread, time::Duration};
 
+/// Height of a popup showing `lines` lines of text, as a percentage of a
+/// terminal `height` rows tall, counting its borders. Kept between 10% and 80%.
+fn popup_height_percent(lines: usize, height: u16) -> u16 {
+    let rows = lines.saturating_add(2);
+    let percent = rows.saturating_mul(100) / usize::from(height.max(1));
+    percent.clamp(10, 80) as u16
+}
+
 // Helper function to show messages in a popup
 pub fn show_message<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
@@ -17,10 +25,6 @@ pub fn show_message<B: ratatui::backend::Backend>(
     // Draw the message
     terminal.draw(|f| {
         let size = f.size();
-        let area = centered_rect(60, 10, size);
-
-        // Clear the area behind the popup
-        f.render_widget(Clear, area);
 
         // Create the message box - add instructions if it's a wait
         let message_text = if duration_ms == 0 {
@@ -29,10 +33,18 @@ pub fn show_message<B: ratatui::backend::Backend>(
             message.to_string()
         };
 
+        // Grow the popup for longer messages, such as snippet previews
+        let percent_y = popup_height_percent(message_text.lines().count(), size.height);
+        let area = centered_rect(60, percent_y, size);
+
+        // Clear the area behind the popup
+        f.render_widget(Clear, area);
+
         let message_box = Paragraph::new(message_text)
             .style(Style::default().fg(color))
             .block(Block::default().borders(Borders::ALL).title(" snipt "))
-            .alignment(Alignment::Center);
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: false });
 
         f.render_widget(message_box, area);
     })?;
@@ -77,3 +89,18 @@ pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_popup_height_percent() {
+        assert_eq!(popup_height_percent(1, 50), 10);
+        assert_eq!(popup_height_percent(18, 50), 40);
+        assert_eq!(popup_height_percent(100, 50), 80);
+        // Very long messages and tiny terminals don't overflow
+        assert_eq!(popup_height_percent(100_000, 24), 80);
+        assert_eq!(popup_height_percent(usize::MAX, 0), 80);
+    }
+}
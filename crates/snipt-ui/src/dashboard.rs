@@ -15,16 +15,47 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph},
     Terminal,
 };
-use snipt_core::{is_daemon_running, load_snippets, Result};
+use snipt_core::config::is_paused;
+use snipt_core::expansion::text_body;
+use snipt_core::history::usage_counts;
+use snipt_core::storage::favorite_snippets;
+use snipt_core::{is_daemon_running, load_snippets, set_clipboard_text, Result, SnippetEntry};
 use std::io::{self, stdout};
 use std::thread;
 use std::time::Duration;
 
+/// Number of snippets shown in the favorites row
+const FAVORITE_LIMIT: usize = 5;
+
 struct DashboardState {
     daemon_status: Option<u32>,
-    selected_action: usize,
+    selected_action: usize, // Actions first, then favorites
     exiting: bool,
     snippet_count: usize,
+    last_updated: Option<SnippetEntry>, // Most recently changed snippet
+    favorites: Vec<SnippetEntry>,       // Pinned and most used snippets
+}
+
+impl DashboardState {
+    /// Reload the snippet count and favorites after snippets may have changed
+    fn reload_snippets(&mut self) {
+        let snippets = load_snippets().unwrap_or_default();
+        self.snippet_count = snippets.len();
+        self.last_updated = snippets
+            .iter()
+            .max_by_key(|entry| entry.updated_at())
+            .cloned();
+        self.favorites = load_favorites(&snippets);
+    }
+}
+
+/// Pinned snippets followed by the most expanded ones
+fn load_favorites(snippets: &[SnippetEntry]) -> Vec<SnippetEntry> {
+    let usage = usage_counts().unwrap_or_default();
+    favorite_snippets(snippets, &usage, FAVORITE_LIMIT)
+        .into_iter()
+        .cloned()
+        .collect()
 }
 
 /// Display the main snipt dashboard UI
@@ -35,17 +66,16 @@ pub fn display_snipt_dashboard(daemon_status: Option<u32>) -> Result<()> {
     let backend = CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
 
-    // Load snippet count
-    let snippets = load_snippets().unwrap_or_default();
-    let snippet_count = snippets.len();
-
     // Create dashboard state
     let mut dashboard_state = DashboardState {
         daemon_status,
         selected_action: 0,
         exiting: false,
-        snippet_count,
+        snippet_count: 0,
+        last_updated: None,
+        favorites: Vec::new(),
     };
+    dashboard_state.reload_snippets();
 
     let result = run_dashboard(&mut terminal, &mut dashboard_state);
 
@@ -79,7 +109,7 @@ fn run_dashboard(
                 let size = f.size();
 
                 // Create a centered layout with distinct sections
-                let vertical_margin = (size.height.saturating_sub(22)) / 2; // Increased for ASCII art
+                let vertical_margin = (size.height.saturating_sub(26)) / 2; // Increased for ASCII art
                 let main_chunks = Layout::default()
                     .direction(Direction::Vertical)
                     .constraints([
@@ -87,6 +117,7 @@ fn run_dashboard(
                         Constraint::Length(2),               // Title area
                         Constraint::Length(6),               // ASCII art logo
                         Constraint::Length(12),              // Main content area
+                        Constraint::Length(4),               // Favorites row
                         Constraint::Length(4),               // Help area
                         Constraint::Min(0),                  // Bottom centering space
                     ])
@@ -205,8 +236,29 @@ fn run_dashboard(
                 let header_paragraph = Paragraph::new(status_header).alignment(Alignment::Center);
                 f.render_widget(header_paragraph, status_layout[0]);
 
+                // Snippet database summary, shown whatever the daemon state
+                let data_lines = vec![
+                    Line::from(vec![
+                        Span::styled("Snippets:       ", Style::default().fg(detail_color)),
+                        Span::styled(
+                            format!("{} stored", state.snippet_count),
+                            Style::default().fg(text_color),
+                        ),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Last updated:   ", Style::default().fg(detail_color)),
+                        match &state.last_updated {
+                            Some(entry) => Span::styled(
+                                format!("{} ({})", entry.formatted_time(), entry.shortcut),
+                                Style::default().fg(text_color),
+                            ),
+                            None => Span::styled("never", Style::default().fg(Color::DarkGray)),
+                        },
+                    ]),
+                ];
+
                 // Enhanced status details with premium styling
-                let status_text = match state.daemon_status {
+                let mut status_text = match state.daemon_status {
                     Some(pid) => {
                         vec![
                             Line::from(vec![
@@ -215,7 +267,11 @@ fn run_dashboard(
                             ]),
                             Line::from(vec![
                                 Span::styled("Text expansion: ", Style::default().fg(detail_color)),
-                                Span::styled("Active", Style::default().fg(success_color)),
+                                if is_paused() {
+                                    Span::styled("Paused", Style::default().fg(secondary_color))
+                                } else {
+                                    Span::styled("Active", Style::default().fg(success_color))
+                                },
                             ]),
                             Line::from(vec![
                                 Span::styled("Trigger method: ", Style::default().fg(detail_color)),
@@ -266,6 +322,10 @@ fn run_dashboard(
                     }
                 };
 
+                // Keep the control command hint last
+                let hint_at = status_text.len().saturating_sub(2);
+                status_text.splice(hint_at..hint_at, data_lines);
+
                 let status_paragraph = Paragraph::new(status_text).alignment(Alignment::Left);
                 f.render_widget(status_paragraph, status_layout[1]);
 
@@ -365,6 +425,72 @@ fn run_dashboard(
                     f.render_widget(button_paragraph, inner_button);
                 }
 
+                // Favorites row with one-key copy
+                let favorites_block = Block::default()
+                    .title(" Favorites ")
+                    .title_alignment(Alignment::Center)
+                    .title_style(Style::default().fg(secondary_color))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(primary_color));
+
+                let inner_favorites = favorites_block.inner(main_chunks[4]);
+                f.render_widget(favorites_block, main_chunks[4]);
+
+                let selected_favorite = state.selected_action.checked_sub(actions.len());
+                let favorites_text = if state.favorites.is_empty() {
+                    vec![
+                        Line::from(Span::styled(
+                            "No favorites yet",
+                            Style::default().fg(detail_color),
+                        )),
+                        Line::from(Span::styled(
+                            "Pin snippets in the manager or expand them to list them here",
+                            Style::default().fg(Color::DarkGray),
+                        )),
+                    ]
+                } else {
+                    let mut chips = Vec::new();
+                    for (i, entry) in state.favorites.iter().enumerate() {
+                        let is_selected = selected_favorite == Some(i);
+                        if i > 0 {
+                            chips.push(Span::raw("   "));
+                        }
+                        chips.push(Span::styled(
+                            format!("[{}]", i + 1),
+                            Style::default().fg(secondary_color),
+                        ));
+                        chips.push(Span::styled(
+                            format!(" {}", entry.shortcut),
+                            if is_selected {
+                                Style::default()
+                                    .fg(secondary_color)
+                                    .bg(dark_bg)
+                                    .add_modifier(Modifier::BOLD)
+                            } else {
+                                Style::default().fg(text_color)
+                            },
+                        ));
+                    }
+
+                    // Preview the selected favorite, or explain the shortcut keys
+                    let detail = match selected_favorite.and_then(|i| state.favorites.get(i)) {
+                        Some(entry) => preview_line(&entry.snippet),
+                        None => format!(
+                            "Press [1-{}] to copy a favorite to the clipboard",
+                            state.favorites.len()
+                        ),
+                    };
+
+                    vec![
+                        Line::from(chips),
+                        Line::from(Span::styled(detail, Style::default().fg(Color::DarkGray))),
+                    ]
+                };
+
+                let favorites_paragraph =
+                    Paragraph::new(favorites_text).alignment(Alignment::Center);
+                f.render_widget(favorites_paragraph, inner_favorites);
+
                 // Help section with premium styling
                 let help_block = Block::default()
                     .title(" Help & Tips ")
@@ -374,8 +500,8 @@ fn run_dashboard(
                     .border_style(Style::default().fg(primary_color));
 
                 // Get inner area before rendering the block
-                let inner_help = help_block.inner(main_chunks[4]);
-                f.render_widget(help_block, main_chunks[4]);
+                let inner_help = help_block.inner(main_chunks[5]);
+                f.render_widget(help_block, main_chunks[5]);
 
                 let help_text = vec![
                     Line::from(vec![
@@ -389,6 +515,8 @@ fn run_dashboard(
                         Span::raw(" select  "),
                         Span::styled("[Enter]", Style::default().fg(secondary_color)),
                         Span::raw(" choose  "),
+                        Span::styled("[1-5]", Style::default().fg(secondary_color)),
+                        Span::raw(" copy favorite  "),
                         Span::styled("[Esc/q]", Style::default().fg(secondary_color)),
                         Span::raw(" exit"),
                     ]),
@@ -427,7 +555,7 @@ fn run_dashboard(
                         }
                     }
                     KeyCode::Down => {
-                        if state.selected_action < actions.len() - 1 {
+                        if state.selected_action < actions.len() + state.favorites.len() - 1 {
                             state.selected_action += 1;
                             force_render = true;
                         }
@@ -441,7 +569,7 @@ fn run_dashboard(
                                 execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
 
                                 // Run the snippet manager
-                                let result = display_snippet_manager();
+                                let result = display_snippet_manager(None);
 
                                 // Restore TUI
                                 enable_raw_mode()?;
@@ -460,8 +588,7 @@ fn run_dashboard(
 
                                 // Update state information
                                 state.daemon_status = is_daemon_running()?;
-                                let snippets = load_snippets().unwrap_or_default();
-                                state.snippet_count = snippets.len();
+                                state.reload_snippets();
                             }
                             1 => {
                                 // Add New Snippet
@@ -470,7 +597,7 @@ fn run_dashboard(
 
                                 // Run the add snippet editor
                                 match interactive_add() {
-                                    AddResult::Added => {
+                                    AddResult::Added(_) => {
                                         // Success - show message
                                         enable_raw_mode()?;
                                         execute!(terminal.backend_mut(), EnterAlternateScreen)?;
@@ -483,8 +610,7 @@ fn run_dashboard(
 
                                         // Update state information
                                         state.daemon_status = is_daemon_running()?;
-                                        let snippets = load_snippets().unwrap_or_default();
-                                        state.snippet_count = snippets.len();
+                                        state.reload_snippets();
 
                                         // Exit this process
                                         return Ok(());
@@ -515,8 +641,7 @@ fn run_dashboard(
 
                                         // Update state information
                                         state.daemon_status = is_daemon_running()?;
-                                        let snippets = load_snippets().unwrap_or_default();
-                                        state.snippet_count = snippets.len();
+                                        state.reload_snippets();
                                     }
                                     AddResult::Error(e) => {
                                         // Error - restore dashboard with error message
@@ -536,7 +661,18 @@ fn run_dashboard(
                                 state.daemon_status = is_daemon_running()?;
                                 force_render = true;
                             }
-                            _ => {}
+                            selected => {
+                                // Favorite
+                                copy_favorite(terminal, state, selected - actions.len())?;
+                            }
+                        }
+                    }
+                    KeyCode::Char(c @ '1'..='9') => {
+                        let index = c as usize - '1' as usize;
+                        if index < state.favorites.len() {
+                            state.selected_action = actions.len() + index;
+                            copy_favorite(terminal, state, index)?;
+                            force_render = true;
                         }
                     }
                     KeyCode::Char('q') => {
@@ -554,6 +690,39 @@ fn run_dashboard(
     Ok(())
 }
 
+/// Copy a favorite's text to the clipboard and confirm it in a popup
+fn copy_favorite<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    state: &DashboardState,
+    index: usize,
+) -> Result<()> {
+    let Some(entry) = state.favorites.get(index) else {
+        return Ok(());
+    };
+
+    match set_clipboard_text(&text_body(entry)) {
+        Ok(()) => show_message(
+            terminal,
+            &format!("Copied '{}' to clipboard", entry.shortcut),
+            Color::Green,
+            1000,
+        ),
+        Err(e) => show_message(terminal, &format!("Error: {}", e), Color::Red, 2000),
+    }
+}
+
+/// First line of a snippet, shortened to fit the favorites row
+fn preview_line(snippet: &str) -> String {
+    const MAX_CHARS: usize = 60;
+    let first_line = snippet.lines().next().unwrap_or("");
+    if first_line.chars().count() > MAX_CHARS || snippet.lines().nth(1).is_some() {
+        let shortened: String = first_line.chars().take(MAX_CHARS).collect();
+        format!("{}…", shortened)
+    } else {
+        first_line.to_string()
+    }
+}
+
 // Helper function to show messages in a popup
 fn show_message<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
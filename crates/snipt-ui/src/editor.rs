@@ -1,11 +1,17 @@
 use crossterm::{
     cursor,
-    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    event::{
+        self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent, KeyModifiers,
+    },
     execute,
     style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
     terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use snipt_core::{add_snippet, Result, SniptError};
+use snipt_core::settings::{EDITOR_MAX_LINES_CEILING, EDITOR_MAX_LINE_LENGTH_CEILING};
+use snipt_core::{
+    add_snippet, execute_char, load_config, load_snippets, trigger_char, Result, SnippetEntry,
+    SniptError,
+};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 use std::{
@@ -14,9 +20,28 @@ use std::{
 };
 
 // Constants
-const MAX_LINES: usize = 10000;
-const MAX_LINE_LENGTH: usize = 5000;
 const MAX_SHORTCUT_LENGTH: usize = 50;
+const LINE_LIMIT_REACHED: &str = "Line limit reached (editor_max_lines)";
+const LENGTH_LIMIT_REACHED: &str = "Line length limit reached (editor_max_line_length)";
+
+/// How long a snippet written in the editor may get, from the config
+#[derive(Clone, Copy)]
+struct EditorLimits {
+    max_lines: usize,
+    max_line_length: usize,
+}
+
+impl EditorLimits {
+    fn load() -> Self {
+        let config = load_config().unwrap_or_default();
+        Self {
+            max_lines: config.editor_max_lines.clamp(1, EDITOR_MAX_LINES_CEILING),
+            max_line_length: config
+                .editor_max_line_length
+                .clamp(1, EDITOR_MAX_LINE_LENGTH_CEILING),
+        }
+    }
+}
 
 #[derive(PartialEq, Copy, Clone)]
 enum EditorMode {
@@ -26,7 +51,8 @@ enum EditorMode {
 }
 
 pub enum AddResult {
-    Added,
+    /// A snippet was added under this shortcut
+    Added(String),
     Cancelled,
     Error(SniptError),
 }
@@ -41,7 +67,7 @@ pub fn interactive_add() -> AddResult {
     }
 
     let mut stdout = stdout();
-    if let Err(e) = execute!(stdout, EnterAlternateScreen) {
+    if let Err(e) = execute!(stdout, EnterAlternateScreen, EnableBracketedPaste) {
         terminal::disable_raw_mode().ok();
         return AddResult::Error(SniptError::Other(format!(
             "Failed to enter alternate screen: {}",
@@ -53,17 +79,18 @@ pub fn interactive_add() -> AddResult {
     let result = run_interactive_ui(&mut stdout);
 
     // Cleanup terminal
-    let _ = execute!(stdout, LeaveAlternateScreen);
+    let _ = execute!(stdout, DisableBracketedPaste, LeaveAlternateScreen);
     let _ = terminal::disable_raw_mode();
 
     match result {
-        Ok(true) => AddResult::Added,
-        Ok(false) => AddResult::Cancelled,
+        Ok(Some(shortcut)) => AddResult::Added(shortcut),
+        Ok(None) => AddResult::Cancelled,
         Err(e) => AddResult::Error(e),
     }
 }
 
-fn run_interactive_ui(stdout: &mut io::Stdout) -> Result<bool> {
+/// Run the add form, returning the shortcut of the added snippet or `None` if cancelled
+fn run_interactive_ui(stdout: &mut io::Stdout) -> Result<Option<String>> {
     let mut shortcut = String::new();
     let mut snippet = Vec::new();
     snippet.push(String::new());
@@ -76,6 +103,14 @@ fn run_interactive_ui(stdout: &mut io::Stdout) -> Result<bool> {
 
     // For paste handling
     let mut paste_buffer = String::new();
+    let mut dedent_paste = false;
+
+    // Show spaces and tabs in the snippet field, toggled with Ctrl+e
+    let mut show_whitespace = false;
+
+    // Shown when a key was ignored because the snippet is at a limit
+    let limits = EditorLimits::load();
+    let mut limit_notice: Option<&'static str> = None;
 
     // For performance optimization
     let mut last_render = Instant::now();
@@ -90,6 +125,9 @@ fn run_interactive_ui(stdout: &mut io::Stdout) -> Result<bool> {
         cursor_pos,
         current_line,
         editor_mode,
+        dedent_paste,
+        show_whitespace,
+        limit_notice,
         error_message.as_deref(),
     ) {
         error_message = Some(format!("UI Error: {}. Using minimal mode.", e));
@@ -110,6 +148,9 @@ fn run_interactive_ui(stdout: &mut io::Stdout) -> Result<bool> {
                 cursor_pos,
                 current_line,
                 editor_mode,
+                dedent_paste,
+                show_whitespace,
+                limit_notice,
                 error_message.as_deref(),
             ) {
                 // Try minimal UI if main UI fails
@@ -157,28 +198,44 @@ fn run_interactive_ui(stdout: &mut io::Stdout) -> Result<bool> {
                     // Only force render when the state actually changes
                     let mut state_changed = false;
 
+                    // A limit notice stays up until the next key
+                    if limit_notice.take().is_some() {
+                        state_changed = true;
+                    }
+
                     // Special handling for paste mode
                     if editor_mode == EditorMode::Paste {
                         match code {
                             KeyCode::Esc => {
                                 // Cancel paste operation
                                 paste_buffer.clear();
-                                return Ok(false);
+                                return Ok(None);
                             }
                             KeyCode::Enter => {
                                 // Process paste buffer
                                 if !paste_buffer.is_empty() {
-                                    process_paste_buffer(
+                                    let pasted = if dedent_paste {
+                                        dedent(&paste_buffer)
+                                    } else {
+                                        paste_buffer.clone()
+                                    };
+                                    limit_notice = process_paste_buffer(
                                         &mut snippet,
                                         &mut current_line,
                                         &mut cursor_pos,
-                                        &paste_buffer,
+                                        &pasted,
+                                        &limits,
                                     );
                                     paste_buffer.clear();
                                 }
                                 editor_mode = EditorMode::Insert;
                                 state_changed = true;
                             }
+                            KeyCode::Char('t') if modifiers.contains(KeyModifiers::CONTROL) => {
+                                // Toggle between raw and dedented paste
+                                dedent_paste = !dedent_paste;
+                                state_changed = true;
+                            }
                             KeyCode::Char(c) => {
                                 // Add to paste buffer
                                 paste_buffer.push(c);
@@ -227,11 +284,11 @@ fn run_interactive_ui(stdout: &mut io::Stdout) -> Result<bool> {
                             // Check for empty fields and return false to indicate cancel
                             if shortcut.is_empty() && (snippet.len() == 1 && snippet[0].is_empty())
                             {
-                                return Ok(false);
+                                return Ok(None);
                             }
 
                             if editor_mode == EditorMode::Normal {
-                                return Ok(snippet_added);
+                                return Ok(None);
                             } else {
                                 editor_mode = EditorMode::Normal;
                                 state_changed = true;
@@ -243,6 +300,10 @@ fn run_interactive_ui(stdout: &mut io::Stdout) -> Result<bool> {
                             paste_buffer.clear();
                             state_changed = true;
                         }
+                        KeyCode::Char('e') if modifiers.contains(KeyModifiers::CONTROL) => {
+                            show_whitespace = !show_whitespace;
+                            state_changed = true;
+                        }
 
                         _ => {
                             if current_field == 0 {
@@ -275,6 +336,8 @@ fn run_interactive_ui(stdout: &mut io::Stdout) -> Result<bool> {
                                             stdout,
                                             &shortcut,
                                             &mut snippet_added,
+                                            &limits,
+                                            &mut limit_notice,
                                         )? {
                                             state_changed = true;
                                         }
@@ -290,6 +353,8 @@ fn run_interactive_ui(stdout: &mut io::Stdout) -> Result<bool> {
                                             stdout,
                                             &shortcut,
                                             &mut snippet_added,
+                                            &limits,
+                                            &mut limit_notice,
                                         )? {
                                             state_changed = true;
                                         }
@@ -305,6 +370,34 @@ fn run_interactive_ui(stdout: &mut io::Stdout) -> Result<bool> {
                         force_render = true;
                     }
                 }
+                Ok(Event::Paste(text)) => {
+                    // Bracketed paste delivers the whole block at once, newlines included
+                    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+                    if editor_mode == EditorMode::Paste {
+                        // Wait for Enter as with a typed-in paste
+                        paste_buffer.push_str(&text);
+                    } else if current_field == 0 {
+                        // Shortcuts are a single line
+                        for c in text.lines().next().unwrap_or("").trim().chars() {
+                            handle_shortcut_input(
+                                &mut shortcut,
+                                &mut cursor_pos,
+                                KeyCode::Char(c),
+                                KeyModifiers::NONE,
+                            )?;
+                        }
+                    } else {
+                        let pasted = if dedent_paste { dedent(&text) } else { text };
+                        limit_notice = process_paste_buffer(
+                            &mut snippet,
+                            &mut current_line,
+                            &mut cursor_pos,
+                            &pasted,
+                            &limits,
+                        );
+                    }
+                    force_render = true;
+                }
                 Err(e) => {
                     error_message = Some(format!("Input error: {}. Press any key to continue.", e));
                     thread_sleep(1000);
@@ -318,63 +411,95 @@ fn run_interactive_ui(stdout: &mut io::Stdout) -> Result<bool> {
         }
 
         if snippet_added {
-            return Ok(true);
+            return Ok(Some(shortcut));
         }
     }
 }
 
+/// Strip the leading whitespace shared by all non-blank lines, so pasted
+/// code lands flush with the left margin
+fn dedent(text: &str) -> String {
+    let common_indent = text
+        .split('\n')
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .reduce(|common, indent| {
+            let shared = common
+                .char_indices()
+                .zip(indent.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(common.len().min(indent.len()), |((i, _), _)| i);
+            &common[..shared]
+        })
+        .unwrap_or("");
+
+    text.split('\n')
+        .map(|line| {
+            line.strip_prefix(common_indent)
+                .unwrap_or(line.trim_start())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 // Process a paste buffer efficiently by handling it all at once
+/// Insert pasted text at the cursor, returning a notice if lines past the
+/// line limit were dropped or pasted lines were cut at the line length limit.
+/// The text after the cursor always stays after the pasted text.
 fn process_paste_buffer(
     snippet: &mut Vec<String>,
     current_line: &mut usize,
     cursor_pos: &mut usize,
     buffer: &str,
-) {
-    // Split the paste buffer by lines
-    let lines: Vec<&str> = buffer.split('\n').collect();
-
-    if lines.is_empty() {
-        return;
+    limits: &EditorLimits,
+) -> Option<&'static str> {
+    let mut notice = None;
+    let mut lines: Vec<&str> = buffer.split('\n').collect();
+
+    // Split the current line at the cursor
+    let current = &snippet[*current_line];
+    let split = current
+        .char_indices()
+        .nth(*cursor_pos)
+        .map_or(current.len(), |(i, _)| i);
+    let before = current[..split].to_string();
+    let after = current[split..].to_string();
+
+    // Drop the lines that don't fit under the line limit
+    let room = limits.max_lines.saturating_sub(snippet.len()) + 1;
+    if lines.len() > room {
+        lines.truncate(room);
+        notice = Some(LINE_LIMIT_REACHED);
     }
 
-    // Clone the current line to avoid borrowing issues
-    let current = snippet[*current_line].clone();
-
-    // Split at cursor position
-    let before = &current[..(*cursor_pos).min(current.len())];
-    let after = &current[(*cursor_pos).min(current.len())..];
-
-    // Replace current line with first part + first line of paste
-    snippet[*current_line] = format!("{}{}", before, lines[0]);
-    *cursor_pos = before.len() + lines[0].len();
-
-    // Insert the rest of the lines
-    for (i, &line) in lines.iter().enumerate().skip(1) {
-        if snippet.len() >= MAX_LINES {
-            break;
-        }
-
-        let insertion_index = *current_line + i;
-
-        // For the last line of paste, append the remainder of the original line
-        if i == lines.len() - 1 {
-            let combined_line = format!("{}{}", line, after);
-            if insertion_index < snippet.len() {
-                snippet.insert(insertion_index, combined_line);
-            } else {
-                snippet.push(combined_line);
+    // Cut each pasted line so the line it ends up on fits, as typing does
+    let last = lines.len() - 1;
+    let mut new_lines: Vec<String> = Vec::with_capacity(lines.len());
+    for (i, line) in lines.into_iter().enumerate() {
+        let prefix = if i == 0 { before.chars().count() } else { 0 };
+        let suffix = if i == last { after.chars().count() } else { 0 };
+        let room = limits.max_line_length.saturating_sub(prefix + suffix);
+        let line = match line.char_indices().nth(room) {
+            Some((cut, _)) => {
+                notice = notice.or(Some(LENGTH_LIMIT_REACHED));
+                &line[..cut]
             }
-        } else if insertion_index < snippet.len() {
-            snippet.insert(insertion_index, line.to_string());
-        } else {
-            snippet.push(line.to_string());
-        }
+            None => line,
+        };
+        new_lines.push(line.to_string());
     }
 
-    // Update current line position
-    if lines.len() > 1 {
-        *current_line += lines.len() - 1;
+    // The cursor ends up after the pasted text, before the rest of the line
+    *cursor_pos = new_lines[last].chars().count();
+    new_lines[0].insert_str(0, &before);
+    if last == 0 {
+        *cursor_pos += before.chars().count();
     }
+    new_lines[last].push_str(&after);
+
+    snippet.splice(*current_line..=*current_line, new_lines);
+    *current_line += last;
+    notice
 }
 
 // Handle shortcut field input
@@ -470,6 +595,8 @@ fn handle_normal_mode(
     stdout: &mut io::Stdout,
     shortcut: &str,
     snippet_added: &mut bool,
+    limits: &EditorLimits,
+    limit_notice: &mut Option<&'static str>,
 ) -> Result<bool> {
     let mut state_changed = false;
     match code {
@@ -492,20 +619,24 @@ fn handle_normal_mode(
             state_changed = true;
         }
         KeyCode::Char('o') => {
-            if snippet.len() < MAX_LINES {
+            if snippet.len() < limits.max_lines {
                 snippet.insert(*current_line + 1, String::new());
                 *current_line += 1;
                 *cursor_pos = 0;
                 *editor_mode = EditorMode::Insert;
                 state_changed = true;
+            } else {
+                *limit_notice = Some(LINE_LIMIT_REACHED);
             }
         }
         KeyCode::Char('O') => {
-            if snippet.len() < MAX_LINES {
+            if snippet.len() < limits.max_lines {
                 snippet.insert(*current_line, String::new());
                 *cursor_pos = 0;
                 *editor_mode = EditorMode::Insert;
                 state_changed = true;
+            } else {
+                *limit_notice = Some(LINE_LIMIT_REACHED);
             }
         }
         KeyCode::Char('h') => {
@@ -592,6 +723,8 @@ fn handle_insert_mode(
     stdout: &mut io::Stdout,
     shortcut: &str,
     snippet_added: &mut bool,
+    limits: &EditorLimits,
+    limit_notice: &mut Option<&'static str>,
 ) -> Result<bool> {
     let mut state_changed = false;
     match code {
@@ -605,7 +738,9 @@ fn handle_insert_mode(
             state_changed = true;
         }
         KeyCode::Enter => {
-            if snippet.len() < MAX_LINES {
+            if snippet.len() >= limits.max_lines {
+                *limit_notice = Some(LINE_LIMIT_REACHED);
+            } else {
                 // Create a new line by splitting at cursor
                 let current = &snippet[*current_line];
 
@@ -718,7 +853,9 @@ fn handle_insert_mode(
         }
         KeyCode::Tab => {
             // Insert 4 spaces for tab
-            if snippet[*current_line].len() < MAX_LINE_LENGTH - 4 {
+            if snippet[*current_line].chars().count() + 4 > limits.max_line_length {
+                *limit_notice = Some(LENGTH_LIMIT_REACHED);
+            } else {
                 for _ in 0..4 {
                     // Convert to chars
                     let mut chars: Vec<char> = snippet[*current_line].chars().collect();
@@ -735,7 +872,9 @@ fn handle_insert_mode(
             }
         }
         KeyCode::Char(c) => {
-            if snippet[*current_line].len() < MAX_LINE_LENGTH {
+            if snippet[*current_line].chars().count() >= limits.max_line_length {
+                *limit_notice = Some(LENGTH_LIMIT_REACHED);
+            } else {
                 // Convert string to chars for proper UTF-8 handling
                 let mut chars: Vec<char> = snippet[*current_line].chars().collect();
                 let cursor_char_pos = (*cursor_pos).min(chars.len());
@@ -755,6 +894,37 @@ fn handle_insert_mode(
     Ok(state_changed)
 }
 
+/// Explain why a shortcut can't be matched while typing, if it can't
+fn invalid_shortcut_reason(shortcut: &str) -> Option<String> {
+    if shortcut.chars().any(char::is_whitespace) {
+        return Some("Shortcut can't contain spaces or other whitespace".to_string());
+    }
+
+    for c in [trigger_char(), execute_char()] {
+        if shortcut.contains(c) {
+            return Some(format!(
+                "Shortcut can't contain the trigger character '{}'",
+                c
+            ));
+        }
+    }
+
+    None
+}
+
+/// Find an existing shortcut that is a prefix of the new one or prefixed by it
+fn overlapping_shortcut(shortcut: &str) -> Option<String> {
+    overlapping_shortcut_in(&load_snippets().ok()?, shortcut)
+}
+
+fn overlapping_shortcut_in(snippets: &[SnippetEntry], shortcut: &str) -> Option<String> {
+    snippets.iter().find_map(|entry| {
+        let overlaps = entry.shortcut != shortcut
+            && (entry.shortcut.starts_with(shortcut) || shortcut.starts_with(&entry.shortcut));
+        overlaps.then(|| entry.shortcut.clone())
+    })
+}
+
 // Helper to submit a snippet
 fn submit_snippet(stdout: &mut io::Stdout, shortcut: &str, snippet: &[String]) -> Result<bool> {
     if shortcut.is_empty() || snippet.is_empty() || snippet[0].is_empty() {
@@ -763,6 +933,24 @@ fn submit_snippet(stdout: &mut io::Stdout, shortcut: &str, snippet: &[String]) -
         return Ok(false); // Return false for "not completed"
     }
 
+    if let Some(problem) = invalid_shortcut_reason(shortcut) {
+        show_error_message(stdout, &problem)?;
+        thread_sleep(1500);
+        return Ok(false);
+    }
+
+    // Overlapping shortcuts still work, but the shorter one may expand first
+    if let Some(existing) = overlapping_shortcut(shortcut) {
+        show_warning_message(
+            stdout,
+            &format!(
+                "'{}' overlaps with existing shortcut '{}' and may be shadowed",
+                shortcut, existing
+            ),
+        )?;
+        thread_sleep(2000);
+    }
+
     // Join the lines with newlines
     let full_snippet = snippet.join("\n");
     match add_snippet(shortcut.to_string(), full_snippet) {
@@ -789,6 +977,9 @@ fn draw_ui(
     cursor_pos: usize,
     current_line: usize,
     editor_mode: EditorMode,
+    dedent_paste: bool,
+    show_whitespace: bool,
+    limit_notice: Option<&str>,
     error_msg: Option<&str>,
 ) -> Result<()> {
     // Use a static AtomicBool for tracking first draw
@@ -835,6 +1026,7 @@ fn draw_ui(
 
     // Calculate title based on current mode
     let title = match editor_mode {
+        EditorMode::Paste if dedent_paste => " ✏️  Paste Mode (dedent) - Enter to confirm ",
         EditorMode::Paste => " ✏️  Paste Mode - Enter to confirm ",
         EditorMode::Normal => " ✏️  Add New Snippet - Normal Mode ",
         EditorMode::Insert => " ✏️  Add New Snippet - Insert Mode ",
@@ -956,16 +1148,47 @@ fn draw_ui(
         start_y + 8,
         panel_width - 6,
         panel_height - 14, // Adjust for better proportions
-        "Snippet:",
+        if show_whitespace {
+            "Snippet (· space, → tab):"
+        } else {
+            "Snippet:"
+        },
         snippet,
         current_field == 1,
         current_line,
+        show_whitespace,
     ) {
         return Err(SniptError::Other(format!(
             "Failed to draw snippet field: {}",
             e
         )));
     }
+
+    // Live length of the snippet, right-aligned on the label row
+    let char_count = snippet
+        .iter()
+        .map(|line| line.chars().count())
+        .sum::<usize>()
+        + snippet.len().saturating_sub(1);
+    let counts = format!(
+        "{:>24}",
+        format!("{} chars · {} lines", char_count, snippet.len())
+    );
+    if let Err(e) = execute!(
+        stdout,
+        cursor::MoveTo(
+            start_x + panel_width - 3 - counts.chars().count() as u16,
+            start_y + 8
+        ),
+        SetForegroundColor(Color::DarkGrey),
+        Print(counts),
+        ResetColor
+    ) {
+        return Err(SniptError::Other(format!(
+            "Failed to draw snippet length: {}",
+            e
+        )));
+    }
     let help_text = match editor_mode {
         EditorMode::Normal => {
             "i/a: Insert | o/O: New line | h/j/k/l: Navigate | Ctrl+d: Delete line | Enter: Submit"
@@ -974,10 +1197,13 @@ fn draw_ui(
             if current_field == 0 {
                 "Tab: Next field | Enter: Next field | Esc: Cancel"
             } else {
-                "Esc: Normal mode | Enter: New line | Arrows: Navigate | Ctrl+v: Paste | Ctrl+w: Submit"
+                "Esc: Normal mode | Enter: New line | Arrows: Navigate | Ctrl+v: Paste | Ctrl+e: Whitespace | Ctrl+w: Submit"
             }
         }
-        EditorMode::Paste => "Enter: Confirm paste | Esc: Cancel | Type or paste text",
+        EditorMode::Paste if dedent_paste => {
+            "Enter: Confirm paste | Ctrl+t: Keep indentation | Esc: Cancel"
+        }
+        EditorMode::Paste => "Enter: Confirm paste | Ctrl+t: Strip indentation | Esc: Cancel",
     };
 
     // Add a distinctive button-like bottom bar for key actions
@@ -1075,6 +1301,20 @@ fn draw_ui(
             // If we can't even print the error, just log it and continue
             eprintln!("Failed to show error: {}", e);
         }
+    } else if let Some(notice) = limit_notice {
+        if let Err(e) = execute!(
+            stdout,
+            cursor::MoveTo(start_x + 2, start_y + panel_height),
+            SetForegroundColor(Color::Black),
+            SetBackgroundColor(Color::Yellow),
+            Print(format!(" {} ", notice)),
+            ResetColor
+        ) {
+            return Err(SniptError::Other(format!(
+                "Failed to draw limit notice: {}",
+                e
+            )));
+        }
     }
 
     // Position cursor - with robust error handling
@@ -1225,6 +1465,7 @@ fn draw_multiline_field(
     lines: &[String],
     active: bool,
     current_line: usize,
+    show_whitespace: bool,
 ) -> Result<()> {
     // Draw label
     if let Err(e) = execute!(
@@ -1298,7 +1539,12 @@ fn draw_multiline_field(
         let line = &lines[line_idx];
 
         // Safely process line for display
-        let visible_text = safe_truncate_string(line, width as usize - 4, true);
+        let shown = if show_whitespace {
+            whitespace_glyphs(line)
+        } else {
+            line.clone()
+        };
+        let visible_text = safe_truncate_string(&shown, width as usize - 4, true);
 
         let is_current = line_idx == current_line && active;
         let line_bg = if is_current { bg_color } else { Color::Black };
@@ -1366,6 +1612,18 @@ fn draw_multiline_field(
     Ok(())
 }
 
+/// Show spaces as `·` and tabs as `→`, one glyph per character so the
+/// cursor column still lines up. Only used for display.
+fn whitespace_glyphs(line: &str) -> String {
+    line.chars()
+        .map(|c| match c {
+            ' ' => '·',
+            '\t' => '→',
+            c => c,
+        })
+        .collect()
+}
+
 // Helper function to find previous character boundary
 fn find_prev_char_boundary(s: &str, pos: usize) -> Option<usize> {
     if pos == 0 || pos > s.len() {
@@ -1560,6 +1818,14 @@ fn show_success_message(stdout: &mut io::Stdout) -> Result<()> {
 }
 
 fn show_error_message(stdout: &mut io::Stdout, message: &str) -> Result<()> {
+    show_status_message(stdout, message, Color::Red)
+}
+
+fn show_warning_message(stdout: &mut io::Stdout, message: &str) -> Result<()> {
+    show_status_message(stdout, message, Color::Yellow)
+}
+
+fn show_status_message(stdout: &mut io::Stdout, message: &str, color: Color) -> Result<()> {
     // Try to get terminal size, with fallback
     let (width, height) = terminal::size().unwrap_or((80, 24));
 
@@ -1572,15 +1838,12 @@ fn show_error_message(stdout: &mut io::Stdout, message: &str) -> Result<()> {
     if let Err(e) = execute!(
         stdout,
         cursor::MoveTo(x, y),
-        SetForegroundColor(Color::Red),
+        SetForegroundColor(color),
         Print("⚠ "),
         Print(display_msg),
         ResetColor
     ) {
-        return Err(SniptError::Other(format!(
-            "Failed to show error message: {}",
-            e
-        )));
+        return Err(SniptError::Other(format!("Failed to show message: {}", e)));
     }
 
     if let Err(e) = stdout.flush() {
@@ -1593,3 +1856,127 @@ fn show_error_message(stdout: &mut io::Stdout, message: &str) -> Result<()> {
 fn thread_sleep(ms: u64) {
     std::thread::sleep(std::time::Duration::from_millis(ms));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paste(
+        lines: &[&str],
+        at: (usize, usize),
+        text: &str,
+        limits: EditorLimits,
+    ) -> (Vec<String>, (usize, usize), Option<&'static str>) {
+        let mut snippet: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        let (mut line, mut cursor) = at;
+        let notice = process_paste_buffer(&mut snippet, &mut line, &mut cursor, text, &limits);
+        (snippet, (line, cursor), notice)
+    }
+
+    const ROOMY: EditorLimits = EditorLimits {
+        max_lines: 100,
+        max_line_length: 100,
+    };
+
+    #[test]
+    fn test_paste() {
+        // Pasted lines go between the text before and after the cursor
+        let (snippet, cursor, notice) = paste(&["héllo world", "end"], (0, 5), " big\nnew", ROOMY);
+        assert_eq!(snippet, ["héllo big", "new world", "end"]);
+        assert_eq!(cursor, (1, 3));
+        assert_eq!(notice, None);
+
+        let (snippet, cursor, _) = paste(&["ab"], (0, 1), "xy", ROOMY);
+        assert_eq!(snippet, ["axyb"]);
+        assert_eq!(cursor, (0, 3));
+    }
+
+    #[test]
+    fn test_paste_limits() {
+        // Lines past the limit are dropped, but the rest of the line is kept
+        let limits = EditorLimits {
+            max_lines: 2,
+            ..ROOMY
+        };
+        let (snippet, cursor, notice) = paste(&["a|b"], (0, 2), "1\n2\n3", limits);
+        assert_eq!(snippet, ["a|1", "2b"]);
+        assert_eq!(cursor, (1, 1));
+        assert_eq!(notice, Some(LINE_LIMIT_REACHED));
+
+        // Pasted lines are cut so each line fits
+        let limits = EditorLimits {
+            max_line_length: 5,
+            ..ROOMY
+        };
+        let (snippet, cursor, notice) = paste(&["ab"], (0, 1), "123456\nxyzxyz", limits);
+        assert_eq!(snippet, ["a1234", "xyzxb"]);
+        assert_eq!(cursor, (1, 4));
+        assert_eq!(notice, Some(LENGTH_LIMIT_REACHED));
+    }
+
+    #[test]
+    fn test_invalid_shortcut_reason() {
+        assert_eq!(invalid_shortcut_reason("sig"), None);
+        assert_eq!(invalid_shortcut_reason("my-sig_2"), None);
+        assert!(invalid_shortcut_reason("my sig")
+            .unwrap()
+            .contains("whitespace"));
+        assert!(invalid_shortcut_reason("sig\t").is_some());
+        for c in [trigger_char(), execute_char()] {
+            let reason = invalid_shortcut_reason(&format!("s{}g", c)).unwrap();
+            assert!(reason.contains(&format!("'{}'", c)));
+        }
+    }
+
+    #[test]
+    fn test_overlapping_shortcut() {
+        let snippets: Vec<SnippetEntry> = ["sig", "email-work"]
+            .iter()
+            .map(|shortcut| SnippetEntry::new(shortcut.to_string(), String::new()))
+            .collect();
+
+        // Either shortcut being a prefix of the other overlaps
+        assert_eq!(
+            overlapping_shortcut_in(&snippets, "signature"),
+            Some("sig".to_string())
+        );
+        assert_eq!(
+            overlapping_shortcut_in(&snippets, "email"),
+            Some("email-work".to_string())
+        );
+        // The same shortcut is an update, not an overlap
+        assert_eq!(overlapping_shortcut_in(&snippets, "sig"), None);
+        assert_eq!(overlapping_shortcut_in(&snippets, "si-g"), None);
+        assert_eq!(overlapping_shortcut_in(&[], "sig"), None);
+    }
+
+    #[test]
+    fn test_dedent() {
+        assert_eq!(
+            dedent("    fn a() {\n        b();\n    }"),
+            "fn a() {\n    b();\n}"
+        );
+
+        // Blank lines don't count towards the shared indent and come out empty
+        assert_eq!(dedent("  a\n\n  b\n \n"), "a\n\nb\n\n");
+
+        // Tabs and spaces only match themselves
+        assert_eq!(dedent("\t  a\n\t b"), " a\nb");
+        assert_eq!(dedent("  a\n\tb"), "  a\n\tb");
+
+        // Without a common indent the text is unchanged
+        assert_eq!(dedent("a\n  b"), "a\n  b");
+    }
+
+    #[test]
+    fn test_whitespace_glyphs() {
+        assert_eq!(whitespace_glyphs("a b\tc"), "a·b→c");
+        assert_eq!(whitespace_glyphs("  é\t"), "··é→");
+        // One glyph per character keeps the cursor column in place
+        let line = " \tx  ";
+        assert_eq!(
+            whitespace_glyphs(line).chars().count(),
+            line.chars().count()
+        );
+    }
+}
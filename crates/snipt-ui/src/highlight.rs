@@ -0,0 +1,79 @@
+//! Syntax highlighting for snippet previews.
+//!
+//! Highlighting is purely presentational: the stored snippet content is never modified.
+
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use snipt_core::SnippetEntry;
+use std::sync::OnceLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+const THEME_NAME: &str = "base16-ocean.dark";
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme() -> Option<&'static Theme> {
+    static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
+    THEME_SET
+        .get_or_init(ThemeSet::load_defaults)
+        .themes
+        .get(THEME_NAME)
+}
+
+/// Find the syntax for a snippet from its `language` field, a fenced code
+/// marker (```lang) or a shebang on the first line
+fn detect_syntax(entry: &SnippetEntry) -> Option<&'static SyntaxReference> {
+    let syntaxes = syntax_set();
+
+    if let Some(language) = entry.language.as_deref() {
+        let language = language.trim();
+        if !language.is_empty() {
+            return syntaxes
+                .find_syntax_by_token(language)
+                .or_else(|| syntaxes.find_syntax_by_token(&language.to_lowercase()));
+        }
+    }
+
+    let first_line = entry.snippet.lines().next()?.trim();
+    if let Some(fence_lang) = first_line.strip_prefix("```") {
+        let fence_lang = fence_lang.trim();
+        if !fence_lang.is_empty() {
+            return syntaxes.find_syntax_by_token(fence_lang);
+        }
+    }
+
+    syntaxes.find_syntax_by_first_line(first_line)
+}
+
+/// Highlight a snippet for display, returning `None` when no language is
+/// known or highlighting fails so callers can fall back to plain text
+pub fn highlight_snippet(entry: &SnippetEntry) -> Option<Vec<Line<'static>>> {
+    let syntax = detect_syntax(entry)?;
+    let mut highlighter = HighlightLines::new(syntax, theme()?);
+
+    let mut lines = Vec::new();
+    for line in LinesWithEndings::from(&entry.snippet) {
+        let ranges = highlighter.highlight_line(line, syntax_set()).ok()?;
+        let spans: Vec<Span<'static>> = ranges
+            .into_iter()
+            .map(|(style, text)| {
+                let fg = style.foreground;
+                Span::styled(
+                    text.trim_end_matches(['\n', '\r']).to_string(),
+                    Style::default().fg(Color::Rgb(fg.r, fg.g, fg.b)),
+                )
+            })
+            .collect();
+        lines.push(Line::from(spans));
+    }
+
+    Some(lines)
+}
@@ -1,9 +1,12 @@
 mod common;
 mod dashboard;
 mod editor;
+mod highlight;
+mod picker;
 mod snippet_manager;
 
 // Public API
 pub use dashboard::display_snipt_dashboard;
 pub use editor::{interactive_add, AddResult};
+pub use picker::pick_snippet;
 pub use snippet_manager::display_snippet_manager;
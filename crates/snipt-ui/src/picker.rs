@@ -0,0 +1,246 @@
+//! Minimal fuzzy snippet picker, opened by the daemon's quick switcher hotkey.
+//!
+//! The picker only chooses a snippet; the caller decides what to do with it.
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Terminal,
+};
+use snipt_core::{load_snippets, Result, SnippetEntry};
+use std::io::stdout;
+
+/// Score how well `query` matches a snippet, or `None` if it doesn't match.
+/// Shortcut matches (exact, prefix, substring, then in-order letters) rank
+/// above matches in the snippet body. Lower is better.
+fn match_score(entry: &SnippetEntry, query: &str) -> Option<usize> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let shortcut = entry.shortcut.to_lowercase();
+    if shortcut == query {
+        Some(0)
+    } else if shortcut.starts_with(query) {
+        Some(1)
+    } else if shortcut.contains(query) {
+        Some(2)
+    } else if is_subsequence(query, &shortcut) {
+        Some(3)
+    } else if entry.snippet.to_lowercase().contains(query) {
+        Some(4)
+    } else {
+        None
+    }
+}
+
+/// Check whether all characters of `needle` appear in `haystack` in order
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|h| h == c))
+}
+
+/// Indices of the snippets matching `query`, best match first
+fn filter_entries(entries: &[SnippetEntry], query: &str) -> Vec<usize> {
+    let query = query.to_lowercase();
+    let mut scored: Vec<(usize, usize)> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(i, entry)| match_score(entry, &query).map(|score| (score, i)))
+        .collect();
+    scored.sort();
+    scored.into_iter().map(|(_, i)| i).collect()
+}
+
+/// Show the picker and return the shortcut of the chosen snippet, or `None`
+/// if the user cancelled. Disabled snippets are not offered.
+pub fn pick_snippet() -> Result<Option<String>> {
+    let entries: Vec<SnippetEntry> = load_snippets()?
+        .into_iter()
+        .filter(|entry| entry.enabled)
+        .collect();
+
+    enable_raw_mode()?;
+    execute!(stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+    let result = run_picker(&mut terminal, &entries);
+
+    disable_raw_mode()?;
+    execute!(stdout(), LeaveAlternateScreen)?;
+
+    result
+}
+
+fn run_picker(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    entries: &[SnippetEntry],
+) -> Result<Option<String>> {
+    let mut query = String::new();
+    let mut matches = filter_entries(entries, &query);
+    let mut list_state = ListState::default();
+    list_state.select(Some(0));
+
+    loop {
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(3),
+                    Constraint::Min(3),
+                    Constraint::Length(6),
+                    Constraint::Length(1),
+                ])
+                .split(f.size());
+
+            let input = Paragraph::new(format!("> {}", query)).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Pick a snippet "),
+            );
+            f.render_widget(input, chunks[0]);
+
+            let items: Vec<ListItem> = matches
+                .iter()
+                .map(|&i| {
+                    let entry = &entries[i];
+                    let preview = entry.snippet.lines().next().unwrap_or("");
+                    ListItem::new(Line::from(vec![
+                        Span::styled(
+                            format!("{:15}", entry.shortcut),
+                            Style::default().fg(Color::Cyan),
+                        ),
+                        Span::raw(" "),
+                        Span::styled(preview.to_string(), Style::default().fg(Color::Gray)),
+                    ]))
+                })
+                .collect();
+            let list = List::new(items)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!(" Snippets ({}) ", matches.len())),
+                )
+                .highlight_style(
+                    Style::default()
+                        .bg(Color::DarkGray)
+                        .add_modifier(Modifier::BOLD),
+                );
+            f.render_stateful_widget(list, chunks[1], &mut list_state);
+
+            let preview = list_state
+                .selected()
+                .and_then(|selected| matches.get(selected))
+                .map(|&i| entries[i].snippet.as_str())
+                .unwrap_or("");
+            let preview = Paragraph::new(preview)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title(" Preview "));
+            f.render_widget(preview, chunks[2]);
+
+            let help = Paragraph::new("Type to filter • ↑/↓ select • Enter paste • Esc cancel")
+                .style(Style::default().fg(Color::DarkGray));
+            f.render_widget(help, chunks[3]);
+        })?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        let selected = list_state.selected().unwrap_or(0);
+        match key {
+            KeyEvent {
+                code: KeyCode::Esc, ..
+            }
+            | KeyEvent {
+                code: KeyCode::Char('c'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => return Ok(None),
+            KeyEvent {
+                code: KeyCode::Enter,
+                ..
+            } => {
+                return Ok(matches.get(selected).map(|&i| entries[i].shortcut.clone()));
+            }
+            KeyEvent {
+                code: KeyCode::Up, ..
+            } => list_state.select(Some(selected.saturating_sub(1))),
+            KeyEvent {
+                code: KeyCode::Down,
+                ..
+            } => {
+                if selected + 1 < matches.len() {
+                    list_state.select(Some(selected + 1));
+                }
+            }
+            KeyEvent {
+                code: KeyCode::Backspace,
+                ..
+            } => {
+                query.pop();
+                matches = filter_entries(entries, &query);
+                list_state.select(Some(0));
+            }
+            KeyEvent {
+                code: KeyCode::Char(c),
+                ..
+            } => {
+                query.push(c);
+                matches = filter_entries(entries, &query);
+                list_state.select(Some(0));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<SnippetEntry> {
+        [
+            ("signature", "Best regards"),
+            ("sig", "Regards"),
+            ("mysig", "Cheers"),
+            ("sxixg", "Thanks"),
+            ("addr", "1 Signal Street"),
+        ]
+        .iter()
+        .map(|(shortcut, body)| SnippetEntry::new(shortcut.to_string(), body.to_string()))
+        .collect()
+    }
+
+    #[test]
+    fn test_match_score() {
+        let entries = entries();
+        let score = |i: usize, query: &str| match_score(&entries[i], query);
+        assert_eq!(score(1, "sig"), Some(0));
+        assert_eq!(score(0, "sig"), Some(1));
+        assert_eq!(score(2, "sig"), Some(2));
+        assert_eq!(score(3, "sig"), Some(3));
+        assert_eq!(score(4, "signal"), Some(4));
+        assert_eq!(score(4, "zzz"), None);
+        // Everything matches an empty query
+        assert_eq!(score(4, ""), Some(0));
+    }
+
+    #[test]
+    fn test_filter_entries() {
+        let entries = entries();
+        // Exact, prefix, substring, in-order letters, then body matches
+        assert_eq!(filter_entries(&entries, "SIG"), vec![1, 0, 2, 3, 4]);
+        assert_eq!(filter_entries(&entries, "regards"), vec![0, 1]);
+        assert!(filter_entries(&entries, "nothing").is_empty());
+        // Without a query the order is kept
+        assert_eq!(filter_entries(&entries, ""), vec![0, 1, 2, 3, 4]);
+    }
+}
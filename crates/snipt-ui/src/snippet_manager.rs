@@ -13,15 +13,24 @@ use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Tabs, Wrap},
+    widgets::{block::Title, Block, Borders, Clear, List, ListItem, Paragraph, Tabs, Wrap},
     Frame, Terminal,
 };
-use snipt_core::{delete_snippet, load_snippets, update_snippet, Result, SnippetEntry, SniptError};
+use snipt_core::expansion::{expand_text, text_body};
+use snipt_core::{
+    delete_snippet, duplicate_snippet, history::usage_counts, is_daemon_running, load_snippets,
+    toggle_pin, toggle_snippet, trigger_char, update_snippet, Result, SnippetEntry, SnippetKind,
+    SniptError,
+};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::{self, stdout};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crate::common::show_message;
+use crate::common::{centered_rect, show_message};
+use crate::editor::{interactive_add, AddResult};
+use crate::highlight::highlight_snippet;
 
 #[derive(PartialEq)]
 enum InputMode {
@@ -29,6 +38,7 @@ enum InputMode {
     Filtering,
     Editing,    // Mode for editing a snippet
     Confirming, // Mode for confirming actions (delete)
+    Palette,    // Command palette opened with ':'
 }
 
 enum ConfirmAction {
@@ -36,20 +46,190 @@ enum ConfirmAction {
     // Can add more confirmation actions later
 }
 
+/// Order of the snippet list, cycled with `s`
+#[derive(Clone, Copy, PartialEq)]
+enum SortMode {
+    /// Order the snippets were added in
+    Added,
+    Name,
+    /// Most recently updated first
+    Updated,
+    /// Most expanded first, based on the expansion history
+    Usage,
+}
+
+impl SortMode {
+    fn next(self) -> Self {
+        match self {
+            SortMode::Added => SortMode::Name,
+            SortMode::Name => SortMode::Updated,
+            SortMode::Updated => SortMode::Usage,
+            SortMode::Usage => SortMode::Added,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortMode::Added => "added",
+            SortMode::Name => "name",
+            SortMode::Updated => "updated",
+            SortMode::Usage => "usage",
+        }
+    }
+}
+
+/// How well a snippet matches the search query; better matches sort first
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchRank {
+    /// The shortcut is the query
+    Exact,
+    /// The shortcut starts with the query
+    Prefix,
+    /// The shortcut contains the query
+    Shortcut,
+    /// The description contains the query
+    Description,
+    /// Only the snippet text contains the query
+    Body,
+}
+
+impl MatchRank {
+    /// Rank `entry` against a lowercase query, or `None` if it doesn't match
+    fn of(entry: &SnippetEntry, query: &str) -> Option<Self> {
+        let shortcut = entry.shortcut.to_lowercase();
+        if shortcut == query {
+            Some(MatchRank::Exact)
+        } else if shortcut.starts_with(query) {
+            Some(MatchRank::Prefix)
+        } else if shortcut.contains(query) {
+            Some(MatchRank::Shortcut)
+        } else if entry.description.to_lowercase().contains(query) {
+            Some(MatchRank::Description)
+        } else if entry.snippet.to_lowercase().contains(query) {
+            Some(MatchRank::Body)
+        } else {
+            None
+        }
+    }
+}
+
+/// Action that can be run from the command palette
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PaletteAction {
+    New,
+    Edit,
+    Duplicate,
+    Delete,
+    Toggle,
+    Pin,
+    CopySnippet,
+    CopyShortcut,
+    Preview,
+    Sort,
+    Search,
+}
+
+impl PaletteAction {
+    const ALL: [PaletteAction; 11] = [
+        PaletteAction::New,
+        PaletteAction::Edit,
+        PaletteAction::Duplicate,
+        PaletteAction::Delete,
+        PaletteAction::Toggle,
+        PaletteAction::Pin,
+        PaletteAction::CopySnippet,
+        PaletteAction::CopyShortcut,
+        PaletteAction::Preview,
+        PaletteAction::Sort,
+        PaletteAction::Search,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            PaletteAction::New => "New snippet",
+            PaletteAction::Edit => "Edit snippet",
+            PaletteAction::Duplicate => "Duplicate snippet",
+            PaletteAction::Delete => "Delete snippet",
+            PaletteAction::Toggle => "Enable/disable snippet",
+            PaletteAction::Pin => "Pin/unpin snippet",
+            PaletteAction::CopySnippet => "Copy snippet to clipboard",
+            PaletteAction::CopyShortcut => "Copy shortcut name",
+            PaletteAction::Preview => "Preview expansion",
+            PaletteAction::Sort => "Cycle sort order",
+            PaletteAction::Search => "Search snippets",
+        }
+    }
+
+    /// Key that runs the action directly from the list, if there is one
+    fn key_hint(self) -> &'static str {
+        match self {
+            PaletteAction::New | PaletteAction::Duplicate => "",
+            PaletteAction::Edit => "e",
+            PaletteAction::Delete => "d",
+            PaletteAction::Toggle => "t",
+            PaletteAction::Pin => "p",
+            PaletteAction::CopySnippet => "Enter",
+            PaletteAction::CopyShortcut => "y",
+            PaletteAction::Preview => "v",
+            PaletteAction::Sort => "s",
+            PaletteAction::Search => "/",
+        }
+    }
+
+    /// Whether the label contains the letters of `query` in order, ignoring case
+    fn matches(self, query: &str) -> bool {
+        let mut label = self.label().chars().flat_map(char::to_lowercase);
+        query
+            .chars()
+            .flat_map(char::to_lowercase)
+            .filter(|c| !c.is_whitespace())
+            .all(|q| label.any(|c| c == q))
+    }
+}
+
+/// A line in the snippet list
+#[derive(Debug, PartialEq)]
+enum ListRow {
+    /// Heading of a group, with the number of listed snippets inside it
+    Group {
+        path: String,
+        depth: usize,
+        count: usize,
+    },
+    /// Snippet at this index of `entries`
+    Snippet { index: usize, depth: usize },
+}
+
 struct AppState {
     entries: Vec<SnippetEntry>,
-    selected: usize,
+    selected: usize, // Position in `rows`
     offset: usize,
     search_query: String,
     filtered_indices: Vec<usize>,
+    match_ranks: HashMap<usize, MatchRank>, // Rank of each filtered snippet while searching
+    rows: Vec<ListRow>,                     // Filtered snippets arranged under their groups
+    collapsed: HashSet<String>,             // Paths of collapsed groups
     input_mode: InputMode,
     tab_index: usize,
     edit_buffer: Vec<String>, // Changed to Vec<String> for multiline editing
     edit_cursor_pos: usize,   // Cursor position in the current line
     edit_line: usize,         // Current line being edited
     confirm_action: Option<ConfirmAction>, // Track what we're confirming
+    sort_mode: SortMode,
+    usage_counts: HashMap<String, usize>, // Expansions per shortcut
+    recently_added: Option<String>,       // Shortcut added just before opening, shown with a badge
+    palette_query: String,
+    palette_selected: usize, // Position in `palette_actions()`
+    daemon_running: bool,
+    daemon_checked: Instant,
+    notice: Option<(String, Color, Instant)>, // Shown in place of the key help for a moment
 }
 
+/// How often the snippet manager rechecks whether the daemon is running
+const DAEMON_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+/// How long a notice such as "Saved" stays in the status bar
+const NOTICE_DURATION: Duration = Duration::from_secs(3);
+
 impl AppState {
     fn new(entries: Vec<SnippetEntry>) -> Self {
         let filtered_indices = (0..entries.len()).collect();
@@ -65,58 +245,216 @@ impl AppState {
             offset: 0,
             search_query: String::new(),
             filtered_indices,
+            match_ranks: HashMap::new(),
+            rows: Vec::new(),
+            collapsed: HashSet::new(),
             input_mode: InputMode::Normal,
             tab_index: 0,
             edit_buffer: vec![String::new()],
             edit_cursor_pos: 0,
             edit_line: 0,
             confirm_action: None,
+            sort_mode: SortMode::Added,
+            usage_counts: load_usage_counts(),
+            recently_added: None,
+            palette_query: String::new(),
+            palette_selected: 0,
+            daemon_running: daemon_running(),
+            daemon_checked: Instant::now(),
+            notice: None,
+        }
+    }
+
+    /// Recheck the daemon now and then so the tab bar stays accurate
+    fn refresh_daemon_status(&mut self) {
+        if self.daemon_checked.elapsed() >= DAEMON_CHECK_INTERVAL {
+            self.daemon_running = daemon_running();
+            self.daemon_checked = Instant::now();
         }
     }
 
+    /// Tell the user whether a change just saved is already in use
+    fn notice_saved(&mut self, what: &str) {
+        self.daemon_running = daemon_running();
+        self.daemon_checked = Instant::now();
+        let (text, color) = if self.daemon_running {
+            (
+                format!("{} — the daemon will reload it", what),
+                Color::Green,
+            )
+        } else {
+            (
+                format!("{} — start the daemon ('snipt start') to use it", what),
+                Color::Yellow,
+            )
+        };
+        self.notice = Some((text, color, Instant::now()));
+    }
+
     fn apply_filter(&mut self) {
         if self.search_query.is_empty() {
             self.filtered_indices = (0..self.entries.len()).collect();
+            self.match_ranks.clear();
         } else {
             let query = self.search_query.to_lowercase();
-            self.filtered_indices = self
+            let matches: Vec<(usize, MatchRank)> = self
                 .entries
                 .iter()
                 .enumerate()
-                .filter(|(_, entry)| {
-                    entry.shortcut.to_lowercase().contains(&query)
-                        || entry.snippet.to_lowercase().contains(&query)
-                })
-                .map(|(i, _)| i)
+                .filter_map(|(i, entry)| MatchRank::of(entry, &query).map(|rank| (i, rank)))
                 .collect();
+            self.filtered_indices = matches.iter().map(|&(i, _)| i).collect();
+            self.match_ranks = matches.into_iter().collect();
         }
 
+        self.sort_filtered();
+        self.build_rows();
+
         // Adjust selected index based on filtered results
-        if self.filtered_indices.is_empty() {
+        if self.rows.is_empty() {
             self.selected = 0;
-        } else if self.selected >= self.filtered_indices.len() {
-            self.selected = self.filtered_indices.len() - 1;
+        } else if self.selected >= self.rows.len() {
+            self.selected = self.rows.len() - 1;
+        }
+    }
+
+    /// Arrange the filtered snippets as a tree: ungrouped snippets first, then
+    /// each group heading followed by its snippets and subgroups. Collapsed
+    /// groups hide their contents. While searching the matches are listed
+    /// without groups, best match first.
+    fn build_rows(&mut self) {
+        if !self.search_query.is_empty() {
+            self.rows = self
+                .filtered_indices
+                .iter()
+                .map(|&index| ListRow::Snippet { index, depth: 0 })
+                .collect();
+            return;
+        }
+
+        self.rows = group_rows(&self.entries, &self.filtered_indices, &self.collapsed);
+    }
+
+    /// Collapse the selected group, or move to the enclosing group heading
+    fn collapse_selected(&mut self) {
+        let target = match self.rows.get(self.selected) {
+            Some(ListRow::Group { path, .. }) if !self.collapsed.contains(path) => {
+                self.collapsed.insert(path.clone());
+                Some(path.clone())
+            }
+            Some(ListRow::Group { path, .. }) => path.rsplit_once('/').map(|(p, _)| p.to_string()),
+            Some(ListRow::Snippet { index, .. }) => {
+                let segments = self.entries[*index].group_segments();
+                (!segments.is_empty()).then(|| segments.join("/"))
+            }
+            None => None,
+        };
+
+        self.build_rows();
+        if let Some(target) = target {
+            self.select_group(&target);
+        }
+    }
+
+    /// Expand the selected group
+    fn expand_selected(&mut self) {
+        if let Some(ListRow::Group { path, .. }) = self.rows.get(self.selected) {
+            let path = path.clone();
+            self.collapsed.remove(&path);
+            self.build_rows();
+            self.select_group(&path);
+        }
+    }
+
+    /// Move the selection to a group heading, if it is listed
+    fn select_group(&mut self, group: &str) {
+        if let Some(position) = self
+            .rows
+            .iter()
+            .position(|row| matches!(row, ListRow::Group { path, .. } if path == group))
+        {
+            self.selected = position;
+        }
+    }
+
+    fn sort_filtered(&mut self) {
+        let entries = &self.entries;
+        match self.sort_mode {
+            SortMode::Added => self.filtered_indices.sort_unstable(),
+            SortMode::Name => self.filtered_indices.sort_by(|&a, &b| {
+                entries[a]
+                    .shortcut
+                    .to_lowercase()
+                    .cmp(&entries[b].shortcut.to_lowercase())
+            }),
+            SortMode::Updated => self
+                .filtered_indices
+                .sort_by_key(|&i| Reverse(entries[i].updated_at())),
+            SortMode::Usage => {
+                let counts = &self.usage_counts;
+                let uses = |i: usize| counts.get(&entries[i].shortcut).copied().unwrap_or(0);
+                self.filtered_indices.sort_by_key(|&i| Reverse(uses(i)));
+            }
+        }
+        // Pinned snippets stay on top in every order
+        self.filtered_indices.sort_by_key(|&i| !entries[i].pinned);
+        // While searching, how well a snippet matches comes before all else
+        let ranks = &self.match_ranks;
+        if !ranks.is_empty() {
+            self.filtered_indices.sort_by_key(|i| ranks[i]);
         }
     }
-    fn get_filtered_entry(&self, index: usize) -> Option<&SnippetEntry> {
-        if self.filtered_indices.is_empty() || index >= self.filtered_indices.len() {
-            return None;
+
+    /// Move the selection to the snippet with this shortcut, if it is listed
+    fn select_shortcut(&mut self, shortcut: &str) {
+        // Open the groups around it so it can be shown
+        if let Some(entry) = self.entries.iter().find(|e| e.shortcut == shortcut) {
+            let segments = entry.group_segments();
+            for len in 1..=segments.len() {
+                self.collapsed.remove(&segments[..len].join("/"));
+            }
+            self.build_rows();
+        }
+
+        if let Some(position) = self.rows.iter().position(|row| {
+            matches!(row, ListRow::Snippet { index, .. } if self.entries[*index].shortcut == shortcut)
+        }) {
+            self.selected = position;
         }
+    }
 
-        let actual_index = self.filtered_indices[index];
-        Some(&self.entries[actual_index])
+    /// Switch to the next sort mode, keeping the selected snippet selected
+    fn cycle_sort(&mut self) {
+        self.sort_mode = self.sort_mode.next();
+        self.resort();
+    }
+
+    /// Sort the list again, keeping the selected snippet selected
+    fn resort(&mut self) {
+        let selected = self.get_selected_entry_index();
+        self.sort_filtered();
+        self.build_rows();
+
+        if let Some(actual_index) = selected {
+            if let Some(position) = self.rows.iter().position(
+                |row| matches!(row, ListRow::Snippet { index, .. } if *index == actual_index),
+            ) {
+                self.selected = position;
+            }
+        }
     }
 
     fn get_selected_entry(&self) -> Option<&SnippetEntry> {
-        self.get_filtered_entry(self.selected)
+        self.get_selected_entry_index()
+            .map(|index| &self.entries[index])
     }
 
+    /// Index into `entries` of the selected snippet; `None` on a group heading
     fn get_selected_entry_index(&self) -> Option<usize> {
-        if self.filtered_indices.is_empty() || self.selected >= self.filtered_indices.len() {
-            return None;
+        match self.rows.get(self.selected) {
+            Some(ListRow::Snippet { index, .. }) => Some(*index),
+            _ => None,
         }
-
-        Some(self.filtered_indices[self.selected])
     }
 
     fn get_current_tab(&self) -> &str {
@@ -158,11 +496,54 @@ impl AppState {
 
             // Save to storage
             update_snippet(&shortcut, new_snippet)?;
+            self.notice_saved("Saved");
         }
         self.input_mode = InputMode::Normal;
         Ok(())
     }
 
+    fn toggle_selected(&mut self) -> Result<()> {
+        if let Some(actual_index) = self.get_selected_entry_index() {
+            let enabled = toggle_snippet(&self.entries[actual_index].shortcut)?;
+            self.entries[actual_index].enabled = enabled;
+            self.notice_saved(if enabled { "Enabled" } else { "Disabled" });
+        }
+        Ok(())
+    }
+
+    fn pin_selected(&mut self) -> Result<()> {
+        if let Some(actual_index) = self.get_selected_entry_index() {
+            let pinned = toggle_pin(&self.entries[actual_index].shortcut)?;
+            self.entries[actual_index].pinned = pinned;
+            self.resort();
+        }
+        Ok(())
+    }
+
+    /// Save a copy of the selected snippet and select the copy
+    fn duplicate_selected(&mut self) -> Result<()> {
+        if let Some(actual_index) = self.get_selected_entry_index() {
+            let copy = duplicate_snippet(&self.entries[actual_index].shortcut)?;
+            self.update_entries(load_snippets()?);
+            self.select_shortcut(&copy);
+        }
+        Ok(())
+    }
+
+    fn open_palette(&mut self) {
+        self.palette_query.clear();
+        self.palette_selected = 0;
+        self.input_mode = InputMode::Palette;
+    }
+
+    /// Palette actions matching the typed query
+    fn palette_actions(&self) -> Vec<PaletteAction> {
+        PaletteAction::ALL
+            .into_iter()
+            .filter(|action| action.matches(&self.palette_query))
+            .collect()
+    }
+
     fn start_delete_confirmation(&mut self) {
         if self.get_selected_entry_index().is_some() {
             self.confirm_action = Some(ConfirmAction::Delete);
@@ -178,16 +559,81 @@ impl AppState {
         self.apply_filter();
 
         // Update selection
-        if self.filtered_indices.is_empty() {
+        if self.rows.is_empty() {
             self.selected = 0;
-        } else if self.selected >= self.filtered_indices.len() {
-            self.selected = self.filtered_indices.len() - 1;
+        } else if self.selected >= self.rows.len() {
+            self.selected = self.rows.len() - 1;
+        }
+    }
+}
+
+/// The rows of `build_rows` outside a search: the snippets at `indices` of
+/// `entries`, ungrouped ones first, then each group heading followed by its
+/// snippets and subgroups
+fn group_rows(
+    entries: &[SnippetEntry],
+    indices: &[usize],
+    collapsed: &HashSet<String>,
+) -> Vec<ListRow> {
+    let mut rows = Vec::new();
+    let mut groups: BTreeMap<Vec<String>, Vec<usize>> = BTreeMap::new();
+    for &index in indices {
+        let segments: Vec<String> = entries[index]
+            .group_segments()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        if segments.is_empty() {
+            rows.push(ListRow::Snippet { index, depth: 0 });
+            continue;
         }
+
+        // Every enclosing group gets a heading, even without snippets of its own
+        for len in 1..segments.len() {
+            groups.entry(segments[..len].to_vec()).or_default();
+        }
+        groups.entry(segments).or_default().push(index);
     }
+
+    let is_collapsed = |segments: &[String]| collapsed.contains(&segments.join("/"));
+
+    for (segments, indices) in &groups {
+        // Skip groups inside a collapsed group
+        if (1..segments.len()).any(|len| is_collapsed(&segments[..len])) {
+            continue;
+        }
+
+        let depth = segments.len() - 1;
+        let count = groups
+            .iter()
+            .filter(|(other, _)| other.starts_with(segments))
+            .map(|(_, indices)| indices.len())
+            .sum();
+        rows.push(ListRow::Group {
+            path: segments.join("/"),
+            depth,
+            count,
+        });
+
+        if !is_collapsed(segments) {
+            rows.extend(indices.iter().map(|&index| ListRow::Snippet {
+                index,
+                depth: depth + 1,
+            }));
+        }
+    }
+
+    rows
+}
+
+/// Count how often each shortcut was expanded, according to the history file
+fn load_usage_counts() -> HashMap<String, usize> {
+    usage_counts().unwrap_or_default().into_iter().collect()
 }
 
-/// Display the snippet manager UI
-pub fn display_snippet_manager() -> Result<()> {
+/// Display the snippet manager UI, optionally selecting and marking a
+/// snippet that was just added
+pub fn display_snippet_manager(select: Option<&str>) -> Result<()> {
     let entries = load_snippets().map_err(|e| {
         eprintln!("Failed to load snippets: {}", e);
         e
@@ -200,6 +646,10 @@ pub fn display_snippet_manager() -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
     let mut app_state = AppState::new(entries);
     app_state.apply_filter();
+    if let Some(shortcut) = select {
+        app_state.select_shortcut(shortcut);
+        app_state.recently_added = Some(shortcut.to_string());
+    }
 
     let result = run_ui(&mut terminal, &mut app_state);
 
@@ -254,8 +704,18 @@ fn run_ui(
                     .map(|t| Span::styled(*t, Style::default().fg(Color::White)))
                     .collect();
 
+                let daemon = if state.daemon_running {
+                    Span::styled(" ● daemon running ", Style::default().fg(Color::Green))
+                } else {
+                    Span::styled(" ○ daemon stopped ", Style::default().fg(Color::DarkGray))
+                };
                 let tabs = Tabs::new(titles)
-                    .block(Block::default().borders(Borders::ALL).title(" snipt "))
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title(" snipt ")
+                            .title(Title::from(daemon).alignment(Alignment::Right)),
+                    )
                     .select(state.tab_index)
                     .style(Style::default().fg(Color::White))
                     .highlight_style(
@@ -324,8 +784,8 @@ fn run_ui(
                             .alignment(Alignment::Right);
                         f.render_widget(info, edit_area_chunks[1]);
                     }
-                    InputMode::Confirming => {
-                        // Don't change the filter area during confirmation
+                    InputMode::Confirming | InputMode::Palette => {
+                        // Don't change the filter area while a dialog is open
                     }
                 }
 
@@ -339,6 +799,10 @@ fn run_ui(
                     draw_multiline_editor(f, state, size);
                 }
 
+                if state.input_mode == InputMode::Palette {
+                    render_command_palette(f, state, size);
+                }
+
                 // Render status bar with keyboard shortcuts
                 let status = render_status_bar(state);
                 f.render_widget(status, main_chunks[3]);
@@ -348,6 +812,16 @@ fn run_ui(
             force_render = false;
         }
 
+        state.refresh_daemon_status();
+        if state
+            .notice
+            .as_ref()
+            .is_some_and(|(_, _, shown)| shown.elapsed() >= NOTICE_DURATION)
+        {
+            state.notice = None;
+            force_render = true;
+        }
+
         if should_refresh {
             should_refresh = false;
             state.apply_filter();
@@ -408,6 +882,38 @@ fn run_ui(
                                 state.start_delete_confirmation();
                             }
                         }
+                        KeyEvent {
+                            code: KeyCode::Char('s'),
+                            ..
+                        } => {
+                            if state.tab_index == 0 {
+                                state.cycle_sort();
+                            }
+                        }
+                        KeyEvent {
+                            code: KeyCode::Char('t'),
+                            ..
+                        } => {
+                            if state.tab_index == 0 {
+                                state.toggle_selected()?;
+                            }
+                        }
+                        KeyEvent {
+                            code: KeyCode::Char('p'),
+                            ..
+                        } => {
+                            if state.tab_index == 0 {
+                                state.pin_selected()?;
+                            }
+                        }
+                        KeyEvent {
+                            code: KeyCode::Char(':'),
+                            ..
+                        } => {
+                            if state.tab_index == 0 {
+                                state.open_palette();
+                            }
+                        }
                         _ => {
                             if state.tab_index == 0 {
                                 handle_list_input(
@@ -603,6 +1109,7 @@ fn run_ui(
                                     match load_snippets() {
                                         Ok(entries) => {
                                             state.update_entries(entries);
+                                            state.notice_saved("Moved to the trash");
                                         }
                                         Err(e) => return Err(e),
                                     }
@@ -624,12 +1131,130 @@ fn run_ui(
                         }
                         _ => {}
                     },
+                    InputMode::Palette => match key {
+                        KeyEvent {
+                            code: KeyCode::Esc, ..
+                        } => {
+                            state.input_mode = InputMode::Normal;
+                        }
+                        KeyEvent {
+                            code: KeyCode::Enter,
+                            ..
+                        } => {
+                            let action =
+                                state.palette_actions().get(state.palette_selected).copied();
+                            state.input_mode = InputMode::Normal;
+                            if let Some(action) = action {
+                                run_palette_action(
+                                    terminal,
+                                    state,
+                                    &mut clipboard,
+                                    action,
+                                    &mut should_refresh,
+                                )?;
+                            }
+                        }
+                        KeyEvent {
+                            code: KeyCode::Up, ..
+                        } => {
+                            state.palette_selected = state.palette_selected.saturating_sub(1);
+                        }
+                        KeyEvent {
+                            code: KeyCode::Down,
+                            ..
+                        } => {
+                            if state.palette_selected + 1 < state.palette_actions().len() {
+                                state.palette_selected += 1;
+                            }
+                        }
+                        KeyEvent {
+                            code: KeyCode::Char(c),
+                            ..
+                        } => {
+                            state.palette_query.push(c);
+                            state.palette_selected = 0;
+                        }
+                        KeyEvent {
+                            code: KeyCode::Backspace,
+                            ..
+                        } => {
+                            state.palette_query.pop();
+                            state.palette_selected = 0;
+                        }
+                        _ => {}
+                    },
                 }
             }
         }
     }
 }
 
+/// Run an action chosen in the command palette
+fn run_palette_action(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    state: &mut AppState,
+    clipboard: &mut Clipboard,
+    action: PaletteAction,
+    should_refresh: &mut bool,
+) -> Result<()> {
+    match action {
+        PaletteAction::New => add_snippet(terminal, state)?,
+        PaletteAction::Edit => state.start_editing(),
+        PaletteAction::Duplicate => state.duplicate_selected()?,
+        PaletteAction::Delete => state.start_delete_confirmation(),
+        PaletteAction::Toggle => state.toggle_selected()?,
+        PaletteAction::Pin => state.pin_selected()?,
+        PaletteAction::CopySnippet | PaletteAction::CopyShortcut | PaletteAction::Preview => {
+            // Same as pressing the key in the list, but never on a group heading
+            if state.get_selected_entry_index().is_some() {
+                let code = match action {
+                    PaletteAction::CopySnippet => KeyCode::Enter,
+                    PaletteAction::CopyShortcut => KeyCode::Char('y'),
+                    _ => KeyCode::Char('v'),
+                };
+                handle_list_input(
+                    terminal,
+                    state,
+                    clipboard,
+                    KeyEvent::from(code),
+                    should_refresh,
+                )?;
+            }
+        }
+        PaletteAction::Sort => state.cycle_sort(),
+        PaletteAction::Search => state.input_mode = InputMode::Filtering,
+    }
+    Ok(())
+}
+
+/// Open the add form over the manager and select the new snippet afterwards
+fn add_snippet(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    state: &mut AppState,
+) -> Result<()> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+
+    let result = interactive_add();
+
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    terminal.clear()?;
+
+    match result {
+        AddResult::Added(shortcut) => {
+            state.update_entries(load_snippets()?);
+            state.select_shortcut(&shortcut);
+            state.recently_added = Some(shortcut);
+        }
+        AddResult::Cancelled => {}
+        AddResult::Error(e) => {
+            show_message(terminal, &format!("Error: {}", e), Color::Red, 2000)?;
+        }
+    }
+    Ok(())
+}
+
 fn handle_list_input(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     state: &mut AppState,
@@ -649,21 +1274,39 @@ fn handle_list_input(
             code: KeyCode::Down,
             ..
         } => {
-            if !state.filtered_indices.is_empty()
-                && state.selected < state.filtered_indices.len() - 1
-            {
+            if !state.rows.is_empty() && state.selected < state.rows.len() - 1 {
                 state.selected += 1;
             }
         }
+        KeyEvent {
+            code: KeyCode::Left,
+            ..
+        } => state.collapse_selected(),
+        KeyEvent {
+            code: KeyCode::Right,
+            ..
+        } => state.expand_selected(),
+        KeyEvent {
+            code: KeyCode::Enter,
+            ..
+        } if matches!(state.rows.get(state.selected), Some(ListRow::Group { .. })) => {
+            // Enter on a group heading opens or closes it
+            match state.rows.get(state.selected) {
+                Some(ListRow::Group { path, .. }) if state.collapsed.contains(path) => {
+                    state.expand_selected()
+                }
+                _ => state.collapse_selected(),
+            }
+        }
         KeyEvent {
             code: KeyCode::Enter,
             ..
         } => {
             if let Some(actual_index) = state.get_selected_entry_index() {
-                let content = &state.entries[actual_index].snippet;
+                let content = text_body(&state.entries[actual_index]);
 
                 // Break long text into chunks to avoid clipboard issues
-                match clipboard.set_text(content.to_owned()) {
+                match clipboard.set_text(content) {
                     Ok(_) => {
                         // Show a quick success message
                         terminal.draw(|f| {
@@ -692,6 +1335,42 @@ fn handle_list_input(
                 }
             }
         }
+        KeyEvent {
+            code: KeyCode::Char('y'),
+            ..
+        } => {
+            // Copy the shortcut name rather than the snippet body
+            if let Some(actual_index) = state.get_selected_entry_index() {
+                let shortcut = &state.entries[actual_index].shortcut;
+                match clipboard.set_text(shortcut.to_owned()) {
+                    Ok(_) => {
+                        let message = format!("Copied shortcut '{}' to clipboard", shortcut);
+                        show_message(terminal, &message, Color::Green, 800)?;
+                    }
+                    Err(e) => {
+                        let error_msg = format!("Clipboard error: {}", e);
+                        show_message(terminal, &error_msg, Color::Red, 2000)?;
+                    }
+                }
+            }
+        }
+        KeyEvent {
+            code: KeyCode::Char('v'),
+            ..
+        } => {
+            // Show what typing the shortcut would produce, with dates filled
+            // in and parameters at their defaults
+            if let Some(entry) = state.get_selected_entry() {
+                let (message, color) = match expand_text(entry, &[]) {
+                    Ok(text) => (format!("{}\n\n{}", entry.shortcut, text), Color::White),
+                    Err(e) => (
+                        format!("Can't preview '{}': {}", entry.shortcut, e),
+                        Color::Red,
+                    ),
+                };
+                show_message(terminal, &message, color, 0)?;
+            }
+        }
         KeyEvent {
             code: KeyCode::Char('d'),
             modifiers: KeyModifiers::CONTROL,
@@ -706,6 +1385,7 @@ fn handle_list_input(
                 match load_snippets() {
                     Ok(entries) => {
                         state.update_entries(entries);
+                        state.notice_saved("Moved to the trash");
                         *should_refresh = true;
                     }
                     Err(e) => return Err(e),
@@ -726,7 +1406,7 @@ fn render_snippet_list<B: ratatui::backend::Backend>(
     let max_visible_items = app_height as usize;
 
     //  Handle empty filtered list correctly
-    if state.filtered_indices.is_empty() {
+    if state.rows.is_empty() {
         let list = List::new(vec![ListItem::new("No snippets found")])
             .block(
                 Block::default()
@@ -740,7 +1420,7 @@ fn render_snippet_list<B: ratatui::backend::Backend>(
     }
 
     // Calculate optimal offset
-    let offset = if !state.filtered_indices.is_empty() {
+    let offset = if !state.rows.is_empty() {
         if state.selected >= state.offset + max_visible_items {
             state
                 .selected
@@ -756,16 +1436,39 @@ fn render_snippet_list<B: ratatui::backend::Backend>(
     };
 
     // Calculate visible entries
-    let end_idx = (offset + max_visible_items).min(state.filtered_indices.len());
+    let end_idx = (offset + max_visible_items).min(state.rows.len());
     let visible_range = offset..end_idx;
 
     // Render list items
     let items: Vec<ListItem> = visible_range
         .map(|i| {
-            let entry = state.get_filtered_entry(i).unwrap();
+            let (index, depth) = match &state.rows[i] {
+                ListRow::Group { path, depth, count } => {
+                    let marker = if state.collapsed.contains(path) {
+                        "▸"
+                    } else {
+                        "▾"
+                    };
+                    let name = path.rsplit('/').next().unwrap_or(path);
+                    return ListItem::new(Line::from(Span::styled(
+                        format!("{}{} {} ({})", "  ".repeat(*depth), marker, name, count),
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    )));
+                }
+                ListRow::Snippet { index, depth } => (*index, *depth),
+            };
+            let entry = &state.entries[index];
+            // Disabled snippets stay listed but dimmed
+            let (shortcut_color, snippet_color) = if entry.enabled {
+                (Color::Cyan, Color::White)
+            } else {
+                (Color::DarkGray, Color::DarkGray)
+            };
             let shortcut_styled = Span::styled(
                 format!("{:15}", entry.shortcut),
-                Style::default().fg(Color::Cyan),
+                Style::default().fg(shortcut_color),
             );
 
             // Extract just the first line for preview
@@ -776,9 +1479,40 @@ fn render_snippet_list<B: ratatui::backend::Backend>(
                 preview_content
             };
 
-            let snippet_styled = Span::styled(snippet_preview, Style::default().fg(Color::White));
+            let snippet_styled = Span::styled(snippet_preview, Style::default().fg(snippet_color));
 
-            let line = Line::from(vec![shortcut_styled, Span::raw(" "), snippet_styled]);
+            let mut spans = vec![
+                Span::raw("  ".repeat(depth)),
+                shortcut_styled,
+                Span::raw(" "),
+                snippet_styled,
+            ];
+            if !entry.enabled {
+                spans.push(Span::styled(" off", Style::default().fg(Color::DarkGray)));
+            }
+            if entry.pinned {
+                spans.push(Span::styled(" ★", Style::default().fg(Color::Yellow)));
+            }
+            // Search results are listed without group headings
+            if let Some(group) = entry
+                .group
+                .as_ref()
+                .filter(|_| !state.search_query.is_empty())
+            {
+                spans.push(Span::styled(
+                    format!(" {}", group),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            if state.recently_added.as_deref() == Some(entry.shortcut.as_str()) {
+                spans.push(Span::styled(
+                    " NEW",
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            let line = Line::from(spans);
 
             ListItem::new(line)
         })
@@ -786,12 +1520,17 @@ fn render_snippet_list<B: ratatui::backend::Backend>(
 
     let total_count = state.filtered_indices.len();
     let title = if state.search_query.is_empty() {
-        format!(" Snippets ({}) ", total_count)
+        format!(
+            " Snippets ({}) · by {} ",
+            total_count,
+            state.sort_mode.label()
+        )
     } else {
         format!(
-            " Filtered Snippets ({}/{}) ",
+            " Filtered Snippets ({}/{}) · by relevance, then {} ",
             total_count,
-            state.entries.len()
+            state.entries.len(),
+            state.sort_mode.label()
         )
     };
 
@@ -817,18 +1556,48 @@ fn render_snippet_details<B: ratatui::backend::Backend>(
     state: &AppState,
     area: Rect,
 ) {
-    let selected_entry = if !state.filtered_indices.is_empty() {
-        state.get_filtered_entry(state.selected)
-    } else {
-        None
-    };
+    let selected_entry = state.get_selected_entry();
 
     let block = Block::default().borders(Borders::ALL).title(" Details ");
 
-    if let Some(entry) = selected_entry {
-        let shortcut_line = Line::from(vec![
+    if let Some(ListRow::Group { path, count, .. }) = state.rows.get(state.selected) {
+        let action = if state.collapsed.contains(path) {
+            "→ or Enter to expand"
+        } else {
+            "← or Enter to collapse"
+        };
+        let content = vec![
+            Line::from(vec![
+                Span::styled("Group: ", Style::default().fg(Color::Yellow)),
+                Span::styled(path.as_str(), Style::default().fg(Color::White)),
+            ]),
+            Line::from(vec![
+                Span::styled("Snippets: ", Style::default().fg(Color::Yellow)),
+                Span::styled(count.to_string(), Style::default().fg(Color::Green)),
+            ]),
+            Line::from(""),
+            Line::from(Span::styled(action, Style::default().fg(Color::DarkGray))),
+        ];
+        f.render_widget(Paragraph::new(Text::from(content)).block(block), area);
+    } else if let Some(entry) = selected_entry {
+        let mut shortcut_spans = vec![
             Span::styled("Shortcut: ", Style::default().fg(Color::Yellow)),
             Span::styled(&entry.shortcut, Style::default().fg(Color::White)),
+        ];
+        if let Some(trigger) = entry.trigger {
+            shortcut_spans.push(Span::styled(
+                format!(" (typed as {}{})", trigger, entry.shortcut),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        let shortcut_line = Line::from(shortcut_spans);
+
+        let created_line = Line::from(vec![
+            Span::styled("Created: ", Style::default().fg(Color::Yellow)),
+            Span::styled(
+                entry.formatted_created_time(),
+                Style::default().fg(Color::Green),
+            ),
         ]);
 
         let timestamp_line = Line::from(vec![
@@ -836,22 +1605,88 @@ fn render_snippet_details<B: ratatui::backend::Backend>(
             Span::styled(entry.formatted_time(), Style::default().fg(Color::Green)),
         ]);
 
+        let status_line = Line::from(vec![
+            Span::styled("Status: ", Style::default().fg(Color::Yellow)),
+            if entry.enabled {
+                Span::styled("enabled", Style::default().fg(Color::Green))
+            } else {
+                Span::styled(
+                    "disabled (press t to enable)",
+                    Style::default().fg(Color::Red),
+                )
+            },
+        ]);
+
+        let length_line = Line::from(vec![
+            Span::styled("Length: ", Style::default().fg(Color::Yellow)),
+            Span::styled(
+                format!(
+                    "{} chars · {} words · {} lines",
+                    entry.snippet.chars().count(),
+                    entry.snippet.split_whitespace().count(),
+                    entry.snippet.lines().count()
+                ),
+                Style::default().fg(Color::White),
+            ),
+        ]);
+
         let snippet_label = Span::styled("Snippet:", Style::default().fg(Color::Yellow));
 
         // Create text to display multiline snippet with proper indentation
-        let mut content = vec![
-            shortcut_line,
+        let mut content = vec![shortcut_line];
+        if !entry.description.is_empty() {
+            content.push(Line::from(vec![
+                Span::styled("Description: ", Style::default().fg(Color::Yellow)),
+                Span::styled(
+                    entry.description.as_str(),
+                    Style::default().fg(Color::White),
+                ),
+            ]));
+        }
+        if let Some(group) = &entry.group {
+            content.push(Line::from(vec![
+                Span::styled("Group: ", Style::default().fg(Color::Yellow)),
+                Span::styled(group.as_str(), Style::default().fg(Color::White)),
+            ]));
+        }
+        if !entry.tags.is_empty() {
+            content.push(Line::from(vec![
+                Span::styled("Tags: ", Style::default().fg(Color::Yellow)),
+                Span::styled(entry.tags.join(", "), Style::default().fg(Color::Cyan)),
+            ]));
+        }
+        match entry.kind {
+            SnippetKind::Text => {}
+            SnippetKind::Link => content.push(Line::from(vec![
+                Span::styled("Kind: ", Style::default().fg(Color::Yellow)),
+                Span::styled("link ", Style::default().fg(Color::White)),
+                Span::styled(entry.markdown_link(), Style::default().fg(Color::DarkGray)),
+            ])),
+            kind => content.push(Line::from(vec![
+                Span::styled("Kind: ", Style::default().fg(Color::Yellow)),
+                Span::styled(kind.name(), Style::default().fg(Color::White)),
+            ])),
+        }
+        content.extend([
+            created_line,
             timestamp_line,
+            status_line,
+            length_line,
             Line::from(""),
             Line::from(snippet_label),
-        ];
+        ]);
 
-        // Split the snippet content by newlines and preserve indentation
-        for line in entry.snippet.lines() {
-            content.push(Line::from(Span::styled(
-                line,
-                Style::default().fg(Color::White),
-            )));
+        // Highlight code snippets, falling back to plain text with preserved indentation
+        match highlight_snippet(entry) {
+            Some(lines) => content.extend(lines),
+            None => {
+                for line in entry.snippet.lines() {
+                    content.push(Line::from(Span::styled(
+                        line,
+                        Style::default().fg(Color::White),
+                    )));
+                }
+            }
         }
 
         // Calculate how many lines we can show in the available space
@@ -996,6 +1831,14 @@ fn render_help_screen<B: ratatui::backend::Backend>(f: &mut Frame<B>, area: Rect
                 Span::styled("  ↑/↓", Style::default().fg(Color::Green)),
                 Span::raw(": Navigate through snippets"),
             ]),
+            Line::from(vec![
+                Span::styled("  ←/→", Style::default().fg(Color::Green)),
+                Span::raw(": Collapse/expand a group"),
+            ]),
+            Line::from(vec![
+                Span::styled("  :", Style::default().fg(Color::Green)),
+                Span::raw(": Open the command palette"),
+            ]),
             Line::from(vec![
                 Span::styled("  Tab", Style::default().fg(Color::Green)),
                 Span::raw(": Switch between tabs"),
@@ -1012,6 +1855,14 @@ fn render_help_screen<B: ratatui::backend::Backend>(f: &mut Frame<B>, area: Rect
                 Span::styled("  Enter", Style::default().fg(Color::Green)),
                 Span::raw(": Copy snippet to clipboard"),
             ]),
+            Line::from(vec![
+                Span::styled("  y", Style::default().fg(Color::Green)),
+                Span::raw(": Copy shortcut name to clipboard"),
+            ]),
+            Line::from(vec![
+                Span::styled("  v", Style::default().fg(Color::Green)),
+                Span::raw(": Preview what the snippet expands to"),
+            ]),
             Line::from(vec![
                             Span::styled("  e", Style::default().fg(Color::Green)),
                             Span::raw(": Edit selected snippet"),
@@ -1020,10 +1871,22 @@ fn render_help_screen<B: ratatui::backend::Backend>(f: &mut Frame<B>, area: Rect
                             Span::styled("  d", Style::default().fg(Color::Green)),
                             Span::raw(": Delete selected snippet"),
                         ]),
+                        Line::from(vec![
+                            Span::styled("  t", Style::default().fg(Color::Green)),
+                            Span::raw(": Enable or disable selected snippet"),
+                        ]),
+                        Line::from(vec![
+                            Span::styled("  p", Style::default().fg(Color::Green)),
+                            Span::raw(": Pin or unpin selected snippet to the top of the list"),
+                        ]),
                         Line::from(vec![
                             Span::styled("  /", Style::default().fg(Color::Green)),
                             Span::raw(": Search snippets"),
                         ]),
+                        Line::from(vec![
+                            Span::styled("  s", Style::default().fg(Color::Green)),
+                            Span::raw(": Cycle sort order (added, name, updated, usage)"),
+                        ]),
                         Line::from(vec![
                             Span::styled("  Esc/q", Style::default().fg(Color::Green)),
                             Span::raw(": Exit"),
@@ -1032,7 +1895,7 @@ fn render_help_screen<B: ratatui::backend::Backend>(f: &mut Frame<B>, area: Rect
                         Line::from(vec![
                             Span::styled("Usage Tips", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
                         ]),
-                        Line::from("• snipt expands text starting with the special character ':' followed by your shortcut."),
+                        Line::from(format!("• snipt expands text starting with the special character '{}' followed by your shortcut.", trigger_char())),
                         Line::from("• Add new snippets with: snipt add --shortcut <name> --snippet <text>"),
                         Line::from("• Or interactively with: snipt new"),
                         Line::from("• Start the daemon with: snipt start"),
@@ -1066,7 +1929,7 @@ fn render_confirmation_dialog<B: ratatui::backend::Backend>(
         Some(ConfirmAction::Delete) => {
             if let Some(actual_index) = state.get_selected_entry_index() {
                 let shortcut = &state.entries[actual_index].shortcut;
-                format!("Delete snippet '{}' (y/n)?", shortcut)
+                format!("Move snippet '{}' to the trash (y/n)?", shortcut)
             } else {
                 "Delete selected snippet (y/n)?".to_string()
             }
@@ -1101,17 +1964,89 @@ fn render_confirmation_dialog<B: ratatui::backend::Backend>(
     f.render_widget(text, dialog_rect);
 }
 
+fn render_command_palette<B: ratatui::backend::Backend>(
+    f: &mut Frame<B>,
+    state: &AppState,
+    size: Rect,
+) {
+    let area = centered_rect(50, 50, size);
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Commands ")
+        .style(Style::default().bg(Color::Black));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1)])
+        .split(inner);
+
+    let input = Paragraph::new(format!(": {}", state.palette_query))
+        .style(Style::default().fg(Color::Yellow));
+    f.render_widget(input, chunks[0]);
+
+    let actions = state.palette_actions();
+    if actions.is_empty() {
+        let empty =
+            Paragraph::new("No matching commands").style(Style::default().fg(Color::DarkGray));
+        f.render_widget(empty, chunks[1]);
+        return;
+    }
+
+    let label_width = chunks[1].width.saturating_sub(10) as usize;
+    let items: Vec<ListItem> = actions
+        .iter()
+        .map(|action| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:width$}", action.label(), width = label_width),
+                    Style::default().fg(Color::White),
+                ),
+                Span::styled(action.key_hint(), Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("► ");
+
+    f.render_stateful_widget(
+        list,
+        chunks[1],
+        &mut ratatui::widgets::ListState::default().with_selected(Some(state.palette_selected)),
+    );
+}
+
+fn daemon_running() -> bool {
+    matches!(is_daemon_running(), Ok(Some(_)))
+}
+
 fn render_status_bar(state: &AppState) -> Paragraph<'static> {
+    if let Some((text, color, _)) = &state.notice {
+        return Paragraph::new(text.clone())
+            .style(Style::default().fg(*color))
+            .alignment(Alignment::Center);
+    }
+
     let help_text = match state.get_current_tab() {
         "Snippets" => match state.input_mode {
             InputMode::Normal => {
-                "↑↓:Navigate | Enter:Copy | e:Edit | d:Delete | /:Search | Tab:Switch | Esc/q:Exit"
+                "↑↓:Navigate | Enter:Copy | y:Copy name | v:Preview | e:Edit | d:Delete | t:Toggle | p:Pin | /:Search | s:Sort | ::Commands | Tab:Switch | Esc/q:Exit"
             }
             InputMode::Filtering => "Enter:Apply Filter | Esc:Cancel",
             InputMode::Editing => {
                 "Ctrl+w:Save | Enter:New Line | Tab:Indent | ↑↓:Navigate Lines | Esc:Cancel"
             }
             InputMode::Confirming => "y:Yes | n/Esc:No",
+            InputMode::Palette => "↑↓:Select | Enter:Run | Type to filter | Esc:Close",
         },
         "Help" => "Tab:Switch | Esc/q:Exit",
         _ => "",
@@ -1200,3 +2135,100 @@ fn show_empty_ui(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Resul
     event::read()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grouped(shortcut: &str, group: Option<&str>) -> SnippetEntry {
+        SnippetEntry {
+            group: group.map(str::to_string),
+            ..SnippetEntry::new(shortcut.to_string(), String::new())
+        }
+    }
+
+    fn group(path: &str, depth: usize, count: usize) -> ListRow {
+        ListRow::Group {
+            path: path.to_string(),
+            depth,
+            count,
+        }
+    }
+
+    fn snippet(index: usize, depth: usize) -> ListRow {
+        ListRow::Snippet { index, depth }
+    }
+
+    #[test]
+    fn test_palette_action_matches() {
+        // Letters match in order, ignoring case and spaces
+        assert!(PaletteAction::Duplicate.matches("dup"));
+        assert!(PaletteAction::Duplicate.matches("DUP SNIP"));
+        assert!(PaletteAction::CopyShortcut.matches("cpsh"));
+        assert!(PaletteAction::Pin.matches(""));
+        assert!(!PaletteAction::Pin.matches("xyz"));
+        assert!(!PaletteAction::Delete.matches("deletex"));
+
+        let matching: Vec<_> = PaletteAction::ALL
+            .into_iter()
+            .filter(|action| action.matches("copy"))
+            .collect();
+        assert_eq!(
+            matching,
+            [PaletteAction::CopySnippet, PaletteAction::CopyShortcut]
+        );
+    }
+
+    #[test]
+    fn test_group_rows() {
+        let entries = vec![
+            grouped("plain", None),
+            grouped("deep", Some("a/b/c")),
+            grouped("top", Some("a")),
+            grouped("gaps", Some("x//y/")),
+        ];
+        let indices = [0, 1, 2, 3];
+
+        // Enclosing groups get headings and count the snippets below them
+        assert_eq!(
+            group_rows(&entries, &indices, &HashSet::new()),
+            vec![
+                snippet(0, 0),
+                group("a", 0, 2),
+                snippet(2, 1),
+                group("a/b", 1, 1),
+                group("a/b/c", 2, 1),
+                snippet(1, 3),
+                group("x", 0, 1),
+                group("x/y", 1, 1),
+                snippet(3, 2),
+            ]
+        );
+
+        // A collapsed group keeps its heading and hides everything inside
+        let collapsed = HashSet::from(["a/b".to_string()]);
+        assert_eq!(
+            group_rows(&entries, &indices, &collapsed),
+            vec![
+                snippet(0, 0),
+                group("a", 0, 2),
+                snippet(2, 1),
+                group("a/b", 1, 1),
+                group("x", 0, 1),
+                group("x/y", 1, 1),
+                snippet(3, 2),
+            ]
+        );
+
+        // Only the filtered snippets are listed
+        assert_eq!(
+            group_rows(&entries, &[1], &HashSet::new()),
+            vec![
+                group("a", 0, 1),
+                group("a/b", 1, 1),
+                group("a/b/c", 2, 1),
+                snippet(1, 3),
+            ]
+        );
+    }
+}